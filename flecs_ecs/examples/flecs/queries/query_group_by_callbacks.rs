@@ -1,9 +1,6 @@
 use crate::z_ignore_test_common::*;
 
 use flecs_ecs::prelude::*;
-use flecs_ecs::sys;
-use std::ffi::c_void;
-use std::sync::Mutex;
 
 #[derive(Debug, Component)]
 pub struct Position {
@@ -14,8 +11,7 @@ pub struct Position {
 #[derive(Component)]
 pub struct Tag;
 
-static GROUP_COUNTER: Mutex<i32> = Mutex::new(0);
-
+// Data that is associated with each group
 struct GroupCtx {
     counter: i32,
 }
@@ -32,46 +28,6 @@ pub struct Third;
 #[derive(Component)]
 pub struct Group;
 
-// callbacks need to be extern "C" to be callable from C
-extern "C" fn callback_group_create(
-    world: *mut sys::ecs_world_t,
-    group_id: u64,
-    _group_by_ctx: *mut c_void,
-) -> *mut c_void {
-    let world_ref = unsafe { WorldRef::from_ptr(world) };
-    println!(
-        "Group created: {:?}",
-        world_ref.world().entity_from_id(group_id).name()
-    );
-
-    println!();
-
-    let mut counter = GROUP_COUNTER.lock().unwrap();
-    *counter += 1;
-
-    // Return data that will be associated with the group
-    let ctx = Box::new(GroupCtx { counter: *counter });
-
-    Box::into_raw(ctx) as *mut std::ffi::c_void // Cast to make sure function type matches
-}
-
-// callbacks need to be extern "C" to be callable from C
-extern "C" fn callback_group_delete(
-    world: *mut sys::ecs_world_t,
-    group_id: u64,
-    _ctx: *mut c_void,
-    _group_by_ctx: *mut c_void,
-) {
-    let world_ref = unsafe { WorldRef::from_ptr(world) };
-    println!(
-        "Group deleted: {:?}",
-        world_ref.world().entity_from_id(group_id).name()
-    );
-
-    // if you have any data associated with the group, you need to free it
-    // or use the callback group_by_ctx where you pass a context to the callback
-}
-
 fn main() {
     let world = World::new();
 
@@ -80,14 +36,26 @@ fn main() {
     world.component::<Second>();
     world.component::<Third>();
 
+    let mut counter = 0;
+
     // Grouped query
     let query = world
         .query::<(&Position,)>()
         .group_by::<Group>()
-        // Callback invoked when a new group is created
-        .on_group_create(Some(callback_group_create))
-        // Callback invoked when a group is deleted
-        .on_group_delete(Some(callback_group_delete))
+        // Callback invoked when a new group is created, the returned value
+        // is the context of the group and is owned by the query
+        .on_group_create_with(move |world, group_id| {
+            println!("Group created: {:?}", world.entity_from_id(group_id).name());
+            println!();
+
+            counter += 1;
+            GroupCtx { counter }
+        })
+        // Callback invoked when a group is deleted, receives the context of the group.
+        // The context is dropped at the end of the callback.
+        .on_group_delete_with(|world, group_id, _ctx: GroupCtx| {
+            println!("Group deleted: {:?}", world.entity_from_id(group_id).name());
+        })
         .build();
 
     // Create entities in 6 different tables with 3 group ids
@@ -138,7 +106,7 @@ fn main() {
 
     query.run_iter(|it, (pos,)| {
         let group = world.entity_from_id(it.group_id());
-        let ctx = unsafe { &*(query.group_context(group) as *mut GroupCtx) };
+        let ctx = query.group_context::<GroupCtx>(group).unwrap();
         println!(
             "Group: {:?} - Table: [{:?}] - Counter: {}",
             group.path().unwrap(),
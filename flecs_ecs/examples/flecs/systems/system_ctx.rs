@@ -3,7 +3,6 @@ use crate::z_ignore_test_common::*;
 use flecs_ecs::prelude::*;
 use std::{
     borrow::Borrow,
-    ffi::c_void,
     time::{SystemTime, UNIX_EPOCH},
 };
 
@@ -48,13 +47,14 @@ fn main() {
     // following example shows how to pass a custom query into a system for a simple
     // collision detection example.
 
-    let mut query_collide = world.new_query::<(&Position, &Radius)>();
+    // The system takes ownership of the query, which is dropped together with the system.
+    let query_collide = world.new_query::<(&Position, &Radius)>();
 
     let sys = world
         .system::<(&Position, &Radius)>()
-        .set_context(&mut query_collide as *mut Query<(&Position, &Radius)> as *mut c_void)
-        .each_iter(|mut it, index, (p1, r1)| {
-            let query = unsafe { it.context::<Query<(&Position, &Radius)>>() };
+        .set_typed_context(query_collide)
+        .each_iter(|it, index, (p1, r1)| {
+            let query = it.context::<Query<(&Position, &Radius)>>().unwrap();
             let e1 = it.entity(index);
 
             query.each_entity(|e2, (p2, r2)| {
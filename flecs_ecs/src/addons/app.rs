@@ -2,15 +2,109 @@
 
 use std::ffi::c_void;
 
+use crate::core::callback_panic::{catch_callback_panic, resume_callback_panic};
 use crate::core::*;
 use crate::sys;
 
+type AppInitFn<'a> = Box<dyn FnOnce(&World) + 'a>;
+type AppFrameFn<'a> = Box<dyn FnMut(&World) + 'a>;
+type AppRunFn<'a> = Box<dyn FnOnce(&mut AppRunner) -> i32 + 'a>;
+
 /// Application interface.
 ///
 /// These are typically constructed via [`World::app()`]
 pub struct App<'a> {
     world: WorldRef<'a>,
     desc: sys::ecs_app_desc_t,
+    callbacks: AppCallbacks<'a>,
+}
+
+/// The closures of an app, which are invoked by the run action of the app.
+#[derive(Default)]
+pub(crate) struct AppCallbacks<'a> {
+    on_init: Option<AppInitFn<'a>>,
+    on_frame: Option<AppFrameFn<'a>>,
+    on_shutdown: Option<AppInitFn<'a>>,
+    run_action: Option<AppRunFn<'a>>,
+}
+
+impl AppCallbacks<'_> {
+    fn is_empty(&self) -> bool {
+        self.on_init.is_none()
+            && self.on_frame.is_none()
+            && self.on_shutdown.is_none()
+            && self.run_action.is_none()
+    }
+}
+
+/// Drives the frames of an app from a custom run action.
+///
+/// # See also
+///
+/// * [`App::set_run_action()`]
+pub struct AppRunner<'a> {
+    world: WorldRef<'a>,
+    desc: *const sys::ecs_app_desc_t,
+    on_frame: Option<&'a mut dyn FnMut(&World)>,
+    result: i32,
+}
+
+impl<'a> AppRunner<'a> {
+    /// Runs a single frame of the app: invokes the [`App::on_frame()`] closure and progresses
+    /// the world.
+    ///
+    /// # Returns
+    ///
+    /// Whether the app should keep running, `false` once [`World::quit()`] was called or a
+    /// system failed.
+    pub fn frame(&mut self) -> bool {
+        if self.result != 0 {
+            return false;
+        }
+        if let Some(on_frame) = self.on_frame.as_mut() {
+            on_frame(&self.world);
+        }
+        let delta_time = unsafe { (*self.desc).delta_time };
+        // a frame returns 1 when the app should quit, like the frame action of flecs
+        self.result = !crate::addons::system::progress(self.world, delta_time) as i32;
+        resume_callback_panic(self.world.world_ptr());
+        self.result == 0
+    }
+
+    /// Runs frames until the app quits, or until the number of frames set with
+    /// [`App::set_frames()`] has run.
+    ///
+    /// This is what an app does when it doesn't have a custom run action.
+    pub fn run_frames(&mut self) -> i32 {
+        let frames = unsafe { (*self.desc).frames };
+        if frames > 0 {
+            for _ in 0..frames {
+                if !self.frame() {
+                    break;
+                }
+            }
+        } else {
+            while self.frame() {}
+        }
+        self.exit_code()
+    }
+
+    /// The exit code of the app, 0 while it is running and after a normal exit.
+    pub fn exit_code(&self) -> i32 {
+        // a frame returns 1 for a normal exit
+        if self.result == 1 {
+            0
+        } else {
+            self.result
+        }
+    }
+}
+
+impl<'a> WorldProvider<'a> for AppRunner<'a> {
+    #[inline(always)]
+    fn world(&self) -> WorldRef<'a> {
+        self.world
+    }
 }
 
 impl<'a> App<'a> {
@@ -29,6 +123,7 @@ impl<'a> App<'a> {
         let mut obj = Self {
             world: world.world(),
             desc: sys::ecs_app_desc_t::default(),
+            callbacks: AppCallbacks::default(),
         };
 
         let stats = unsafe { sys::ecs_get_world_info(obj.world.ptr_mut()) };
@@ -134,7 +229,6 @@ impl<'a> App<'a> {
         self
     }
 
-    // TODO change this to FnMut(&mut World) -> cint
     /// Set the application init action.
     ///
     /// # Arguments
@@ -143,6 +237,7 @@ impl<'a> App<'a> {
     ///
     /// # See also
     ///
+    /// * [`App::on_init()`]
     /// * C++ API: `app_builder::init`
     #[doc(alias = "app_builder::init")]
     pub fn init(&mut self, value: sys::ecs_app_init_action_t) -> &mut Self {
@@ -150,8 +245,152 @@ impl<'a> App<'a> {
         self
     }
 
+    /// Set a closure that is invoked once before the main loop starts.
+    ///
+    /// # Arguments
+    ///
+    /// * `func` - The closure, which receives the world of the app.
+    ///
+    /// # See also
+    ///
+    /// * [`App::on_frame()`]
+    /// * [`App::on_shutdown()`]
+    pub fn on_init(&mut self, func: impl FnOnce(&World) + 'a) -> &mut Self {
+        self.callbacks.on_init = Some(Box::new(func));
+        self
+    }
+
+    /// Set a closure that is invoked every frame, before the world progresses.
+    ///
+    /// # Arguments
+    ///
+    /// * `func` - The closure, which receives the world of the app.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use flecs_ecs::prelude::*;
+    ///
+    /// let world = World::new();
+    /// let mut frames = 0;
+    ///
+    /// world
+    ///     .app()
+    ///     .on_frame(|world| {
+    ///         frames += 1;
+    ///         if frames == 3 {
+    ///             world.quit();
+    ///         }
+    ///     })
+    ///     .run();
+    ///
+    /// assert_eq!(frames, 3);
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// * [`App::on_init()`]
+    /// * [`App::on_shutdown()`]
+    pub fn on_frame(&mut self, func: impl FnMut(&World) + 'a) -> &mut Self {
+        self.callbacks.on_frame = Some(Box::new(func));
+        self
+    }
+
+    /// Set a closure that is invoked once after the main loop has finished.
+    ///
+    /// # Arguments
+    ///
+    /// * `func` - The closure, which receives the world of the app.
+    ///
+    /// # See also
+    ///
+    /// * [`App::on_init()`]
+    /// * [`App::on_frame()`]
+    pub fn on_shutdown(&mut self, func: impl FnOnce(&World) + 'a) -> &mut Self {
+        self.callbacks.on_shutdown = Some(Box::new(func));
+        self
+    }
+
+    /// Set a custom run action, which replaces the main loop of the app.
+    ///
+    /// The run action is invoked after the [`App::on_init()`] closure and decides when frames
+    /// run, which allows the app to be driven by an external event loop. It returns the exit
+    /// code of the app.
+    ///
+    /// # Arguments
+    ///
+    /// * `func` - The run action, which runs frames with [`AppRunner::frame()`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use flecs_ecs::prelude::*;
+    ///
+    /// let world = World::new();
+    /// let events = ["resize", "redraw", "redraw"];
+    /// let mut frames = 0;
+    ///
+    /// let code = world
+    ///     .app()
+    ///     .on_frame(|_| frames += 1)
+    ///     .set_run_action(|runner| {
+    ///         // an external event loop that only runs a frame when it needs to redraw
+    ///         for event in events {
+    ///             if event == "redraw" && !runner.frame() {
+    ///                 break;
+    ///             }
+    ///         }
+    ///         runner.exit_code()
+    ///     })
+    ///     .run();
+    ///
+    /// assert_eq!(code, 0);
+    /// assert_eq!(frames, 2);
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// * C API: `ecs_app_set_run_action`
+    pub fn set_run_action(&mut self, func: impl FnOnce(&mut AppRunner) -> i32 + 'a) -> &mut Self {
+        self.callbacks.run_action = Some(Box::new(func));
+        self
+    }
+
     /// Set the application context.
     ///
+    /// The value is stored as the typed context of the world, so it can be accessed with
+    /// [`World::typed_context()`] from anywhere the world is available. It is dropped when the
+    /// context is replaced or when the world is destroyed.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The context.
+    ///
+    /// # See also
+    ///
+    /// * [`App::typed_context()`]
+    /// * C++ API: `app_builder::ctx`
+    #[doc(alias = "app_builder::ctx")]
+    pub fn set_typed_context<C: Send + Sync + 'static>(&mut self, value: C) -> &mut Self {
+        self.world.world_ctx_mut().context = Some(Box::new(value));
+        self
+    }
+
+    /// Get the application context.
+    ///
+    /// # Returns
+    ///
+    /// The context, or `None` if no context is set or if it is not of type `C`.
+    ///
+    /// # See also
+    ///
+    /// * [`App::set_typed_context()`]
+    pub fn typed_context<C: Send + Sync + 'static>(&self) -> Option<&C> {
+        self.world.world_ctx().context.as_ref()?.downcast_ref::<C>()
+    }
+
+    /// Set the raw application context pointer, which is passed to custom run and frame actions.
+    ///
     /// # Arguments
     ///
     /// * `ctx` - The context.
@@ -160,41 +399,128 @@ impl<'a> App<'a> {
     ///
     /// * C++ API: `app_builder::ctx`
     #[doc(alias = "app_builder::ctx")]
-    pub fn context(&mut self, ctx: *mut c_void) -> &mut Self {
+    pub fn set_context_ptr(&mut self, ctx: *mut c_void) -> &mut Self {
         self.desc.ctx = ctx;
         self
     }
 
+    /// Set the raw application context pointer.
+    ///
+    /// # See also
+    ///
+    /// * [`App::set_context_ptr()`]
+    #[deprecated(note = "renamed to `set_context_ptr`, use `set_typed_context` for an owned value")]
+    pub fn context(&mut self, ctx: *mut c_void) -> &mut Self {
+        self.set_context_ptr(ctx)
+    }
+
     /// Run application. This will run the application with the parameters specified in desc.
     /// After the application quits ([`World::quit()`] is called) this will return.
     /// If a custom run action is set, it will be invoked by this operation.
     /// The default run action calls the frame action in a loop until it returns a non-zero value.
     ///
+    /// When closures are set with [`App::on_init()`], [`App::on_frame()`], [`App::on_shutdown()`]
+    /// or [`App::set_run_action()`], the run action of flecs is replaced by one that invokes them
+    /// and progresses the world with [`World::progress_time()`]. As flecs has a single run action
+    /// for the process, it stays installed and is also used by apps that run afterwards. Apps
+    /// without closures run through the run and frame actions of flecs otherwise.
+    ///
+    /// Unlike the C++ API, the world isn't destroyed when the app quits. It is destroyed when the
+    /// last [`World`] that references it is dropped.
+    ///
     /// # Returns
     ///
     /// The exit code of the application.
     ///
+    /// # Panics
+    ///
+    /// Panics if closures are set while another run action was registered through the C API.
+    ///
     /// # See also
     ///
     /// * C++ API: `app_builder::run`
     #[doc(alias = "app_builder::run")]
     pub fn run(&mut self) -> i32 {
         let world_ptr = self.world.ptr_mut();
-        let result = unsafe { sys::ecs_app_run(world_ptr, &mut self.desc) };
-        unsafe {
-            if sys::ecs_should_quit(world_ptr) {
-                // Only free world if quit flag is set. This ensures that we won't
-                // try to cleanup the world if the app is used in an environment
-                // that takes over the main loop, like with emscripten.
-                if sys::flecs_poly_release_(world_ptr as *mut c_void) == 0 {
-                    sys::ecs_fini(world_ptr);
-                }
-            }
+        let callbacks = std::mem::take(&mut self.callbacks);
+        if callbacks.is_empty() {
+            let result = unsafe { sys::ecs_app_run(world_ptr, &mut self.desc) };
+            resume_callback_panic(world_ptr);
+            return result;
+        }
+
+        // fails if the application registered its own run action through the C API
+        if unsafe { sys::ecs_app_set_run_action(Some(run_action)) } != 0 {
+            panic!(
+                "{}: app closures can't be used when another run action is set",
+                FlecsErrorCode::InvalidOperation
+            );
         }
+
+        // the run action is shared by all worlds, it finds the closures through the world
+        let mut callbacks: AppCallbacks<'static> = unsafe { std::mem::transmute(callbacks) };
+        let world_ctx = self.world.world_ctx_mut();
+        let prev = std::mem::replace(&mut world_ctx.app_callbacks, &mut callbacks);
+        let result = unsafe { sys::ecs_app_run(world_ptr, &mut self.desc) };
+        self.world.world_ctx_mut().app_callbacks = prev;
+        resume_callback_panic(world_ptr);
         result
     }
 }
 
+/// Run action of apps, invoked by `ecs_app_run`.
+///
+/// Runs the same way as the default run action, and additionally invokes the closures of the
+/// app. Panics of systems are resumed after the frame in which they happened, which ends the
+/// main loop.
+unsafe extern "C" fn run_action(
+    world: *mut sys::ecs_world_t,
+    desc: *mut sys::ecs_app_desc_t,
+) -> i32 {
+    let world_ref = unsafe { WorldRef::from_ptr(world) };
+    let callbacks = std::mem::replace(
+        &mut world_ref.world_ctx_mut().app_callbacks,
+        std::ptr::null_mut(),
+    );
+    let mut no_callbacks = AppCallbacks::default();
+    let callbacks = match unsafe { callbacks.as_mut() } {
+        Some(callbacks) => callbacks,
+        None => &mut no_callbacks,
+    };
+
+    catch_callback_panic(world, || {
+        if let Some(init) = unsafe { (*desc).init } {
+            unsafe { init(world) };
+        }
+        if let Some(on_init) = callbacks.on_init.take() {
+            on_init(&world_ref);
+        }
+
+        let mut runner = AppRunner {
+            world: world_ref,
+            desc,
+            on_frame: callbacks
+                .on_frame
+                .as_mut()
+                .map(|on_frame| &mut **on_frame as &mut dyn FnMut(&World)),
+            result: 0,
+        };
+        let result = match callbacks.run_action.take() {
+            Some(run_action) => run_action(&mut runner),
+            None => runner.run_frames(),
+        };
+
+        // ensure the quit flag is set, like the default run action does
+        world_ref.quit();
+
+        if let Some(on_shutdown) = callbacks.on_shutdown.take() {
+            on_shutdown(&world_ref);
+        }
+        result
+    })
+    .unwrap_or(-1)
+}
+
 /// App mixin implementation
 impl World {
     /// Create a new app.
@@ -0,0 +1,242 @@
+//! Buffered events that are read by systems instead of observers.
+//!
+//! Events that are sent with an [`EventWriter`] are stored in the [`Events`] singleton of the
+//! event type. At the start of every frame, the events sent during the previous frame become
+//! readable, so that any system can process all events of a frame in the phase of its choice
+//! with an [`EventReader`].
+//!
+//! # Example
+//!
+//! ```
+//! use flecs_ecs::prelude::*;
+//! use flecs_ecs::addons::event_queue::EventReader;
+//!
+//! #[derive(Component)]
+//! struct DamageEvent {
+//!     target: Entity,
+//!     amount: i32,
+//! }
+//!
+//! #[derive(Component)]
+//! struct Health(i32);
+//!
+//! let world = World::new();
+//! world.add_events::<DamageEvent>();
+//!
+//! let player = world.entity().set(Health(100));
+//!
+//! // send an event from outside of a system
+//! world.event_writer::<DamageEvent>().send(DamageEvent {
+//!     target: player.id(),
+//!     amount: 10,
+//! });
+//!
+//! let mut reader = EventReader::<DamageEvent>::new();
+//! world
+//!     .system::<()>()
+//!     .kind::<flecs::pipeline::PostUpdate>()
+//!     .run(move |mut it| {
+//!         while it.next() {}
+//!         let world = it.world();
+//!         reader.read(world, |damage| {
+//!             world
+//!                 .entity_from_id(damage.target)
+//!                 .get::<&mut Health>(|health| health.0 -= damage.amount);
+//!         });
+//!     });
+//!
+//! world.progress();
+//! player.get::<&Health>(|health| assert_eq!(health.0, 90));
+//! ```
+
+use std::marker::PhantomData;
+use std::sync::Mutex;
+
+use crate::core::*;
+use crate::sys;
+
+/// Double buffered queue of events of type `T`, stored as a singleton.
+///
+/// Events that are sent during a frame are readable during the next frame, after which they
+/// are dropped. Sending events only requires shared access, so that events can be sent from
+/// systems that run on multiple threads.
+///
+/// The queue is created by [`World::add_events()`].
+#[derive(flecs_ecs_derive::Component)]
+pub struct Events<T: Send + Sync + 'static> {
+    // events sent during the current frame
+    pending: Mutex<Vec<T>>,
+    // events sent during the previous frame
+    readable: Vec<T>,
+    frame: u64,
+}
+
+impl<T: Send + Sync + 'static> Default for Events<T> {
+    fn default() -> Self {
+        Self {
+            pending: Mutex::new(Vec::new()),
+            readable: Vec::new(),
+            frame: 0,
+        }
+    }
+}
+
+impl<T: Send + Sync + 'static> Events<T> {
+    /// Send an event, which is readable after the next call to [`Events::update()`].
+    pub fn send(&self, event: T) {
+        self.pending
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(event);
+    }
+
+    /// Iterate the readable events.
+    pub fn iter(&self) -> std::slice::Iter<'_, T> {
+        self.readable.iter()
+    }
+
+    /// Returns the number of readable events.
+    pub fn len(&self) -> usize {
+        self.readable.len()
+    }
+
+    /// Returns `true` if there are no readable events.
+    pub fn is_empty(&self) -> bool {
+        self.readable.is_empty()
+    }
+
+    /// Make the events that were sent since the last update readable, and drop the events that
+    /// were readable until now.
+    ///
+    /// This is done at the start of every frame by the system registered with
+    /// [`World::add_events()`].
+    pub fn update(&mut self) {
+        let pending = self.pending.get_mut().unwrap_or_else(|e| e.into_inner());
+        self.readable = std::mem::take(pending);
+        self.frame += 1;
+    }
+}
+
+/// Sends events of type `T` to the [`Events`] singleton of the world.
+///
+/// # See also
+///
+/// * [`World::event_writer()`]
+pub struct EventWriter<'a, T: Send + Sync + 'static> {
+    world: WorldRef<'a>,
+    _marker: PhantomData<T>,
+}
+
+impl<'a, T: Send + Sync + 'static> EventWriter<'a, T> {
+    /// Send an event, which can be read by systems during the next frame.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the events of type `T` were not registered with [`World::add_events()`].
+    pub fn send(&self, event: T) {
+        ecs_assert!(
+            self.world.has::<Events<T>>(),
+            FlecsErrorCode::InvalidOperation,
+            "events of type '{}' are sent before they are registered with `World::add_events()`",
+            std::any::type_name::<T>()
+        );
+        self.world
+            .get::<&Events<T>>(|events: &Events<T>| events.send(event));
+    }
+}
+
+/// Reads the events of type `T` that were sent during the previous frame.
+///
+/// Every reader reads the events of a frame once, so a system that runs multiple times
+/// during a frame doesn't process the same events twice.
+/// Readers are typically moved into the callback of a system.
+pub struct EventReader<T: Send + Sync + 'static> {
+    last_read: u64,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T: Send + Sync + 'static> Default for EventReader<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Send + Sync + 'static> EventReader<T> {
+    /// Create a new reader.
+    pub fn new() -> Self {
+        Self {
+            last_read: 0,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Invoke `func` for each event that this reader hasn't read yet.
+    ///
+    /// # Arguments
+    ///
+    /// * `world` - The world (or stage) to read the events from.
+    /// * `func` - The function to invoke for each event.
+    ///
+    /// # Returns
+    ///
+    /// The number of events that were read.
+    pub fn read<'a>(&mut self, world: impl WorldProvider<'a>, mut func: impl FnMut(&T)) -> usize {
+        let world = world.world();
+        if !world.has::<Events<T>>() {
+            return 0;
+        }
+
+        let mut count = 0;
+        world.get::<&Events<T>>(|events: &Events<T>| {
+            if events.frame == self.last_read {
+                return;
+            }
+            self.last_read = events.frame;
+            for event in events.iter() {
+                func(event);
+            }
+            count = events.len();
+        });
+        count
+    }
+}
+
+impl World {
+    /// Register a buffered event queue for events of type `T`.
+    ///
+    /// This adds the [`Events<T>`] singleton and a system that makes the events of the
+    /// previous frame readable at the start of each frame, before the systems of the
+    /// `OnLoad` phase run. Registering the same event type multiple times has no effect.
+    ///
+    /// # See also
+    ///
+    /// * [`World::event_writer()`]
+    /// * [`EventReader`]
+    pub fn add_events<T: Send + Sync + 'static>(&self) -> &Self {
+        if self.has::<Events<T>>() {
+            return self;
+        }
+
+        self.set(Events::<T>::default());
+        self.system::<()>()
+            .kind_id(unsafe { sys::EcsPreFrame })
+            .run(|mut it| {
+                while it.next() {}
+                it.world()
+                    .get::<&mut Events<T>>(|events: &mut Events<T>| events.update());
+            });
+        self
+    }
+
+    /// Create an [`EventWriter`] for events of type `T`.
+    ///
+    /// # See also
+    ///
+    /// * [`World::add_events()`]
+    pub fn event_writer<T: Send + Sync + 'static>(&self) -> EventWriter<'_, T> {
+        EventWriter {
+            world: self.world(),
+            _marker: PhantomData,
+        }
+    }
+}
@@ -4,6 +4,9 @@ pub mod app;
 #[cfg(feature = "flecs_doc")]
 pub mod doc;
 
+#[cfg(feature = "flecs_pipeline")]
+pub mod event_queue;
+
 #[cfg(feature = "flecs_module")]
 pub mod module;
 
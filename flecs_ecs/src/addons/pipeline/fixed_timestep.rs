@@ -0,0 +1,221 @@
+//! Fixed timestep simulation for systems that must advance by a constant amount of time.
+
+use crate::core::*;
+use crate::sys;
+
+/// Phase for systems that run with a fixed timestep.
+///
+/// Systems in this phase don't run in the regular pipeline. When a fixed timestep is set
+/// with [`World::set_fixed_timestep()`], [`World::progress()`] runs them zero or more times per
+/// frame, once for every fixed step that fits in the time that passed, before the systems of
+/// the regular pipeline run. The delta time of the systems is the fixed step.
+#[derive(flecs_ecs_derive::Component)]
+pub struct FixedUpdate;
+
+impl World {
+    /// Set the fixed timestep at which the systems of the [`FixedUpdate`] phase run.
+    ///
+    /// Every frame, the time that passed is added to an accumulator. The [`FixedUpdate`] systems
+    /// run once for every `step` in the accumulator, after which the remaining time is left in
+    /// the accumulator for the next frame. Systems can interpolate between the last two fixed
+    /// steps with [`TableIter::interpolation_alpha()`].
+    ///
+    /// # Arguments
+    ///
+    /// * `step` - The time that a fixed step advances the simulation, a value of 0 disables
+    ///   the fixed timestep.
+    /// * `max_steps` - The maximum number of fixed steps per frame. If a frame takes longer,
+    ///   the time that can't be caught up with is dropped.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use flecs_ecs::prelude::*;
+    /// use flecs_ecs::addons::pipeline::FixedUpdate;
+    ///
+    /// let world = World::new();
+    /// world.set_fixed_timestep(1.0 / 60.0, 4);
+    ///
+    /// world
+    ///     .system::<()>()
+    ///     .kind::<FixedUpdate>()
+    ///     .run(|mut it| {
+    ///         while it.next() {}
+    ///         println!("physics step: {}", it.delta_time());
+    ///     });
+    ///
+    /// world
+    ///     .system::<()>()
+    ///     .kind::<flecs::pipeline::OnStore>()
+    ///     .run(|mut it| {
+    ///         while it.next() {}
+    ///         println!("render, alpha: {}", it.interpolation_alpha());
+    ///     });
+    ///
+    /// // runs two physics steps, the remaining time is interpolated
+    /// world.progress_time(2.5 / 60.0);
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// * [`World::get_fixed_timestep()`]
+    /// * [`World::get_fixed_timestep_alpha()`]
+    pub fn set_fixed_timestep(&self, step: FTime, max_steps: i32) {
+        ecs_assert!(
+            max_steps > 0,
+            FlecsErrorCode::InvalidParameter,
+            "maximum number of fixed steps must be larger than 0"
+        );
+
+        if step <= 0.0 {
+            if let Some(fixed) = self.world_ctx_mut().fixed_timestep.take() {
+                unsafe { sys::ecs_delete(self.world_ptr_mut(), *fixed.pipeline) };
+            }
+            return;
+        }
+
+        if self.world_ctx().fixed_timestep.is_none() {
+            let pipeline = self
+                .pipeline()
+                .with::<flecs::system::System>()
+                .with_first::<flecs::DependsOn>(FixedUpdate::id(self))
+                .without::<flecs::Disabled>()
+                .up_type::<flecs::DependsOn>()
+                .without::<flecs::Disabled>()
+                .up_type::<flecs::ChildOf>()
+                .build()
+                .id();
+            self.world_ctx_mut().fixed_timestep = Some(FixedTimestep {
+                pipeline,
+                ..Default::default()
+            });
+        }
+
+        if let Some(fixed) = self.world_ctx_mut().fixed_timestep.as_mut() {
+            fixed.step = step;
+            fixed.max_steps = max_steps;
+        }
+    }
+
+    /// Get the fixed timestep, or 0 if no fixed timestep is set.
+    ///
+    /// # See also
+    ///
+    /// * [`World::set_fixed_timestep()`]
+    pub fn get_fixed_timestep(&self) -> FTime {
+        fixed_timestep(self.world()).map_or(0.0, |fixed| fixed.step)
+    }
+
+    /// Get the progress towards the next fixed step, between 0 and 1.
+    ///
+    /// Systems that run after the [`FixedUpdate`] systems can use it to interpolate between the
+    /// states of the last two fixed steps. Returns 0 if no fixed timestep is set.
+    ///
+    /// # See also
+    ///
+    /// * [`World::set_fixed_timestep()`]
+    /// * [`TableIter::interpolation_alpha()`]
+    pub fn get_fixed_timestep_alpha(&self) -> FTime {
+        fixed_timestep(self.world()).map_or(0.0, |fixed| fixed.alpha)
+    }
+
+    /// Get the number of fixed steps that ran in the last frame.
+    ///
+    /// # See also
+    ///
+    /// * [`World::set_fixed_timestep()`]
+    pub fn get_fixed_timestep_steps(&self) -> i32 {
+        fixed_timestep(self.world()).map_or(0, |fixed| fixed.steps)
+    }
+}
+
+impl<const IS_RUN: bool, P> TableIter<'_, IS_RUN, P>
+where
+    P: ComponentId,
+{
+    /// Return the progress towards the next fixed step, between 0 and 1.
+    ///
+    /// Systems that render the state of [`FixedUpdate`] systems can use it to interpolate
+    /// between the last two fixed steps. Returns 0 if no fixed timestep is set.
+    ///
+    /// # See also
+    ///
+    /// * [`World::set_fixed_timestep()`]
+    pub fn interpolation_alpha(&self) -> FTime {
+        self.world().get_fixed_timestep_alpha()
+    }
+}
+
+/// The fixed timestep of a world, see [`World::set_fixed_timestep()`].
+#[derive(Default, Clone, Copy)]
+pub(crate) struct FixedTimestep {
+    /// The pipeline that runs the fixed update systems.
+    pipeline: Entity,
+    step: FTime,
+    max_steps: i32,
+    /// The number of fixed steps that ran in the last frame.
+    steps: i32,
+    /// The time that hasn't been simulated by fixed steps yet.
+    accumulator: FTime,
+    alpha: FTime,
+}
+
+fn fixed_timestep(world: WorldRef) -> Option<FixedTimestep> {
+    world.world_ctx().fixed_timestep
+}
+
+/// Runs the fixed update pipeline once for every fixed step that fits in the time that was
+/// accumulated, up to the maximum number of steps per frame. Invoked by `World::progress`
+/// before the pipeline of the world runs.
+pub(crate) fn run_fixed_timestep(world: WorldRef, delta_time: FTime) {
+    let world_ptr = world.world_ptr_mut();
+    let Some(fixed) = world.world_ctx_mut().fixed_timestep.as_mut() else {
+        return;
+    };
+    fixed.steps = 0;
+    if !unsafe { sys::ecs_is_alive(world_ptr, *fixed.pipeline) } {
+        return;
+    }
+    fixed.accumulator += delta_time;
+
+    // systems of the fixed update pipeline may change the timestep, so it is looked up again
+    // after every step
+    while let Some(fixed) = world.world_ctx_mut().fixed_timestep.as_mut() {
+        if fixed.accumulator < fixed.step {
+            fixed.alpha = fixed.accumulator / fixed.step;
+            break;
+        }
+        if fixed.steps == fixed.max_steps {
+            // drop the time that can't be caught up with, so that a slow frame doesn't cause
+            // the next frames to run even more steps
+            fixed.accumulator -= fixed.step * (fixed.accumulator / fixed.step).trunc();
+            fixed.alpha = fixed.accumulator / fixed.step;
+            break;
+        }
+
+        let (pipeline, step) = (fixed.pipeline, fixed.step);
+        reset_last_frame(world);
+        unsafe { sys::ecs_run_pipeline(world_ptr, *pipeline, step) };
+
+        if let Some(fixed) = world.world_ctx_mut().fixed_timestep.as_mut() {
+            fixed.accumulator -= step;
+            fixed.steps += 1;
+        }
+    }
+}
+
+/// Systems that ran in a previous step of this frame must run again, also if the pipeline is
+/// rebuilt while running, which resumes after the systems that ran in the current frame.
+fn reset_last_frame(world: WorldRef) {
+    let world_ptr = world.world_ptr_mut();
+    let phase = ecs_dependson(FixedUpdate::id(world));
+    let mut it = unsafe { sys::ecs_each_id(world_ptr, phase) };
+    while unsafe { sys::ecs_each_next(&mut it) } {
+        for i in 0..it.count as usize {
+            let system = unsafe { sys::ecs_system_get(world_ptr, *it.entities.add(i)) };
+            if !system.is_null() {
+                unsafe { (*(system as *mut sys::ecs_system_t)).last_frame = 0 };
+            }
+        }
+    }
+}
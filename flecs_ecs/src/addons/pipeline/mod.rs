@@ -1,7 +1,15 @@
 //! Pipelines order and schedule systems for execution.
 
+mod fixed_timestep;
 mod pipeline_builder;
+mod pipeline_runner;
+mod schedule;
+mod system_profiler;
+pub use fixed_timestep::*;
 pub use pipeline_builder::*;
+pub use pipeline_runner::*;
+pub use schedule::*;
+pub use system_profiler::*;
 
 use std::ops::{Deref, DerefMut};
 
@@ -93,3 +101,24 @@ where
         self.entity
     }
 }
+
+/// Runs a pipeline with `run`, after evaluating the run conditions of systems. Systems that the
+/// pipeline runs are reported to the system profiler while it is enabled, and are skipped after
+/// a system error stopped the pipeline.
+pub(crate) fn run_pipeline<R>(world: WorldRef, run: impl FnOnce() -> R) -> R {
+    crate::addons::system::eval_run_conditions(world);
+    let system_count = unsafe { sys::ecs_count_id(world.world_ptr(), flecs::system::System::ID) };
+    if system_count as usize != world.world_ctx().system_runs.len() {
+        crate::addons::system::install_system_runs(world);
+    }
+    let running = std::mem::replace(&mut world.world_ctx_mut().running_pipeline, true);
+    if !running {
+        world
+            .world_ctx()
+            .pipeline_stopped
+            .store(false, std::sync::atomic::Ordering::Relaxed);
+    }
+    let result = run();
+    world.world_ctx_mut().running_pipeline = running;
+    result
+}
@@ -158,6 +158,7 @@ where
     type BuiltType = Pipeline<'a, T>;
 
     fn build(&mut self) -> Self::BuiltType {
+        crate::addons::system::order_pipeline_query(&mut self.desc.query);
         let pipeline = Pipeline::<T>::new(self.world(), self.desc);
         for string_parts in self.term_builder.str_ptrs_to_free.iter() {
             unsafe {
@@ -0,0 +1,294 @@
+//! Running a subset of the systems of a pipeline, for example to step through its phases.
+
+use std::collections::HashMap;
+
+use super::PipelineSchedule;
+use crate::core::callback_panic::resume_callback_panic;
+use crate::core::*;
+use crate::sys;
+
+/// Runs the systems of a pipeline that are in a range of phases or that have a tag, as
+/// returned by [`World::pipeline_runner()`].
+///
+/// Phases are ordered by the depth of their [`flecs::DependsOn`] relationships, in the same way
+/// as the pipeline orders them. The selected systems run one after the other in the order of the
+/// pipeline schedule, the commands that a system enqueues are merged before the next system
+/// runs.
+///
+/// # Example
+///
+/// ```
+/// use flecs_ecs::prelude::*;
+///
+/// #[derive(Component)]
+/// struct Editor;
+///
+/// let world = World::new();
+///
+/// world
+///     .system_named::<()>("Input")
+///     .kind::<flecs::pipeline::OnLoad>()
+///     .run(|mut it| while it.next() {})
+///     .add::<Editor>();
+///
+/// world
+///     .system_named::<()>("Physics")
+///     .kind::<flecs::pipeline::OnUpdate>()
+///     .run(|mut it| while it.next() {});
+///
+/// world
+///     .system_named::<()>("Gizmos")
+///     .kind::<flecs::pipeline::OnStore>()
+///     .run(|mut it| while it.next() {})
+///     .add::<Editor>();
+///
+/// // while the game is paused, only run the systems of the editor
+/// let runner = world.pipeline_runner_id(0).with_tag::<Editor>().clone();
+/// assert_eq!(runner.systems().len(), 2);
+/// runner.run(1.0 / 60.0);
+///
+/// // run everything up to and including the OnUpdate phase
+/// world
+///     .pipeline_runner_id(0)
+///     .until::<flecs::pipeline::OnUpdate>()
+///     .run(1.0 / 60.0);
+/// ```
+#[derive(Clone)]
+pub struct PipelineRunner<'a> {
+    world: WorldRef<'a>,
+    pipeline: Entity,
+    from: Option<Entity>,
+    until: Option<Entity>,
+    phase: Option<Entity>,
+    tags: Vec<Entity>,
+}
+
+impl<'a> PipelineRunner<'a> {
+    pub(crate) fn new(world: WorldRef<'a>, pipeline: Entity) -> Self {
+        let pipeline = if pipeline == 0 {
+            world.get_pipeline().id()
+        } else {
+            pipeline
+        };
+        Self {
+            world,
+            pipeline,
+            from: None,
+            until: None,
+            phase: None,
+            tags: Vec::new(),
+        }
+    }
+
+    /// Only run the systems of the phase and of the phases that run after it.
+    ///
+    /// # Arguments
+    ///
+    /// * `phase` - The first phase to run.
+    pub fn from_id(&mut self, phase: impl Into<Entity>) -> &mut Self {
+        self.from = Some(phase.into());
+        self
+    }
+
+    /// Only run the systems of the phase and of the phases that run after it.
+    ///
+    /// # Type Parameters
+    ///
+    /// * `Phase` - The first phase to run.
+    pub fn from<Phase: ComponentId>(&mut self) -> &mut Self {
+        self.from_id(Phase::id(self.world))
+    }
+
+    /// Only run the systems of the phase and of the phases that run before it.
+    ///
+    /// # Arguments
+    ///
+    /// * `phase` - The last phase to run.
+    pub fn until_id(&mut self, phase: impl Into<Entity>) -> &mut Self {
+        self.until = Some(phase.into());
+        self
+    }
+
+    /// Only run the systems of the phase and of the phases that run before it.
+    ///
+    /// # Type Parameters
+    ///
+    /// * `Phase` - The last phase to run.
+    pub fn until<Phase: ComponentId>(&mut self) -> &mut Self {
+        self.until_id(Phase::id(self.world))
+    }
+
+    /// Only run the systems of a single phase. Unlike a range of phases, this excludes other
+    /// phases with the same depth, whose systems run interleaved with those of the phase.
+    ///
+    /// # Arguments
+    ///
+    /// * `phase` - The phase to run.
+    pub fn phase_id(&mut self, phase: impl Into<Entity>) -> &mut Self {
+        self.phase = Some(phase.into());
+        self
+    }
+
+    /// Only run the systems of a single phase.
+    ///
+    /// # Type Parameters
+    ///
+    /// * `Phase` - The phase to run.
+    pub fn phase<Phase: ComponentId>(&mut self) -> &mut Self {
+        self.phase_id(Phase::id(self.world))
+    }
+
+    /// Only run the systems that have the tag. If multiple tags are added, a system must have
+    /// all of them.
+    ///
+    /// # Arguments
+    ///
+    /// * `tag` - The tag, which can also be a pair.
+    pub fn with_tag_id(&mut self, tag: impl IntoId) -> &mut Self {
+        self.tags.push(Entity(*tag.into()));
+        self
+    }
+
+    /// Only run the systems that have the tag. If multiple tags are added, a system must have
+    /// all of them.
+    ///
+    /// # Type Parameters
+    ///
+    /// * `T` - The tag.
+    pub fn with_tag<T: ComponentOrPairId>(&mut self) -> &mut Self {
+        self.with_tag_id(T::get_id(self.world))
+    }
+
+    /// Returns the systems that [`PipelineRunner::run()`] runs, in the order in which they run.
+    pub fn systems(&self) -> Vec<EntityView<'a>> {
+        let schedule = PipelineSchedule::new(self.world, self.pipeline);
+        let mut depths = PhaseDepths::default();
+        schedule
+            .systems()
+            .filter(|system| self.tags.iter().all(|tag| system.has_id(*tag)))
+            .filter(|system| {
+                if self.from.is_none() && self.until.is_none() && self.phase.is_none() {
+                    return true;
+                }
+                let Some(phase) = system.target::<flecs::DependsOn>(0) else {
+                    return false;
+                };
+                let phase = phase.id();
+                let depth = depths.depth(self.world, phase);
+                self.phase.map_or(true, |only| only == phase)
+                    && self
+                        .from
+                        .map_or(true, |from| depths.depth(self.world, from) <= depth)
+                    && self
+                        .until
+                        .map_or(true, |until| depth <= depths.depth(self.world, until))
+            })
+            .collect()
+    }
+
+    /// Returns the phases of the systems that [`PipelineRunner::run()`] runs, in the order in
+    /// which they run. Passing each phase to [`PipelineRunner::phase_id()`] steps through the
+    /// pipeline one phase at a time.
+    pub fn phases(&self) -> Vec<EntityView<'a>> {
+        let mut phases: Vec<EntityView<'a>> = Vec::new();
+        for system in self.systems() {
+            if let Some(phase) = system.target::<flecs::DependsOn>(0) {
+                if !phases.contains(&phase) {
+                    phases.push(phase);
+                }
+            }
+        }
+        phases
+    }
+
+    /// Run the selected systems.
+    ///
+    /// The systems run as a frame of the world, like [`World::progress_time()`]: the world time
+    /// and frame statistics are updated, run conditions are evaluated, the system profiler is
+    /// invoked and a system error can stop the remaining systems. Unlike `progress`, startup
+    /// systems and the fixed update pipeline don't run, and systems run on the main thread.
+    ///
+    /// # Arguments
+    ///
+    /// * `delta_time` - The delta time that is passed to the systems. Pass 0.0 for automatic
+    ///   time measurement.
+    pub fn run(&self, delta_time: FTime) {
+        ecs_assert!(
+            !self.world.is_readonly(),
+            FlecsErrorCode::InvalidOperation,
+            "cannot run a pipeline while the world is in readonly mode"
+        );
+
+        let systems = self.systems();
+        let world_ptr = self.world.world_ptr_mut();
+        crate::addons::pipeline::run_pipeline(self.world, || unsafe {
+            let delta_time = sys::ecs_frame_begin(world_ptr, delta_time);
+            // a system that panicked skips the callbacks of the systems after it, the panic is
+            // resumed once the frame has ended
+            for system in systems {
+                sys::ecs_run(world_ptr, *system.id(), delta_time, std::ptr::null_mut());
+            }
+            sys::ecs_frame_end(world_ptr);
+        });
+        resume_callback_panic(self.world.world_ptr());
+        if let Some(error) = self.world.take_system_error() {
+            log_error(&error.to_string());
+        }
+    }
+}
+
+/// Caches the depth of phases, which is the length of their longest chain of
+/// [`flecs::DependsOn`] relationships. Phases with a lower depth run first.
+#[derive(Default)]
+struct PhaseDepths {
+    depths: HashMap<Entity, usize>,
+}
+
+impl PhaseDepths {
+    fn depth(&mut self, world: WorldRef, phase: Entity) -> usize {
+        if let Some(depth) = self.depths.get(&phase) {
+            return *depth;
+        }
+        // insert a placeholder first, so that a cycle can't recurse forever
+        self.depths.insert(phase, 0);
+        let mut depth = 0;
+        EntityView::new_from(world, phase).each_target::<flecs::DependsOn>(|target| {
+            depth = depth.max(self.depth(world, target.id()) + 1);
+        });
+        self.depths.insert(phase, depth);
+        depth
+    }
+}
+
+impl World {
+    /// Create a runner for a subset of the systems of a pipeline.
+    ///
+    /// # Type Parameters
+    ///
+    /// * `Pipeline` - The type associated with the pipeline.
+    ///
+    /// # See also
+    ///
+    /// * [`World::pipeline_runner_id()`]
+    /// * [`World::run_pipeline()`]
+    pub fn pipeline_runner<Pipeline>(&self) -> PipelineRunner<'_>
+    where
+        Pipeline: ComponentType<Struct> + ComponentId,
+    {
+        PipelineRunner::new(self.world(), Entity(Pipeline::id(self)))
+    }
+
+    /// Create a runner for a subset of the systems of a pipeline.
+    ///
+    /// # Arguments
+    ///
+    /// * `pipeline` - The pipeline, 0 for the current pipeline.
+    ///
+    /// # See also
+    ///
+    /// * [`World::pipeline_runner()`]
+    /// * [`World::run_pipeline_id()`]
+    pub fn pipeline_runner_id(&self, pipeline: impl Into<Entity>) -> PipelineRunner<'_> {
+        PipelineRunner::new(self.world(), pipeline.into())
+    }
+}
@@ -0,0 +1,264 @@
+//! Inspection of the schedule that a pipeline computed for its systems.
+
+use std::fmt::Write;
+
+use super::Pipeline;
+use crate::core::*;
+use crate::sys;
+
+/// The schedule of a pipeline, as returned by [`Pipeline::schedule()`].
+///
+/// A pipeline runs its systems in batches. Between two batches, the pipeline inserts a sync
+/// point at which the commands that the systems of the previous batch enqueued are merged.
+/// Sync points are inserted when a system reads a component that a previous system of the same
+/// batch wrote to the command queue, or when systems switch between running on multiple threads
+/// or in immediate mode. A merge also happens at the end of the frame, which isn't part of the
+/// schedule.
+#[derive(Debug, Clone)]
+pub struct PipelineSchedule<'a> {
+    world: WorldRef<'a>,
+    batches: Vec<ScheduleBatch<'a>>,
+}
+
+/// A list of systems that run without a sync point in between.
+#[derive(Debug, Clone)]
+pub struct ScheduleBatch<'a> {
+    /// The systems of the batch, in the order in which they run.
+    pub systems: Vec<EntityView<'a>>,
+    /// Whether the systems run on multiple threads.
+    pub multi_threaded: bool,
+    /// Whether the systems run in immediate mode, in which case every system is followed by a
+    /// sync point.
+    pub immediate: bool,
+    /// The sync point that runs before the batch, `None` for the first batch.
+    pub sync_point: Option<SyncPoint<'a>>,
+}
+
+/// A sync point that the pipeline inserted before a batch of systems.
+#[derive(Debug, Clone, Copy)]
+pub struct SyncPoint<'a> {
+    /// Why the sync point was inserted.
+    pub reason: SyncReason<'a>,
+    /// The first system of the batch, which caused the sync point.
+    pub system: EntityView<'a>,
+}
+
+/// Reason why a pipeline inserted a sync point.
+#[derive(Debug, Clone, Copy)]
+pub enum SyncReason<'a> {
+    /// The system accesses a component that a previous system wrote to the command queue.
+    ComponentWrite(IdView<'a>),
+    /// The system runs on a different number of threads than the previous system.
+    Threading,
+    /// The system runs in immediate mode, or the previous system did.
+    Immediate,
+}
+
+impl<'a> PipelineSchedule<'a> {
+    pub(crate) fn new(world: WorldRef<'a>, pipeline: Entity) -> Self {
+        ecs_assert!(
+            !world.is_readonly(),
+            FlecsErrorCode::InvalidOperation,
+            "cannot compute the pipeline schedule while the world is in readonly mode"
+        );
+
+        let mut systems: *const sys::ecs_entity_t = std::ptr::null();
+        let mut system_count = 0;
+        let op_count = unsafe {
+            sys::ecs_rust_pipeline_schedule(
+                world.world_ptr_mut(),
+                *pipeline,
+                &mut systems,
+                &mut system_count,
+            )
+        };
+        let systems = if systems.is_null() {
+            &[]
+        } else {
+            unsafe { std::slice::from_raw_parts(systems, system_count as usize) }
+        };
+
+        let mut ops = vec![sys::ecs_rust_pipeline_op_t::default(); op_count as usize];
+        let op_count = unsafe {
+            sys::ecs_rust_pipeline_get_ops(
+                world.world_ptr_mut(),
+                *pipeline,
+                ops.as_mut_ptr(),
+                op_count,
+            )
+        };
+        ops.truncate(op_count as usize);
+
+        let mut batches = Vec::with_capacity(ops.len());
+        for op in ops {
+            let reason = match op.merge_reason as sys::ecs_rust_merge_reason_t {
+                sys::ecs_rust_merge_reason_t_EcsRustMergeComponent => Some(
+                    SyncReason::ComponentWrite(IdView::new_from(world, op.merge_id)),
+                ),
+                sys::ecs_rust_merge_reason_t_EcsRustMergeThreading => Some(SyncReason::Threading),
+                sys::ecs_rust_merge_reason_t_EcsRustMergeImmediate => Some(SyncReason::Immediate),
+                _ => None,
+            };
+
+            let start = op.offset as usize;
+            let end = start + op.count as usize;
+            batches.push(ScheduleBatch {
+                systems: systems[start..end]
+                    .iter()
+                    .map(|system| EntityView::new_from(world, *system))
+                    .collect(),
+                multi_threaded: op.multi_threaded,
+                immediate: op.immediate,
+                sync_point: reason.map(|reason| SyncPoint {
+                    reason,
+                    system: EntityView::new_from(world, op.merge_system),
+                }),
+            });
+        }
+
+        Self { world, batches }
+    }
+
+    /// Returns the batches of systems of the schedule, in the order in which they run.
+    pub fn batches(&self) -> &[ScheduleBatch<'a>] {
+        &self.batches
+    }
+
+    /// Returns an iterator over the systems of the schedule, in the order in which they run.
+    pub fn systems(&self) -> impl Iterator<Item = EntityView<'a>> + '_ {
+        self.batches
+            .iter()
+            .flat_map(|batch| batch.systems.iter().copied())
+    }
+
+    /// Returns an iterator over the sync points of the schedule.
+    pub fn sync_points(&self) -> impl Iterator<Item = &SyncPoint<'a>> + '_ {
+        self.batches
+            .iter()
+            .filter_map(|batch| batch.sync_point.as_ref())
+    }
+
+    /// Export the schedule as a [Graphviz](https://graphviz.org) DOT graph.
+    ///
+    /// Systems are drawn as boxes in the order in which they run, systems that run on multiple
+    /// threads are filled. Sync points are drawn as octagons that are labeled with their reason.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph pipeline {\n    node [shape=box];\n");
+        let mut prev: Option<String> = None;
+        let mut link = |dot: &mut String, node: String| {
+            if let Some(prev) = prev.replace(node.clone()) {
+                let _ = writeln!(dot, "    {} -> {};", prev, node);
+            }
+        };
+
+        for (index, batch) in self.batches.iter().enumerate() {
+            if let Some(sync_point) = &batch.sync_point {
+                let label = match sync_point.reason {
+                    SyncReason::ComponentWrite(id) => {
+                        format!("sync: {} written", dot_escape(id.to_str()))
+                    }
+                    SyncReason::Threading => "sync: threading".to_string(),
+                    SyncReason::Immediate => "sync: immediate".to_string(),
+                };
+                let node = format!("sync{}", index);
+                let _ = writeln!(
+                    dot,
+                    "    {} [shape=octagon, color=red, label=\"{}\"];",
+                    node, label
+                );
+                link(&mut dot, node);
+            }
+
+            for system in &batch.systems {
+                let node = format!("system{}", *system.id());
+                let style = if batch.multi_threaded {
+                    ", style=filled"
+                } else {
+                    ""
+                };
+                let _ = writeln!(
+                    dot,
+                    "    {} [label=\"{}\"{}];",
+                    node,
+                    dot_escape(&system.to_string()),
+                    style
+                );
+                link(&mut dot, node);
+            }
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+impl<'a> WorldProvider<'a> for PipelineSchedule<'a> {
+    fn world(&self) -> WorldRef<'a> {
+        self.world
+    }
+}
+
+fn dot_escape(label: &str) -> String {
+    label.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+impl<'a, T> Pipeline<'a, T>
+where
+    T: QueryTuple,
+{
+    /// Returns the schedule that the pipeline computed for its systems.
+    ///
+    /// The schedule is updated for systems that were created or changed since the last frame.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the world is in readonly mode, e.g. while a system is running.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use flecs_ecs::prelude::*;
+    /// use flecs_ecs::addons::pipeline::SyncReason;
+    ///
+    /// #[derive(Component, Default)]
+    /// struct Position {
+    ///     x: f32,
+    ///     y: f32,
+    /// }
+    ///
+    /// let world = World::new();
+    /// world.entity().set(Position { x: 0.0, y: 0.0 });
+    ///
+    /// // a system that adds a component writes it to the command queue
+    /// world
+    ///     .system_named::<()>("Spawn")
+    ///     .write::<Position>()
+    ///     .run(|mut it| while it.next() {});
+    ///
+    /// world
+    ///     .system_named::<&Position>("Render")
+    ///     .each(|_pos| {});
+    ///
+    /// let schedule = world.get_pipeline_schedule();
+    /// let sync_point = schedule.sync_points().next().unwrap();
+    /// assert_eq!(sync_point.system.name(), "Render");
+    /// assert!(matches!(sync_point.reason, SyncReason::ComponentWrite(id) if id == world.component_id::<Position>()));
+    ///
+    /// println!("{}", schedule.to_dot());
+    /// ```
+    pub fn schedule(&self) -> PipelineSchedule<'a> {
+        PipelineSchedule::new(self.world(), self.id())
+    }
+}
+
+impl World {
+    /// Returns the schedule of the pipeline that is used by [`World::progress()`].
+    ///
+    /// # See also
+    ///
+    /// * [`Pipeline::schedule()`]
+    /// * [`World::get_pipeline()`]
+    pub fn get_pipeline_schedule(&self) -> PipelineSchedule<'_> {
+        PipelineSchedule::new(self.world(), self.get_pipeline().id())
+    }
+}
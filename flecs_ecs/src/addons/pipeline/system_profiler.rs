@@ -0,0 +1,172 @@
+//! Hooks that are invoked before and after a pipeline runs a system, for integrating profilers.
+
+use std::cell::Cell;
+use std::thread::ThreadId;
+use std::time::{Duration, Instant};
+
+use crate::core::callback_panic::catch_callback_panic;
+use crate::core::*;
+use crate::sys;
+
+/// Callback that is invoked before and after a pipeline runs a system.
+pub(crate) type SystemProfilerFn = Box<dyn for<'a> Fn(&SystemProfile<'a>) + Send + Sync>;
+
+/// Whether a [`SystemProfile`] is reported before or after the system runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SystemProfileKind {
+    /// The system is about to run.
+    Begin,
+    /// The system has run.
+    End,
+}
+
+/// A system run, as reported to the callback of [`World::set_system_profiler()`].
+#[derive(Debug, Clone, Copy)]
+pub struct SystemProfile<'a> {
+    /// Whether the system is about to run or has run.
+    pub kind: SystemProfileKind,
+    /// The system.
+    pub system: EntityView<'a>,
+    /// The stage that runs the system, which is 0 unless the system runs on multiple threads.
+    pub stage_id: i32,
+    /// The thread that runs the system.
+    pub thread: ThreadId,
+    /// The time it took to run the system, zero before the system runs.
+    pub elapsed: Duration,
+    /// The number of entities that the system iterated, zero before the system runs.
+    pub entity_count: i32,
+}
+
+impl World {
+    /// Set a callback that is invoked before and after a pipeline runs a system.
+    ///
+    /// The callback is only invoked while the profiler is enabled with
+    /// [`World::enable_system_profiler()`], and replaces the callback that was set before.
+    /// Systems that run on multiple threads invoke the callback on each thread, with the stage
+    /// of the thread. Systems that aren't run by a pipeline, such as systems that are ran
+    /// manually, don't invoke the callback.
+    ///
+    /// # Arguments
+    ///
+    /// * `callback` - Invoked with the system run before and after the system runs.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use flecs_ecs::prelude::*;
+    /// use flecs_ecs::addons::pipeline::SystemProfileKind;
+    ///
+    /// let world = World::new();
+    ///
+    /// world
+    ///     .system_named::<()>("Move")
+    ///     .run(|mut it| while it.next() {});
+    ///
+    /// world.set_system_profiler(|profile| {
+    ///     if profile.kind == SystemProfileKind::End {
+    ///         println!("{} took {:?}", profile.system, profile.elapsed);
+    ///     }
+    /// });
+    /// world.enable_system_profiler(true);
+    ///
+    /// world.progress(); // prints "Move took ..."
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// * [`World::enable_system_profiler()`]
+    pub fn set_system_profiler(
+        &self,
+        callback: impl for<'a> Fn(&SystemProfile<'a>) + Send + Sync + 'static,
+    ) {
+        self.world_ctx_mut().system_profiler = Some(Box::new(Box::new(callback)));
+    }
+
+    /// Enable or disable the callback set with [`World::set_system_profiler()`].
+    ///
+    /// # Arguments
+    ///
+    /// * `enable` - Whether the callback is invoked when systems run.
+    ///
+    /// # See also
+    ///
+    /// * [`World::set_system_profiler()`]
+    /// * [`World::is_system_profiler_enabled()`]
+    pub fn enable_system_profiler(&self, enable: bool) {
+        self.world_ctx_mut().system_profiler_enabled = enable;
+    }
+
+    /// Returns whether the system profiler is enabled.
+    ///
+    /// # See also
+    ///
+    /// * [`World::enable_system_profiler()`]
+    pub fn is_system_profiler_enabled(&self) -> bool {
+        self.world_ctx().system_profiler_enabled
+    }
+}
+
+thread_local! {
+    /// The iterator of the system that is being profiled on this thread, and the number of
+    /// entities it has iterated.
+    static PROFILED_ITER: Cell<(*const sys::ecs_iter_t, i32)> =
+        const { Cell::new((std::ptr::null(), 0)) };
+}
+
+/// Returns the system profiler of a world if systems that a pipeline runs are profiled.
+fn profiler(world: &WorldCtx) -> Option<&SystemProfilerFn> {
+    if world.system_profiler_enabled && world.running_pipeline {
+        world.system_profiler.as_deref()
+    } else {
+        None
+    }
+}
+
+/// Run a system, and report the run to the system profiler if the system is run by a pipeline
+/// while the profiler is enabled.
+pub(crate) unsafe fn run_profiled(it: *mut sys::ecs_iter_t, run: impl FnOnce()) {
+    let world = unsafe { WorldRef::from_ptr((*it).real_world) };
+    if profiler(world.world_ctx()).is_none() {
+        run();
+        return;
+    }
+
+    let system = unsafe { (*it).system };
+    let stage_id = unsafe { sys::ecs_stage_get_id((*it).world) };
+    let report = |kind, elapsed, entity_count| {
+        // the system may have replaced or disabled the profiler
+        let Some(callback) = profiler(world.world_ctx()) else {
+            return;
+        };
+        catch_callback_panic(world.world_ptr(), || {
+            callback(&SystemProfile {
+                kind,
+                system: EntityView::new_from(world, system),
+                stage_id,
+                thread: std::thread::current().id(),
+                elapsed,
+                entity_count,
+            });
+        });
+    };
+
+    report(SystemProfileKind::Begin, Duration::ZERO, 0);
+    let outer = PROFILED_ITER.replace((it, 0));
+    let start = Instant::now();
+    run();
+    let elapsed = start.elapsed();
+    let (_, entity_count) = PROFILED_ITER.replace(outer);
+    report(SystemProfileKind::End, elapsed, entity_count);
+}
+
+/// Count the entities of the current result of an iterator, if it is the iterator of the
+/// system that is being profiled.
+#[inline]
+pub(crate) fn count_profiled_entities(it: &sys::ecs_iter_t) {
+    PROFILED_ITER.with(|profiled| {
+        let (iter, count) = profiled.get();
+        if std::ptr::eq(iter, it) {
+            profiled.set((iter, count + it.count));
+        }
+    });
+}
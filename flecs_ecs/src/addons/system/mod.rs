@@ -4,16 +4,25 @@
 //! query in combination with a callback function. In addition systems have
 //! support for time management, scheduling via pipeline and can be monitored by the stats addon.
 
+mod run_condition;
 mod system_builder;
+mod system_error;
+mod system_order;
+mod system_run;
 mod system_runner_fluent;
 pub use system_builder::*;
+pub use system_error::*;
+pub use system_order::*;
 pub use system_runner_fluent::*;
 
 use std::ops::DerefMut;
 use std::{ops::Deref, os::raw::c_void, ptr::NonNull};
 
+use crate::core::private::internal_SystemAPI;
 use crate::core::*;
 use crate::sys;
+pub(crate) use run_condition::*;
+pub(crate) use system_run::*;
 
 /// Systems are a query + function that can be ran manually or by a pipeline.
 #[derive(Clone, Copy)]
@@ -71,6 +80,9 @@ impl<'a> System<'a> {
         }
 
         let id = unsafe { sys::ecs_system_init(world.world_ptr_mut(), &desc) };
+        if id != 0 {
+            register_query(unsafe { (*sys::ecs_system_get(world.world_ptr(), id)).query });
+        }
         let entity = EntityView::new_from(world.world(), id);
 
         Self { entity }
@@ -95,34 +107,297 @@ impl<'a> System<'a> {
 
     /// Set the context for the system
     ///
+    /// The system takes ownership of the value, which is dropped when the context is replaced or
+    /// when the system is deleted. The context can be accessed from within the system with
+    /// [`TableIter::context()`].
+    ///
     /// # Arguments
     ///
-    /// * `context` - The context to set.
+    /// * `value` - The context to set.
     ///
     /// # See also
     ///
+    /// * [`System::typed_context()`]
     /// * C++ API: `system::ctx`
     #[doc(alias = "system::ctx")]
-    pub fn set_context(&mut self, context: *mut c_void) {
-        let desc: sys::ecs_system_desc_t = sys::ecs_system_desc_t {
-            entity: *self.id(),
-            ctx: context,
-            ..Default::default()
-        };
-
-        unsafe {
-            sys::ecs_system_init(self.world.world_ptr_mut(), &desc);
-        }
+    pub fn set_typed_context<C: 'static>(&mut self, value: C) {
+        self.set_context_ptr_with_free(new_context(value), Some(free_context));
     }
 
     /// Get the context for the system
     ///
+    /// # Returns
+    ///
+    /// The context, or `None` if no context is set or if it is not of type `C`.
+    ///
+    /// # See also
+    ///
+    /// * [`System::set_typed_context()`]
+    /// * C++ API: `system::ctx`
+    #[doc(alias = "system::ctx")]
+    pub fn typed_context<C: 'static>(&self) -> Option<&C> {
+        let system = self.system_ptr();
+        unsafe { context_ref((*system).ctx, (*system).ctx_free) }
+    }
+
+    /// Set a raw context pointer for the system
+    ///
+    /// The previous context is freed if it has a free function.
+    ///
+    /// # Arguments
+    ///
+    /// * `context` - The context to set.
+    ///
     /// # See also
     ///
+    /// * [`System::context_ptr()`]
     /// * C++ API: `system::ctx`
     #[doc(alias = "system::ctx")]
+    pub fn set_context_ptr(&mut self, context: *mut c_void) {
+        self.set_context_ptr_with_free(context, None);
+    }
+
+    /// Get the raw context pointer for the system
+    ///
+    /// # See also
+    ///
+    /// * C++ API: `system::ctx`
+    #[doc(alias = "system::ctx")]
+    pub fn context_ptr(&self) -> *mut c_void {
+        unsafe { (*self.system_ptr()).ctx }
+    }
+
+    /// Set a raw context pointer for the system
+    ///
+    /// # See also
+    ///
+    /// * [`System::set_context_ptr()`]
+    #[deprecated(note = "renamed to `set_context_ptr`, use `set_typed_context` for an owned value")]
+    pub fn set_context(&mut self, context: *mut c_void) {
+        self.set_context_ptr(context);
+    }
+
+    /// Get the raw context pointer for the system
+    ///
+    /// # See also
+    ///
+    /// * [`System::context_ptr()`]
+    #[deprecated(note = "renamed to `context_ptr`, use `typed_context` for an owned value")]
     pub fn context(&self) -> *mut c_void {
-        unsafe { (*sys::ecs_system_get(self.world.world_ptr(), *self.id())).ctx }
+        self.context_ptr()
+    }
+
+    fn system_ptr(&self) -> *mut sys::ecs_system_t {
+        let system = unsafe { sys::ecs_system_get(self.world.world_ptr(), *self.id()) };
+        ecs_assert!(
+            !system.is_null(),
+            FlecsErrorCode::InvalidParameter,
+            "entity is not a system"
+        );
+        system as *mut sys::ecs_system_t
+    }
+
+    // the context is updated in place, as reinitializing the system with `ecs_system_init`
+    // would also free the callback contexts that hold the Rust closures.
+    fn set_context_ptr_with_free(&mut self, ctx: *mut c_void, ctx_free: sys::ecs_ctx_free_t) {
+        let system = unsafe { &mut *self.system_ptr() };
+        replace_context(&mut system.ctx, &mut system.ctx_free, ctx, ctx_free);
+    }
+
+    /// Replace the callback of the system with a callback that is invoked for each matching
+    /// entity, as if the system was built with [`SystemAPI::each()`].
+    ///
+    /// The previous callback of the system, including a run callback, is dropped. The system
+    /// entity, its query, its context and its position in the pipeline are kept, which makes it
+    /// possible to hot-reload the code of a system or to replace it with a mock in tests.
+    ///
+    /// Must not be called while the system is running.
+    ///
+    /// # Type Parameters
+    ///
+    /// * `T` - The components of the callback, which must match the first terms of the query
+    ///   of the system.
+    ///
+    /// # Arguments
+    ///
+    /// * `func` - The new callback.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the components of `T` don't match the terms of the query of the system.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use flecs_ecs::prelude::*;
+    ///
+    /// #[derive(Component)]
+    /// struct Position {
+    ///     x: f32,
+    /// }
+    ///
+    /// let world = World::new();
+    /// let entity = world.entity().set(Position { x: 0.0 });
+    ///
+    /// let mut system = world
+    ///     .system::<&mut Position>()
+    ///     .each(|pos| pos.x += 1.0);
+    ///
+    /// world.progress();
+    ///
+    /// system.set_callback::<&mut Position, _>(|pos| pos.x += 10.0);
+    /// world.progress();
+    ///
+    /// entity.get::<&Position>(|pos| assert_eq!(pos.x, 11.0));
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// * [`System::set_callback_entity()`]
+    /// * [`System::set_run()`]
+    pub fn set_callback<T, Func>(&mut self, func: Func)
+    where
+        T: QueryTuple + 'a,
+        Func: FnMut(T::TupleType<'_>) + 'static,
+    {
+        self.assert_callback_fields::<T>();
+        self.replace_callbacks(
+            Some(SystemBuilder::<'a, T>::execute_each::<false, Func>),
+            Box::into_raw(Box::new(func)) as *mut c_void,
+            Some(SystemBuilder::<'a, ()>::free_callback::<Func>),
+            None,
+            std::ptr::null_mut(),
+            None,
+        );
+    }
+
+    /// Replace the callback of the system with a callback that is invoked for each matching
+    /// entity, with the entity, as if the system was built with [`SystemAPI::each_entity()`].
+    ///
+    /// The previous callback of the system, including a run callback, is dropped. The system
+    /// entity, its query, its context and its position in the pipeline are kept.
+    ///
+    /// Must not be called while the system is running.
+    ///
+    /// # Type Parameters
+    ///
+    /// * `T` - The components of the callback, which must match the first terms of the query
+    ///   of the system.
+    ///
+    /// # Arguments
+    ///
+    /// * `func` - The new callback.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the components of `T` don't match the terms of the query of the system.
+    ///
+    /// # See also
+    ///
+    /// * [`System::set_callback()`]
+    /// * [`System::set_run()`]
+    pub fn set_callback_entity<T, Func>(&mut self, func: Func)
+    where
+        T: QueryTuple + 'a,
+        Func: FnMut(EntityView, T::TupleType<'_>) + 'static,
+    {
+        self.assert_callback_fields::<T>();
+        self.replace_callbacks(
+            Some(SystemBuilder::<'a, T>::execute_each_entity::<false, Func>),
+            Box::into_raw(Box::new(func)) as *mut c_void,
+            Some(SystemBuilder::<'a, ()>::free_callback::<Func>),
+            None,
+            std::ptr::null_mut(),
+            None,
+        );
+    }
+
+    /// Replace the callback of the system with a run callback, as if the system was built with
+    /// [`SystemAPI::run()`].
+    ///
+    /// The previous callback of the system, including an `each` callback, is dropped. The
+    /// system entity, its query, its context and its position in the pipeline are kept.
+    ///
+    /// Must not be called while the system is running.
+    ///
+    /// # Arguments
+    ///
+    /// * `func` - The new run callback.
+    ///
+    /// # See also
+    ///
+    /// * [`System::set_callback()`]
+    pub fn set_run<Func>(&mut self, func: Func)
+    where
+        Func: FnMut(TableIter<true, ()>) + 'static,
+    {
+        self.replace_callbacks(
+            None,
+            std::ptr::null_mut(),
+            None,
+            Some(SystemBuilder::<'a, ()>::execute_run::<Func>),
+            Box::into_raw(Box::new(func)) as *mut c_void,
+            Some(SystemBuilder::<'a, ()>::free_callback::<Func>),
+        );
+    }
+
+    // the callbacks are replaced in place, as reinitializing the system with `ecs_system_init`
+    // would move it to the end of its phase and rebuild its query.
+    fn replace_callbacks(
+        &mut self,
+        action: sys::ecs_iter_action_t,
+        callback_ctx: *mut c_void,
+        callback_ctx_free: sys::ecs_ctx_free_t,
+        run: sys::ecs_run_action_t,
+        run_ctx: *mut c_void,
+        run_ctx_free: sys::ecs_ctx_free_t,
+    ) {
+        if self.world.is_readonly() {
+            panic!(
+                "{}: cannot replace the callback of a system while the world is in readonly mode",
+                FlecsErrorCode::InvalidOperation
+            );
+        }
+
+        let system = unsafe { &mut *self.system_ptr() };
+        system.action = action;
+        replace_context(
+            &mut system.callback_ctx,
+            &mut system.callback_ctx_free,
+            callback_ctx,
+            callback_ctx_free,
+        );
+        // the run action of a system with run conditions wraps the run callback
+        if !replace_wrapped_run(self.world, self.id(), run, run_ctx, run_ctx_free) {
+            system.run = run;
+            replace_context(
+                &mut system.run_ctx,
+                &mut system.run_ctx_free,
+                run_ctx,
+                run_ctx_free,
+            );
+        }
+    }
+
+    // the callback reads the fields of the query as the components of `T`, a mismatch would
+    // reinterpret the data of one component as another, read a missing optional component or
+    // write a component that the query only reads.
+    fn assert_callback_fields<T: QueryTuple>(&self) {
+        let mut desc = sys::ecs_query_desc_t::default();
+        T::register_ids_descriptor(self.world.world_ptr_mut(), &mut desc);
+        let query = unsafe { &*(*self.system_ptr()).query };
+        let count = T::COUNT as usize;
+        let matches = query.term_count as usize >= count
+            && (0..count).all(|i| {
+                let (term, expected) = (&query.terms[i], &desc.terms[i]);
+                term.id == expected.id && term.oper == expected.oper && term.inout == expected.inout
+            });
+        if !matches {
+            panic!(
+                "{}: the components of the callback don't match the query of the system",
+                FlecsErrorCode::InvalidParameter
+            );
+        }
     }
 
     /// Get the underlying query for the system
@@ -182,6 +457,9 @@ impl<'a> System<'a> {
 
     /// Run the system worker
     ///
+    /// Workers don't evaluate the run conditions of the system, they use the result of the
+    /// last evaluation by a pipeline or by [`System::run()`].
+    ///
     /// # Arguments
     ///
     /// * `stage_current` - The current stage.
@@ -0,0 +1,72 @@
+//! Run conditions that decide whether a system runs.
+//!
+//! A system with run conditions gets the run action of [`system_run`], which skips the system
+//! when a condition was false. Conditions aren't [`Send`], so they are evaluated on the thread
+//! that progresses the world before the pipeline runs, and the threads that run the system only
+//! read the result.
+
+use std::sync::atomic::Ordering;
+
+use super::system_run;
+use crate::core::*;
+use crate::sys;
+
+/// A run condition of a system.
+pub(crate) type RunCondition = Box<dyn FnMut(&World) -> bool>;
+
+/// Set the run conditions of a system, which replace the conditions it had before.
+///
+/// The system only runs if all conditions return true. Conditions are evaluated in the order
+/// in which they were added, and evaluation stops at the first condition that returns false.
+pub(crate) fn set_run_conditions(world: WorldRef, system: Entity, conditions: Vec<RunCondition>) {
+    let run = unsafe { &mut *system_run(world, system) };
+    run.conditions = conditions;
+    run.enabled.store(true, Ordering::Relaxed);
+}
+
+/// Evaluate the run conditions of all systems. Pipelines run a system with conditions only if
+/// they were true the last time they were evaluated.
+pub(crate) fn eval_run_conditions(world: WorldRef) {
+    let systems: Vec<Entity> = world.world_ctx().system_runs.keys().copied().collect();
+    for system in systems {
+        eval_system_conditions(world, system);
+    }
+}
+
+/// Evaluate the run conditions of a system, if it has any.
+pub(crate) fn eval_system_conditions(world: WorldRef, system: Entity) {
+    // a condition may have deleted the system
+    let Some(&run) = world.world_ctx().system_runs.get(&system) else {
+        return;
+    };
+    let run = unsafe { &mut *run };
+    if run.conditions.is_empty() {
+        return;
+    }
+    let enabled = run.conditions.iter_mut().all(|condition| condition(&world));
+    run.enabled.store(enabled, Ordering::Relaxed);
+}
+
+/// Returns a run condition that is true if the singleton `R` was set or modified since the
+/// condition was last evaluated.
+pub(crate) fn resource_changed<R: ComponentId>() -> RunCondition {
+    let mut last = None;
+    Box::new(move |world| {
+        let id = R::id(world);
+        let mut table_id = 0;
+        let mut version = 0;
+        let current = unsafe {
+            sys::ecs_rust_get_component_version(
+                world.world_ptr(),
+                id,
+                id,
+                &mut table_id,
+                &mut version,
+            )
+        }
+        .then_some((table_id, version));
+        let changed = current.is_some() && current != last;
+        last = current;
+        changed
+    })
+}
@@ -14,6 +14,9 @@ where
     term_builder: TermBuilder,
     world: WorldRef<'a>,
     is_instanced: bool,
+    run_conditions: Vec<RunCondition>,
+    order_constraints: Vec<(u64, Entity)>,
+    error_policy: Option<SystemErrorPolicy>,
     _phantom: std::marker::PhantomData<&'a T>,
 }
 
@@ -29,6 +32,9 @@ where
             world: world.into(),
             _phantom: std::marker::PhantomData,
             is_instanced: false,
+            run_conditions: Vec::new(),
+            order_constraints: Vec::new(),
+            error_policy: None,
         };
 
         obj.desc.entity = unsafe { sys::ecs_entity_init(obj.world_ptr_mut(), &Default::default()) };
@@ -55,6 +61,9 @@ where
             world: world.into(),
             _phantom: std::marker::PhantomData,
             is_instanced: false,
+            run_conditions: Vec::new(),
+            order_constraints: Vec::new(),
+            error_policy: None,
         };
 
         if obj.desc.entity == 0 {
@@ -87,6 +96,9 @@ where
             world: world.into(),
             _phantom: std::marker::PhantomData,
             is_instanced: false,
+            run_conditions: Vec::new(),
+            order_constraints: Vec::new(),
+            error_policy: None,
         };
 
         let entity_desc: sys::ecs_entity_desc_t = sys::ecs_entity_desc_t {
@@ -178,6 +190,312 @@ where
         self.kind_id(enum_id)
     }
 
+    /// Run the system before another system, or before all systems of a set.
+    ///
+    /// Systems of a phase run in the order in which they were created, unless they are ordered
+    /// with `before`, [`after()`](Self::after) and [`in_set()`](Self::in_set). Constraints
+    /// only affect the order of systems within the same phase.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - the system or set that runs after this system.
+    ///
+    /// # Panics
+    ///
+    /// [`build()`](Builder::build) panics if the constraints of the systems contain a cycle.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use flecs_ecs::prelude::*;
+    ///
+    /// let world = World::new();
+    ///
+    /// let physics = world.entity_named("Physics");
+    ///
+    /// world
+    ///     .system_named::<()>("Render")
+    ///     .after(physics)
+    ///     .run(|mut it| while it.next() {});
+    ///
+    /// world
+    ///     .system_named::<()>("Collide")
+    ///     .in_set(physics)
+    ///     .run(|mut it| while it.next() {});
+    ///
+    /// world
+    ///     .system_named::<()>("Move")
+    ///     .before(physics)
+    ///     .run(|mut it| while it.next() {});
+    ///
+    /// // the systems run in the order Move, Collide, Render
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// * [`SystemBuilder::after()`]
+    /// * [`SystemBuilder::in_set()`]
+    pub fn before(&mut self, other: impl Into<Entity>) -> &mut Self {
+        self.add_order_constraint(RunsBefore::id(self.world()), other.into())
+    }
+
+    /// Run the system after another system, or after all systems of a set.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - the system or set that runs before this system.
+    ///
+    /// # Panics
+    ///
+    /// [`build()`](Builder::build) panics if the constraints of the systems contain a cycle.
+    ///
+    /// # See also
+    ///
+    /// * [`SystemBuilder::before()`]
+    /// * [`SystemBuilder::in_set()`]
+    pub fn after(&mut self, other: impl Into<Entity>) -> &mut Self {
+        self.add_order_constraint(RunsAfter::id(self.world()), other.into())
+    }
+
+    /// Add the system to a set. Systems that run before or after the set run before or after
+    /// all systems in the set.
+    ///
+    /// # Arguments
+    ///
+    /// * `set` - the entity that labels the set.
+    ///
+    /// # See also
+    ///
+    /// * [`SystemBuilder::before()`]
+    /// * [`SystemBuilder::after()`]
+    pub fn in_set(&mut self, set: impl Into<Entity>) -> &mut Self {
+        self.add_order_constraint(InSet::id(self.world()), set.into())
+    }
+
+    fn add_order_constraint(&mut self, relationship: u64, target: Entity) -> &mut Self {
+        self.order_constraints.push((relationship, target));
+        self
+    }
+
+    /// Only run the system if a condition is true.
+    ///
+    /// Conditions are evaluated once per frame on the thread that progresses the world, before
+    /// the pipeline runs, and don't cause sync points. A system with multiple conditions only
+    /// runs if all conditions are true. [`System::run()`] evaluates the conditions again,
+    /// [`System::run_worker()`] uses the result of the last evaluation.
+    ///
+    /// # Arguments
+    ///
+    /// * `condition` - returns whether the system should run.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use flecs_ecs::prelude::*;
+    ///
+    /// #[derive(Component)]
+    /// struct Paused;
+    ///
+    /// let world = World::new();
+    ///
+    /// world
+    ///     .system::<()>()
+    ///     .run_if(|world| !world.has::<Paused>())
+    ///     .run(|mut it| {
+    ///         while it.next() {}
+    ///         println!("not paused");
+    ///     });
+    ///
+    /// world.progress(); // prints "not paused"
+    ///
+    /// world.add::<Paused>();
+    /// world.progress(); // prints nothing
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// * [`SystemBuilder::run_if_any()`]
+    /// * [`SystemBuilder::run_if_resource_changed()`]
+    pub fn run_if(&mut self, condition: impl FnMut(&World) -> bool + 'static) -> &mut Self {
+        self.run_conditions.push(Box::new(condition));
+        self
+    }
+
+    /// Only run the system if the query `Q` matches at least one entity.
+    ///
+    /// # Type Parameters
+    ///
+    /// * `Q` - the query that must have results.
+    ///
+    /// # See also
+    ///
+    /// * [`SystemBuilder::run_if()`]
+    pub fn run_if_any<Q>(&mut self) -> &mut Self
+    where
+        Q: QueryTuple + 'static,
+    {
+        let mut query = self.world.new_query::<Q>();
+        self.run_if(move |_| query.is_true())
+    }
+
+    /// Only run the system if the singleton `R` was set or modified since the condition was
+    /// last evaluated, which is the case the first time it is evaluated after `R` was added.
+    ///
+    /// Changes are detected for components that are set or marked as modified, changes made
+    /// through mutable references that aren't followed by [`EntityView::modified()`] aren't
+    /// detected.
+    ///
+    /// # Type Parameters
+    ///
+    /// * `R` - the singleton component.
+    ///
+    /// # See also
+    ///
+    /// * [`SystemBuilder::run_if()`]
+    pub fn run_if_resource_changed<R>(&mut self) -> &mut Self
+    where
+        R: ComponentId,
+    {
+        self.run_conditions.push(resource_changed::<R>());
+        self
+    }
+
+    /// Set the policy for errors returned by the callback of the system.
+    ///
+    /// Systems without a policy use the policy of the world, see
+    /// [`World::set_system_error_policy()`].
+    ///
+    /// # Arguments
+    ///
+    /// * `policy` - The policy.
+    ///
+    /// # See also
+    ///
+    /// * [`SystemBuilder::try_each()`]
+    /// * [`SystemBuilder::try_run()`]
+    pub fn on_error(&mut self, policy: SystemErrorPolicy) -> &mut Self {
+        self.error_policy = Some(policy);
+        self
+    }
+
+    /// Build the system with a fallible callback that is invoked for each matching entity.
+    ///
+    /// When the callback returns an error, it isn't invoked for the remaining entities of the
+    /// run, and the error is handled according to the [`SystemErrorPolicy`] of the system.
+    ///
+    /// # Arguments
+    ///
+    /// * `func` - The callback.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use flecs_ecs::prelude::*;
+    /// use flecs_ecs::addons::system::SystemErrorPolicy;
+    ///
+    /// #[derive(Component)]
+    /// struct Asset {
+    ///     path: &'static str,
+    /// }
+    ///
+    /// let world = World::new();
+    /// world.entity().set(Asset { path: "missing.png" });
+    ///
+    /// world
+    ///     .system_named::<&Asset>("LoadAssets")
+    ///     .on_error(SystemErrorPolicy::Propagate)
+    ///     .try_each(|asset| {
+    ///         if asset.path.starts_with("missing") {
+    ///             return Err(format!("asset not found: {}", asset.path));
+    ///         }
+    ///         Ok(())
+    ///     });
+    ///
+    /// let error = world.try_progress().unwrap_err();
+    /// assert_eq!(error.to_string(), "system LoadAssets failed: asset not found: missing.png");
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// * [`SystemBuilder::on_error()`]
+    /// * [`SystemBuilder::try_each_entity()`]
+    /// * [`SystemBuilder::try_run()`]
+    pub fn try_each<Func, E>(&mut self, mut func: Func) -> System<'a>
+    where
+        Func: FnMut(T::TupleType<'_>) -> Result<(), E> + 'static,
+        E: Into<BoxedSystemError>,
+    {
+        let policy = self.error_policy;
+        self.run_each(
+            move |mut it| handle_each_errors(&mut it, policy),
+            move |components| {
+                if !each_failed() {
+                    if let Err(error) = func(components) {
+                        set_each_error(error.into());
+                    }
+                }
+            },
+        )
+    }
+
+    /// Build the system with a fallible callback that is invoked for each matching entity,
+    /// with the entity.
+    ///
+    /// When the callback returns an error, it isn't invoked for the remaining entities of the
+    /// run, and the error is handled according to the [`SystemErrorPolicy`] of the system.
+    ///
+    /// # Arguments
+    ///
+    /// * `func` - The callback.
+    ///
+    /// # See also
+    ///
+    /// * [`SystemBuilder::try_each()`]
+    pub fn try_each_entity<Func, E>(&mut self, mut func: Func) -> System<'a>
+    where
+        Func: FnMut(EntityView, T::TupleType<'_>) -> Result<(), E> + 'static,
+        E: Into<BoxedSystemError>,
+    {
+        let policy = self.error_policy;
+        self.run_each_entity(
+            move |mut it| handle_each_errors(&mut it, policy),
+            move |entity, components| {
+                if !each_failed() {
+                    if let Err(error) = func(entity, components) {
+                        set_each_error(error.into());
+                    }
+                }
+            },
+        )
+    }
+
+    /// Build the system with a fallible run callback.
+    ///
+    /// When the callback returns an error, the error is handled according to the
+    /// [`SystemErrorPolicy`] of the system.
+    ///
+    /// # Arguments
+    ///
+    /// * `func` - The callback.
+    ///
+    /// # See also
+    ///
+    /// * [`SystemBuilder::try_each()`]
+    pub fn try_run<Func, E>(&mut self, mut func: Func) -> System<'a>
+    where
+        Func: FnMut(TableIter<true, ()>) -> Result<(), E> + 'static,
+        E: Into<BoxedSystemError>,
+    {
+        let policy = self.error_policy;
+        self.run(move |it| {
+            let world = it.world();
+            let system = it.system().id();
+            if let Err(error) = func(it) {
+                handle_system_error(world, system, policy, error.into());
+            }
+        })
+    }
+
     /// Specify whether system can run on multiple threads.
     ///
     /// # Arguments
@@ -327,6 +645,30 @@ impl<'a, T: QueryTuple> internals::QueryConfig<'a> for SystemBuilder<'a, T> {
 
 impl<'a, T: QueryTuple> TermBuilderImpl<'a> for SystemBuilder<'a, T> {}
 
+impl<'a, T: QueryTuple> SystemBuilder<'a, T> {
+    fn free_str_ptrs(&mut self) {
+        for string_parts in std::mem::take(&mut self.term_builder.str_ptrs_to_free) {
+            unsafe {
+                String::from_raw_parts(
+                    string_parts.ptr as *mut u8,
+                    string_parts.len,
+                    string_parts.capacity,
+                );
+            }
+        }
+    }
+}
+
+/// Run callback of fallible `each` systems, which handles the error of the `each` callback.
+fn handle_each_errors(it: &mut TableIter<true, ()>, policy: Option<SystemErrorPolicy>) {
+    while it.next() {
+        it.each();
+    }
+    if let Some(error) = take_each_error() {
+        handle_system_error(it.world(), it.system().id(), policy, error);
+    }
+}
+
 impl<'a, T: QueryTuple> QueryBuilderImpl<'a> for SystemBuilder<'a, T> {}
 
 impl<'a, T> Builder<'a> for SystemBuilder<'a, T>
@@ -342,16 +684,53 @@ where
     /// * C++ API: `node_builder::build`
     #[doc(alias = "node_builder::build")]
     fn build(&mut self) -> Self::BuiltType {
+        // change detection of `Changed` terms requires a cached query
+        if T::CHANGED_FIELDS != 0
+            && self.desc.query.cache_kind == QueryCacheKind::Default as sys::ecs_query_cache_kind_t
+        {
+            self.desc.query.cache_kind = QueryCacheKind::Auto as sys::ecs_query_cache_kind_t;
+        }
+        if !self.order_constraints.is_empty() {
+            if let Err(err) = validate_system_order(
+                self.world,
+                Entity(self.desc.entity),
+                &self.order_constraints,
+            ) {
+                self.free_str_ptrs();
+                // don't leave the entity of a system that was never built in the world
+                let world_ptr = self.world_ptr_mut();
+                if !unsafe {
+                    sys::ecs_has_id(world_ptr, self.desc.entity, flecs::system::System::ID)
+                } {
+                    unsafe { sys::ecs_delete(world_ptr, self.desc.entity) };
+                }
+                panic!("{}", err);
+            }
+        }
         let system = System::new(self.world(), self.desc, self.is_instanced);
-        for string_parts in self.term_builder.str_ptrs_to_free.iter() {
-            unsafe {
-                String::from_raw_parts(
-                    string_parts.ptr as *mut u8,
-                    string_parts.len,
-                    string_parts.capacity,
-                );
+        if !self.order_constraints.is_empty() {
+            for (relationship, target) in std::mem::take(&mut self.order_constraints) {
+                unsafe {
+                    sys::ecs_add_id(
+                        self.world_ptr_mut(),
+                        *system.id(),
+                        ecs_pair(relationship, *target),
+                    );
+                }
             }
+            self.world.world_ctx_mut().has_system_order = true;
+        }
+        if !self.run_conditions.is_empty() {
+            set_run_conditions(
+                self.world,
+                system.id(),
+                std::mem::take(&mut self.run_conditions),
+            );
+        }
+        if self.world.world_ctx().has_system_order {
+            order_systems(self.world);
         }
+        self.free_str_ptrs();
         system
     }
 }
@@ -0,0 +1,223 @@
+//! Errors returned by fallible systems, and the policies that decide how they are handled.
+
+use std::cell::RefCell;
+use std::error::Error;
+use std::fmt;
+
+use crate::core::callback_panic::resume_callback_panic;
+use crate::core::*;
+
+/// An error returned by the callback of a fallible system.
+pub type BoxedSystemError = Box<dyn Error + Send + Sync>;
+
+/// What happens when the callback of a fallible system returns an error.
+///
+/// The policy of a system is set with [`SystemBuilder::on_error()`](crate::addons::system::SystemBuilder::on_error). Systems without a
+/// policy use the policy of the world, which is set with [`World::set_system_error_policy()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SystemErrorPolicy {
+    /// Log the error and keep running the system. This is the default policy.
+    #[default]
+    Log,
+    /// Log the error and disable the system, so that it doesn't run until it is enabled again.
+    Disable,
+    /// Log the error and stop the pipeline. The remaining systems of the frame don't run, and
+    /// [`World::progress()`] returns false as if [`World::quit()`] was called.
+    Stop,
+    /// Stop the pipeline and return the error from [`World::try_progress()`]. The remaining
+    /// systems of the frame don't run. [`World::progress()`] logs the error instead.
+    Propagate,
+}
+
+/// An error returned by a fallible system, as returned by [`World::try_progress()`].
+#[derive(Debug)]
+pub struct SystemError {
+    system: Entity,
+    name: String,
+    error: BoxedSystemError,
+}
+
+impl SystemError {
+    /// Returns the system that returned the error.
+    pub fn system(&self) -> Entity {
+        self.system
+    }
+
+    /// Returns the error that the system returned.
+    pub fn error(&self) -> &(dyn Error + Send + Sync + 'static) {
+        &*self.error
+    }
+
+    /// Converts into the error that the system returned.
+    pub fn into_error(self) -> BoxedSystemError {
+        self.error
+    }
+}
+
+impl fmt::Display for SystemError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "system {} failed: {}", self.name, self.error)
+    }
+}
+
+impl Error for SystemError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&*self.error)
+    }
+}
+
+thread_local! {
+    /// The error of the `each` callback of a fallible system that is running on this thread,
+    /// which is handled by the run callback of the system after iterating the table.
+    static EACH_ERROR: RefCell<Option<BoxedSystemError>> = const { RefCell::new(None) };
+}
+
+/// Returns whether the `each` callback of the fallible system that is running on this thread
+/// returned an error, in which case it isn't invoked for the remaining entities.
+pub(crate) fn each_failed() -> bool {
+    EACH_ERROR.with(|error| error.borrow().is_some())
+}
+
+pub(crate) fn set_each_error(error: BoxedSystemError) {
+    EACH_ERROR.with(|slot| *slot.borrow_mut() = Some(error));
+}
+
+pub(crate) fn take_each_error() -> Option<BoxedSystemError> {
+    EACH_ERROR.with(|error| error.borrow_mut().take())
+}
+
+/// Handle an error that `system` returned according to `policy`, or the policy of the world
+/// if the system has no policy.
+pub(crate) fn handle_system_error(
+    world: WorldRef,
+    system: Entity,
+    policy: Option<SystemErrorPolicy>,
+    error: BoxedSystemError,
+) {
+    let real_world = world.real_world();
+    let policy = policy.unwrap_or(real_world.world_ctx().system_error_policy);
+    let system_view = EntityView::new_from(world, system);
+    let error = SystemError {
+        system,
+        name: system_view.to_string(),
+        error,
+    };
+
+    if policy != SystemErrorPolicy::Propagate {
+        log_error(&error.to_string());
+    }
+
+    match policy {
+        SystemErrorPolicy::Log => {}
+        SystemErrorPolicy::Disable => {
+            system_view.disable_self();
+        }
+        SystemErrorPolicy::Stop => {
+            stop_pipeline(world);
+            real_world.quit();
+        }
+        SystemErrorPolicy::Propagate => {
+            stop_pipeline(world);
+            let mut pending = real_world
+                .world_ctx()
+                .system_error
+                .lock()
+                .unwrap_or_else(|e| e.into_inner());
+            // only the first error of a frame is returned
+            if pending.is_none() {
+                *pending = Some(error);
+            } else {
+                log_error(&error.to_string());
+            }
+        }
+    }
+}
+
+fn stop_pipeline(_world: WorldRef) {
+    #[cfg(feature = "flecs_pipeline")]
+    _world
+        .real_world()
+        .world_ctx()
+        .pipeline_stopped
+        .store(true, std::sync::atomic::Ordering::Relaxed);
+}
+
+impl World {
+    /// Set the policy for errors returned by fallible systems that don't have a policy.
+    ///
+    /// # Arguments
+    ///
+    /// * `policy` - The policy.
+    ///
+    /// # See also
+    ///
+    /// * [`SystemBuilder::on_error()`](crate::addons::system::SystemBuilder::on_error)
+    /// * [`World::get_system_error_policy()`]
+    pub fn set_system_error_policy(&self, policy: SystemErrorPolicy) {
+        self.world_ctx_mut().system_error_policy = policy;
+    }
+
+    /// Get the policy for errors returned by fallible systems that don't have a policy.
+    ///
+    /// # See also
+    ///
+    /// * [`World::set_system_error_policy()`]
+    pub fn get_system_error_policy(&self) -> SystemErrorPolicy {
+        self.world_ctx().system_error_policy
+    }
+
+    /// Progress the world, and return the error of a system with the
+    /// [`SystemErrorPolicy::Propagate`] policy.
+    ///
+    /// This is the same as [`World::progress()`], except that an error stops the pipeline and
+    /// is returned instead of being logged. If multiple systems return an error, the first
+    /// error is returned and the others are logged.
+    ///
+    /// # Returns
+    ///
+    /// True if the world has been progressed, false if [`World::quit()`] has been called, or
+    /// the error of a system.
+    ///
+    /// # See also
+    ///
+    /// * [`World::try_progress_time()`]
+    #[cfg(feature = "flecs_pipeline")]
+    pub fn try_progress(&self) -> Result<bool, SystemError> {
+        self.try_progress_time(0.0)
+    }
+
+    /// Progress the world by delta time, and return the error of a system with the
+    /// [`SystemErrorPolicy::Propagate`] policy.
+    ///
+    /// # Arguments
+    ///
+    /// * `delta_time` - The time to progress the world by. Pass 0.0 for automatic time measurement.
+    ///
+    /// # See also
+    ///
+    /// * [`World::try_progress()`]
+    /// * [`World::progress_time()`]
+    #[cfg(feature = "flecs_pipeline")]
+    pub fn try_progress_time(&self, delta_time: FTime) -> Result<bool, SystemError> {
+        // errors of systems that were ran manually since the last frame
+        if let Some(error) = self.take_system_error() {
+            log_error(&error.to_string());
+        }
+        let result = super::progress(self.into(), delta_time);
+        resume_callback_panic(self.world_ptr());
+        match self.take_system_error() {
+            Some(error) => Err(error),
+            None => Ok(result),
+        }
+    }
+
+    /// Takes the error that a system with the [`SystemErrorPolicy::Propagate`] policy
+    /// returned, if any.
+    pub(crate) fn take_system_error(&self) -> Option<SystemError> {
+        self.world_ctx()
+            .system_error
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .take()
+    }
+}
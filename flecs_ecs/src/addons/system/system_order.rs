@@ -0,0 +1,314 @@
+//! Ordering constraints between systems.
+//!
+//! Systems of a phase run in the order in which they were created, unless they are ordered
+//! with [`SystemBuilder::before()`], [`SystemBuilder::after()`] and [`SystemBuilder::in_set()`].
+//! The constraints are stored as relationships on the system entities, which are sorted
+//! topologically when a system is built. Pipelines sort the systems of a phase with the order
+//! that is stored in the world.
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::ffi::{c_int, c_void};
+
+use crate::core::world_ctx::WorldCtx;
+use crate::core::*;
+use crate::sys;
+
+/// Relationship that makes a system run before the target system, or before all systems that
+/// are in the target set.
+#[derive(flecs_ecs_derive::Component)]
+pub struct RunsBefore;
+
+/// Relationship that makes a system run after the target system, or after all systems that
+/// are in the target set.
+#[derive(flecs_ecs_derive::Component)]
+pub struct RunsAfter;
+
+/// Relationship that adds a system to a set. The target can be any entity, ordering
+/// constraints that refer to it apply to all systems in the set.
+#[derive(flecs_ecs_derive::Component)]
+pub struct InSet;
+
+/// Checks that the ordering constraints of a system that is about to be built don't form a
+/// cycle with the constraints of the systems of the world.
+///
+/// # Returns
+///
+/// The error message that describes the cycle, if any.
+pub(crate) fn validate_system_order(
+    world: WorldRef,
+    system: Entity,
+    constraints: &[(u64, Entity)],
+) -> Result<(), String> {
+    sort_systems(world, Some((system, constraints))).map(|_| ())
+}
+
+/// Sort the systems of the world topologically by their ordering constraints, and store the
+/// order in which pipelines run them.
+///
+/// Systems that have no ordering constraints keep their position in creation order. Systems
+/// with constraints are sorted among the positions of the systems with constraints, and run in
+/// creation order where the constraints allow it.
+pub(crate) fn order_systems(world: WorldRef) {
+    let keys = match sort_systems(world, None) {
+        Ok(keys) => keys,
+        // the constraints of a system are validated before the system is built
+        Err(err) => panic!("{}", err),
+    };
+
+    let world_ptr = world.world_ptr_mut();
+    let world_ctx = world.world_ctx_mut();
+    let prev = std::mem::replace(&mut world_ctx.system_order, keys);
+    let keys = &world.world_ctx().system_order;
+    for (system, key) in keys {
+        if prev.get(system) != Some(key) {
+            unsafe { sys::ecs_rust_system_order_changed(world_ptr, **system) };
+        }
+    }
+    for system in prev.keys().filter(|system| !keys.contains_key(system)) {
+        unsafe { sys::ecs_rust_system_order_changed(world_ptr, **system) };
+    }
+
+    #[cfg(feature = "flecs_pipeline")]
+    order_builtin_pipeline(world);
+}
+
+/// Replaces the builtin pipeline of the world with a pipeline that has the same query, and
+/// that runs the systems of a phase in the order of their constraints.
+#[cfg(feature = "flecs_pipeline")]
+fn order_builtin_pipeline(world: WorldRef) {
+    let world_ptr = world.world_ptr_mut();
+    let builtin = unsafe { sys::ecs_lookup(world_ptr, c"flecs.pipeline.BuiltinPipeline".as_ptr()) };
+    if builtin == 0 || unsafe { sys::ecs_get_pipeline(world_ptr) } != builtin {
+        return;
+    }
+    unsafe {
+        let pipeline = sys::ecs_rust_ordered_pipeline_init(world_ptr, false, Some(compare_systems));
+        sys::ecs_set_pipeline(world_ptr, pipeline);
+    }
+}
+
+/// Progresses the world like `ecs_progress`, see `run_pipeline`. Startup systems run in the
+/// order of their constraints, and the fixed update pipeline runs before the pipeline of the
+/// world.
+#[cfg(feature = "flecs_pipeline")]
+pub(crate) fn progress(world: WorldRef, delta_time: f32) -> bool {
+    let order_by = if world.world_ctx().has_system_order {
+        Some(compare_systems as _)
+    } else {
+        None
+    };
+    let world_ptr = world.world_ptr_mut();
+    crate::addons::pipeline::run_pipeline(world, || unsafe {
+        let delta_time = sys::ecs_frame_begin(world_ptr, delta_time);
+        // read through the generated struct, `WorldInfo` doesn't match the layout of flecs
+        let info = sys::ecs_get_world_info(world_ptr) as *const sys::ecs_world_info_t;
+        if (*info).frame_count_total == 0 {
+            sys::ecs_rust_run_startup_systems(world_ptr, order_by);
+        }
+        crate::addons::pipeline::run_fixed_timestep(world, delta_time);
+        sys::ecs_run_pipeline(world_ptr, 0, delta_time);
+        sys::ecs_frame_end(world_ptr);
+        !sys::ecs_should_quit(world_ptr)
+    })
+}
+
+/// Sorts the systems of a pipeline query in the order of their constraints, by adding a term
+/// for the system object that the constraints are looked up with.
+///
+/// # Panics
+///
+/// Panics if the query doesn't have room for the term.
+#[cfg(feature = "flecs_pipeline")]
+pub(crate) fn order_pipeline_query(desc: &mut sys::ecs_query_desc_t) {
+    if desc.order_by_callback.is_some() {
+        return;
+    }
+    let Some(term) = desc
+        .terms
+        .iter_mut()
+        .find(|term| !unsafe { sys::ecs_term_is_initialized(*term) })
+    else {
+        panic!(
+            "{}: a pipeline can have at most {} terms, one of which orders its systems",
+            FlecsErrorCode::InvalidParameter,
+            desc.terms.len() - 1
+        );
+    };
+    let poly_system = ecs_pair(
+        unsafe { sys::FLECS_IDEcsPolyID_ },
+        flecs::system::System::ID,
+    );
+    term.id = poly_system;
+    term.inout = sys::ecs_inout_kind_t_EcsIn as i16;
+    desc.order_by = poly_system;
+    desc.order_by_callback = Some(compare_systems);
+}
+
+/// Compares systems by the position that their ordering constraints give them, which is the
+/// system id of systems without constraints. Pipelines pass the system objects of the systems.
+pub(crate) unsafe extern "C" fn compare_systems(
+    e1: sys::ecs_entity_t,
+    ptr1: *const c_void,
+    e2: sys::ecs_entity_t,
+    ptr2: *const c_void,
+) -> c_int {
+    let key = |entity: sys::ecs_entity_t, ptr: *const c_void| {
+        let system = unsafe { (*(ptr as *const sys::EcsPoly)).poly as *const sys::ecs_system_t };
+        if system.is_null() {
+            return entity;
+        }
+        let world_ctx = unsafe { sys::ecs_get_binding_ctx((*system).world) } as *const WorldCtx;
+        if world_ctx.is_null() {
+            return entity;
+        }
+        unsafe { &(*world_ctx).system_order }
+            .get(&Entity(entity))
+            .copied()
+            .unwrap_or(entity)
+    };
+    let (k1, k2) = (key(e1, ptr1), key(e2, ptr2));
+    (k1 > k2) as c_int - (k1 < k2) as c_int
+}
+
+/// Returns the order key of every system that has ordering constraints, optionally with the
+/// constraints of a system that is about to be built.
+fn sort_systems(
+    world: WorldRef,
+    pending: Option<(Entity, &[(u64, Entity)])>,
+) -> Result<HashMap<Entity, u64, fxhash::FxBuildHasher>, String> {
+    let mut systems = entities_with(world, flecs::system::System::ID);
+    if let Some((system, _)) = pending {
+        if !systems.contains(&system) {
+            systems.push(system);
+        }
+    }
+    systems.sort_unstable();
+
+    let index: HashMap<Entity, usize> = systems.iter().enumerate().map(|(i, e)| (*e, i)).collect();
+    let runs_before = RunsBefore::id(world);
+    let runs_after = RunsAfter::id(world);
+    let in_set = InSet::id(world);
+
+    // the systems that a constraint with `target` refers to
+    let members = |target: Entity| -> Vec<usize> {
+        let mut members: Vec<usize> = entities_with(world, ecs_pair(in_set, *target))
+            .iter()
+            .filter_map(|e| index.get(e).copied())
+            .collect();
+        if let Some((system, constraints)) = pending {
+            if constraints.contains(&(in_set, target)) {
+                members.push(index[&system]);
+            }
+        }
+        if let Some(i) = index.get(&target) {
+            members.push(*i);
+        }
+        members
+    };
+
+    let mut successors = vec![Vec::new(); systems.len()];
+    let mut predecessors = vec![Vec::new(); systems.len()];
+    let mut add_constraint = |i: usize, relationship: u64, target: Entity| {
+        for m in members(target) {
+            if relationship == runs_before {
+                successors[i].push(m);
+                predecessors[m].push(i);
+            } else if relationship == runs_after {
+                successors[m].push(i);
+                predecessors[i].push(m);
+            }
+        }
+    };
+    for (i, system) in systems.iter().enumerate() {
+        let view = EntityView::new_from(world, *system);
+        view.each_target::<RunsBefore>(|target| add_constraint(i, runs_before, target.id()));
+        view.each_target::<RunsAfter>(|target| add_constraint(i, runs_after, target.id()));
+    }
+    if let Some((system, constraints)) = pending {
+        for (relationship, target) in constraints {
+            add_constraint(index[&system], *relationship, *target);
+        }
+    }
+
+    // only systems with constraints are sorted, in the positions that they were created in
+    let constrained: Vec<usize> = (0..systems.len())
+        .filter(|i| !successors[*i].is_empty() || !predecessors[*i].is_empty())
+        .collect();
+
+    let mut in_degree: Vec<usize> = predecessors.iter().map(Vec::len).collect();
+    let mut ready: BinaryHeap<Reverse<usize>> = constrained
+        .iter()
+        .copied()
+        .filter(|i| in_degree[*i] == 0)
+        .map(Reverse)
+        .collect();
+
+    let mut order = Vec::with_capacity(constrained.len());
+    while let Some(Reverse(i)) = ready.pop() {
+        order.push(i);
+        for &s in &successors[i] {
+            in_degree[s] -= 1;
+            if in_degree[s] == 0 {
+                ready.push(Reverse(s));
+            }
+        }
+    }
+
+    if order.len() != constrained.len() {
+        let cycle = find_cycle(&predecessors, &in_degree)
+            .iter()
+            .map(|i| EntityView::new_from(world, systems[*i]).to_string())
+            .collect::<Vec<_>>()
+            .join(" -> ");
+        return Err(format!(
+            "{}: system ordering constraints contain a cycle: {}",
+            FlecsErrorCode::InvalidOperation,
+            cycle
+        ));
+    }
+
+    Ok(order
+        .into_iter()
+        .zip(&constrained)
+        .map(|(system, position)| (systems[system], *systems[*position]))
+        .collect())
+}
+
+/// Returns the entities that have `id`, including disabled entities.
+fn entities_with(world: WorldRef, id: u64) -> Vec<Entity> {
+    let mut entities = Vec::new();
+    let mut it = unsafe { sys::ecs_each_id(world.world_ptr(), id) };
+    while unsafe { sys::ecs_each_next(&mut it) } {
+        for i in 0..it.count as usize {
+            entities.push(Entity(unsafe { *it.entities.add(i) }));
+        }
+    }
+    entities
+}
+
+/// Returns a cycle among the systems that couldn't be sorted, in the order in which the
+/// constraints require the systems to run.
+fn find_cycle(predecessors: &[Vec<usize>], in_degree: &[usize]) -> Vec<usize> {
+    // every remaining system has a remaining predecessor, so walking back eventually repeats
+    let mut current = in_degree.iter().position(|d| *d > 0).unwrap_or_default();
+    let mut path = Vec::new();
+    while !path.contains(&current) {
+        path.push(current);
+        current = predecessors[current]
+            .iter()
+            .copied()
+            .find(|p| in_degree[*p] > 0)
+            .unwrap_or(current);
+    }
+    let start = path.iter().position(|i| *i == current).unwrap_or_default();
+    let mut cycle = path.split_off(start);
+    cycle.reverse();
+    // start at the system that was created first, so that the reported cycle is stable
+    let first = (0..cycle.len())
+        .min_by_key(|i| cycle[*i])
+        .unwrap_or_default();
+    cycle.rotate_left(first);
+    cycle.push(cycle[0]);
+    cycle
+}
@@ -0,0 +1,167 @@
+//! The run action that is installed on systems, which skips systems whose run conditions were
+//! false or that run after a system error stopped the pipeline, and reports the systems that a
+//! pipeline runs to the system profiler.
+//!
+//! The run action wraps the run action of the system, or iterates the system in the same way
+//! as flecs does if it doesn't have one. Systems that are built with [`SystemBuilder`] get it
+//! when they are built, other systems before a pipeline runs.
+
+use std::ffi::c_void;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use super::RunCondition;
+use crate::core::*;
+use crate::sys;
+
+/// The state of the run action of a system, and the run action that it replaces.
+pub(crate) struct SystemRun {
+    world: *mut sys::ecs_world_t,
+    system: Entity,
+    pub(crate) conditions: Vec<RunCondition>,
+    /// The result of the last evaluation of the conditions.
+    pub(crate) enabled: AtomicBool,
+    run: sys::ecs_run_action_t,
+    run_ctx: *mut c_void,
+    run_ctx_free: sys::ecs_ctx_free_t,
+}
+
+/// Returns the state of the run action of a system, after installing the run action if the
+/// system doesn't have it yet.
+pub(crate) fn system_run(world: WorldRef, system: Entity) -> *mut SystemRun {
+    if let Some(&run) = world.world_ctx().system_runs.get(&system) {
+        return run;
+    }
+
+    let world_ptr = world.real_world().world_ptr_mut();
+    let system_ptr = unsafe { sys::ecs_system_get(world_ptr, *system) } as *mut sys::ecs_system_t;
+    ecs_assert!(
+        !system_ptr.is_null(),
+        FlecsErrorCode::InvalidParameter,
+        "entity is not a system"
+    );
+
+    let system_data = unsafe { &mut *system_ptr };
+    let run = Box::into_raw(Box::new(SystemRun {
+        world: world_ptr,
+        system,
+        conditions: Vec::new(),
+        enabled: AtomicBool::new(true),
+        run: system_data.run,
+        run_ctx: system_data.run_ctx,
+        run_ctx_free: system_data.run_ctx_free,
+    }));
+    system_data.run = Some(run_system);
+    system_data.run_ctx = run as *mut c_void;
+    system_data.run_ctx_free = Some(free_system_run);
+    world.world_ctx_mut().system_runs.insert(system, run);
+    run
+}
+
+/// Replace the run action that the run action of a system wraps, which keeps the run conditions
+/// of the system. Returns false if the system doesn't have the run action.
+pub(crate) fn replace_wrapped_run(
+    world: WorldRef,
+    system: Entity,
+    run: sys::ecs_run_action_t,
+    run_ctx: *mut c_void,
+    run_ctx_free: sys::ecs_ctx_free_t,
+) -> bool {
+    let Some(&system_run) = world.world_ctx().system_runs.get(&system) else {
+        return false;
+    };
+    let system_run = unsafe { &mut *system_run };
+    system_run.run = run;
+    replace_context(
+        &mut system_run.run_ctx,
+        &mut system_run.run_ctx_free,
+        run_ctx,
+        run_ctx_free,
+    );
+    true
+}
+
+/// Install the run action on the systems that don't have it yet, such as the systems of
+/// flecs modules.
+pub(crate) fn install_system_runs(world: WorldRef) {
+    let world_ptr = world.world_ptr_mut();
+    let mut systems = Vec::new();
+    let mut it = unsafe { sys::ecs_each_id(world_ptr, flecs::system::System::ID) };
+    while unsafe { sys::ecs_each_next(&mut it) } {
+        for i in 0..it.count as usize {
+            systems.push(Entity(unsafe { *it.entities.add(i) }));
+        }
+    }
+
+    let world_ctx = world.world_ctx();
+    for system in systems {
+        if !world_ctx.system_runs.contains_key(&system) {
+            system_run(world, system);
+        }
+    }
+}
+
+/// The run action of systems, which runs the system in the same way as flecs would have if
+/// its run conditions were true.
+unsafe extern "C" fn run_system(it: *mut sys::ecs_iter_t) {
+    let run = unsafe { &*((*it).run_ctx as *const SystemRun) };
+    if !run.enabled.load(Ordering::Relaxed) || pipeline_stopped(run.world) {
+        // flecs finalizes the iterator of systems without terms after the run action
+        let system = unsafe { sys::ecs_system_get((*it).real_world, (*it).system) };
+        if unsafe { (*(*system).query).term_count } != 0 {
+            unsafe { sys::ecs_iter_fini(it) };
+        }
+        return;
+    }
+
+    #[cfg(feature = "flecs_pipeline")]
+    unsafe {
+        crate::addons::pipeline::run_profiled(it, || invoke(run, it));
+    }
+    #[cfg(not(feature = "flecs_pipeline"))]
+    unsafe {
+        invoke(run, it);
+    }
+}
+
+/// Returns whether a system error stopped the pipeline, see `SystemErrorPolicy::Stop`.
+fn pipeline_stopped(_world: *mut sys::ecs_world_t) -> bool {
+    #[cfg(feature = "flecs_pipeline")]
+    {
+        let world = unsafe { WorldRef::from_ptr(_world) };
+        world.world_ctx().pipeline_stopped.load(Ordering::Relaxed)
+    }
+    #[cfg(not(feature = "flecs_pipeline"))]
+    false
+}
+
+/// Invoke the run action that the run action of the system replaced.
+unsafe fn invoke(run: &SystemRun, it: *mut sys::ecs_iter_t) {
+    unsafe {
+        (*it).run_ctx = run.run_ctx;
+        match run.run {
+            Some(run) => run(it),
+            None => {
+                let callback = (*it)
+                    .callback
+                    .expect("system without run action or callback");
+                while sys::ecs_iter_next(it) {
+                    #[cfg(feature = "flecs_pipeline")]
+                    crate::addons::pipeline::count_profiled_entities(&*it);
+                    callback(it);
+                }
+            }
+        }
+    }
+}
+
+unsafe extern "C" fn free_system_run(ctx: *mut c_void) {
+    let run = unsafe { Box::from_raw(ctx as *mut SystemRun) };
+    if let Some(run_ctx_free) = run.run_ctx_free {
+        unsafe { run_ctx_free(run.run_ctx) };
+    }
+    if unsafe { sys::ecs_is_fini(run.world) } {
+        return;
+    }
+    let world = unsafe { WorldRef::from_ptr(run.world) };
+    world.world_ctx_mut().system_runs.remove(&run.system);
+}
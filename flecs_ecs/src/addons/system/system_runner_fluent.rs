@@ -2,6 +2,7 @@
 //!
 use std::os::raw::c_void;
 
+use crate::core::callback_panic::resume_callback_panic;
 use crate::core::*;
 use crate::sys;
 
@@ -71,6 +72,8 @@ impl<'a> Drop for SystemRunnerFluent<'a> {
                 );
             }
         } else {
+            // workers use the result of the last evaluation instead, conditions aren't `Send`
+            crate::addons::system::eval_system_conditions(self.stage.real_world(), Entity(self.id));
             unsafe {
                 sys::ecs_run(
                     self.stage.world_ptr_mut(),
@@ -80,5 +83,6 @@ impl<'a> Drop for SystemRunnerFluent<'a> {
                 );
             }
         }
+        resume_callback_panic(self.stage.world_ptr());
     }
 }
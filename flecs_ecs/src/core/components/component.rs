@@ -1,6 +1,6 @@
 //! Registering and working with components
 
-use std::{marker::PhantomData, ops::Deref, os::raw::c_void, ptr};
+use std::{marker::PhantomData, ops::Deref, os::raw::c_void};
 
 use crate::core::*;
 use crate::sys;
@@ -82,221 +82,196 @@ impl<'a, T: ComponentId> Component<'a, T> {
         self.base.entity
     }
 
-    /// Get the binding context for the component.
-    ///
-    /// # Arguments
+    /// Register on add hook.
     ///
-    /// * `type_hooks`: the type hooks.
+    /// A component can have multiple hooks, which are invoked in the order they were registered.
     ///
     /// # See also
     ///
-    /// * C++ API: `component::get_binding_context`
-    #[doc(alias = "component::get_binding_context")]
-    fn get_binding_context(type_hooks: &mut sys::ecs_type_hooks_t) -> &mut ComponentBindingCtx {
-        let mut binding_ctx: *mut ComponentBindingCtx = type_hooks.binding_ctx as *mut _;
-
-        if binding_ctx.is_null() {
-            let new_binding_ctx = Box::<ComponentBindingCtx>::default();
-            let static_ref = Box::leak(new_binding_ctx);
-            binding_ctx = static_ref;
-            type_hooks.binding_ctx = binding_ctx as *mut c_void;
-            type_hooks.binding_ctx_free = Some(Self::binding_ctx_drop);
-        }
-        unsafe { &mut *binding_ctx }
+    /// * [`Component::on_add_handle()`]
+    /// * C++ API: `component::on_add`
+    #[doc(alias = "component::on_add")]
+    pub fn on_add<Func>(&mut self, func: Func) -> &mut Self
+    where
+        Func: FnMut(EntityView, &mut T) + 'static,
+    {
+        self.on_add_handle(func);
+        self
     }
 
-    /// Get the type hooks for the component.
+    /// Register on add hook and return a handle that can be used to remove it.
     ///
     /// # See also
     ///
-    /// * C++ API: `component::get_hooks`
-    #[doc(alias = "component::get_hooks")]
-    pub fn get_hooks(&self) -> sys::ecs_type_hooks_t {
-        let type_hooks: *const sys::ecs_type_hooks_t =
-            unsafe { sys::ecs_get_hooks_id(self.world.world_ptr(), *self.id) };
-        if type_hooks.is_null() {
-            sys::ecs_type_hooks_t::default()
-        } else {
-            unsafe { *type_hooks }
-        }
+    /// * [`Component::on_add()`]
+    /// * [`HookHandle::remove()`]
+    pub fn on_add_handle<Func>(&mut self, func: Func) -> HookHandle<'a>
+    where
+        Func: FnMut(EntityView, &mut T) + 'static,
+    {
+        self.base
+            .insert_hook(HookKind::OnAdd, func, Self::run_hook::<Func>)
     }
 
-    /// Function to free the binding context.
-    ///
-    /// # See also
+    /// Register on remove hook.
     ///
-    /// * C++ API: `component::binding_ctx_free`
-    #[doc(alias = "component::binding_ctx_free")]
-    unsafe extern "C" fn binding_ctx_drop(ptr: *mut c_void) {
-        let ptr_struct: *mut ComponentBindingCtx = ptr as *mut ComponentBindingCtx;
-        unsafe {
-            ptr::drop_in_place(ptr_struct);
-        }
-    }
-
-    /// Register on add hook.
+    /// A component can have multiple hooks, which are invoked in the order they were registered.
     ///
     /// # See also
     ///
-    /// * C++ API: `component::on_add`
-    #[doc(alias = "component::on_add")]
-    pub fn on_add<Func>(&mut self, func: Func) -> &mut Self
+    /// * [`Component::on_remove_handle()`]
+    /// * C++ API: `component::on_remove`
+    #[doc(alias = "component::on_remove")]
+    pub fn on_remove<Func>(&mut self, func: Func) -> &mut Self
     where
         Func: FnMut(EntityView, &mut T) + 'static,
     {
-        let mut type_hooks: sys::ecs_type_hooks_t = self.get_hooks();
-
-        ecs_assert!(
-            type_hooks.on_add.is_none(),
-            FlecsErrorCode::InvalidOperation,
-            "on_add hook already set for component {}",
-            std::any::type_name::<T>()
-        );
-
-        let binding_ctx = Self::get_binding_context(&mut type_hooks);
-        let boxed_func = Box::new(func);
-        let static_ref = Box::leak(boxed_func);
-        binding_ctx.on_add = Some(static_ref as *mut _ as *mut c_void);
-        binding_ctx.free_on_add = Some(Self::on_add_drop::<Func>);
-        type_hooks.on_add = Some(Self::run_add::<Func>);
-        unsafe { sys::ecs_set_hooks_id(self.world.world_ptr_mut(), *self.id, &type_hooks) };
+        self.on_remove_handle(func);
         self
     }
 
-    /// Register on remove hook.
+    /// Register on remove hook and return a handle that can be used to remove it.
     ///
     /// # See also
     ///
-    /// * C++ API: `component::on_remove`
-    #[doc(alias = "component::on_remove")]
-    pub fn on_remove<Func>(&mut self, func: Func) -> &mut Self
+    /// * [`Component::on_remove()`]
+    /// * [`HookHandle::remove()`]
+    pub fn on_remove_handle<Func>(&mut self, func: Func) -> HookHandle<'a>
     where
         Func: FnMut(EntityView, &mut T) + 'static,
     {
-        let mut type_hooks: sys::ecs_type_hooks_t = self.get_hooks();
-
-        ecs_assert!(
-            type_hooks.on_remove.is_none(),
-            FlecsErrorCode::InvalidOperation,
-            "on_remove hook already set for component {}",
-            std::any::type_name::<T>()
-        );
-
-        let binding_ctx = Self::get_binding_context(&mut type_hooks);
-        let boxed_func = Box::new(func);
-        let static_ref = Box::leak(boxed_func);
-        binding_ctx.on_remove = Some(static_ref as *mut _ as *mut c_void);
-        binding_ctx.free_on_remove = Some(Self::on_remove_drop::<Func>);
-        type_hooks.on_remove = Some(Self::run_remove::<Func>);
-        unsafe { sys::ecs_set_hooks_id(self.world.world_ptr_mut(), *self.id, &type_hooks) };
-        self
+        self.base
+            .insert_hook(HookKind::OnRemove, func, Self::run_hook::<Func>)
     }
 
     /// Register on set hook.
     ///
+    /// A component can have multiple hooks, which are invoked in the order they were registered.
+    ///
     /// # See also
     ///
+    /// * [`Component::on_set_handle()`]
     /// * C++ API: `component::on_set`
     #[doc(alias = "component::on_set")]
     pub fn on_set<Func>(&mut self, func: Func) -> &mut Self
     where
         Func: FnMut(EntityView, &mut T) + 'static,
     {
-        let mut type_hooks: sys::ecs_type_hooks_t = self.get_hooks();
-
-        ecs_assert!(
-            type_hooks.on_set.is_none(),
-            FlecsErrorCode::InvalidOperation,
-            "on_set hook already set for component {}",
-            std::any::type_name::<T>()
-        );
-
-        let binding_ctx = Self::get_binding_context(&mut type_hooks);
-        let boxed_func = Box::new(func);
-        let static_ref = Box::leak(boxed_func);
-        binding_ctx.on_set = Some(static_ref as *mut _ as *mut c_void);
-        binding_ctx.free_on_set = Some(Self::on_set_drop::<Func>);
-        type_hooks.on_set = Some(Self::run_set::<Func>);
-        unsafe { sys::ecs_set_hooks_id(self.world.world_ptr_mut(), *self.id, &type_hooks) };
+        self.on_set_handle(func);
         self
     }
 
-    /// Function to free the on add hook.
-    unsafe extern "C" fn on_add_drop<Func>(func: *mut c_void)
+    /// Register on set hook and return a handle that can be used to remove it.
+    ///
+    /// # See also
+    ///
+    /// * [`Component::on_set()`]
+    /// * [`HookHandle::remove()`]
+    pub fn on_set_handle<Func>(&mut self, func: Func) -> HookHandle<'a>
     where
         Func: FnMut(EntityView, &mut T) + 'static,
     {
-        let ptr_func: *mut Func = func as *mut Func;
-        unsafe {
-            ptr::drop_in_place(ptr_func);
-        }
+        self.base
+            .insert_hook(HookKind::OnSet, func, Self::run_hook::<Func>)
     }
 
-    /// Function to free the on remove hook.
-    unsafe extern "C" fn on_remove_drop<Func>(func: *mut c_void)
+    /// Register on replace hook.
+    ///
+    /// The hook is invoked with the old and the new value right before a `set` overwrites an
+    /// existing value of the component, which also applies to pairs that use `T` as data type
+    /// and to [`EntityView::modify()`]. Observers can subscribe to the same moment with the
+    /// [`flecs::OnReplace`] event.
+    ///
+    /// A deferred `set` overwrites a value that the entity already owns in place, when the `set`
+    /// is issued, so that is also when the hook is invoked. A deferred `set` of a component that
+    /// the entity doesn't own yet adds it when the commands are merged, and doesn't invoke the
+    /// hook, also not when the staged value is set again before the merge.
+    ///
+    /// The hook is not invoked when the component is first added, nor when a value is changed
+    /// through `get` and then flagged with `modified`, as the old value is no longer available.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use flecs_ecs::prelude::*;
+    ///
+    /// #[derive(Component)]
+    /// struct Position {
+    ///     x: i32,
+    ///     y: i32,
+    /// }
+    ///
+    /// let world = World::new();
+    ///
+    /// world
+    ///     .component::<Position>()
+    ///     .on_replace(|_entity, old, new| {
+    ///         println!("moved from ({}, {}) to ({}, {})", old.x, old.y, new.x, new.y);
+    ///     });
+    ///
+    /// let e = world.entity().set(Position { x: 1, y: 2 }); // no replace, value is added
+    /// e.set(Position { x: 3, y: 4 }); // prints "moved from (1, 2) to (3, 4)"
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// * [`Component::on_replace_handle()`]
+    /// * [`Component::on_set()`]
+    /// * [`flecs::OnReplace`]
+    pub fn on_replace<Func>(&mut self, func: Func) -> &mut Self
     where
-        Func: FnMut(EntityView, &mut T) + 'static,
+        Func: FnMut(EntityView, &T, &T) + 'static,
     {
-        let ptr_func: *mut Func = func as *mut Func;
-        unsafe {
-            ptr::drop_in_place(ptr_func);
-        }
+        self.on_replace_handle(func);
+        self
     }
 
-    /// Function to free the on set hook.
-    unsafe extern "C" fn on_set_drop<Func>(func: *mut c_void)
+    /// Register on replace hook and return a handle that can be used to remove it.
+    ///
+    /// # See also
+    ///
+    /// * [`Component::on_replace()`]
+    /// * [`HookHandle::remove()`]
+    pub fn on_replace_handle<Func>(&mut self, func: Func) -> HookHandle<'a>
     where
-        Func: FnMut(EntityView, &mut T) + 'static,
+        Func: FnMut(EntityView, &T, &T) + 'static,
     {
-        let ptr_func: *mut Func = func as *mut Func;
-        unsafe {
-            ptr::drop_in_place(ptr_func);
-        }
+        let func = Box::into_raw(Box::new(func)) as *mut c_void;
+        let id = self.base.binding_context().insert_replace_hook(
+            func,
+            ComponentBindingCtx::free_hook::<Func>,
+            Self::run_replace::<Func>,
+        );
+        HookHandle::new(self.world, self.id, HookKind::OnReplace, id)
     }
 
-    /// Function to run the on add hook.
-    unsafe extern "C" fn run_add<Func>(iter: *mut sys::ecs_iter_t)
+    /// Function to run a hook for every entity the hook iterator was invoked for.
+    unsafe fn run_hook<Func>(func: *mut c_void, iter: *mut sys::ecs_iter_t)
     where
         Func: FnMut(EntityView, &mut T) + 'static,
     {
-        let ctx: *mut ComponentBindingCtx = (*iter).callback_ctx as *mut _;
-        let on_add = (*ctx).on_add.unwrap();
-        let on_add = on_add as *mut Func;
-        let on_add = &mut *on_add;
-        let world = WorldRef::from_ptr((*iter).world);
-        let entity = EntityView::new_from(world, *(*iter).entities);
-        let component: *mut T = ecs_field::<T>(iter, 0);
-        on_add(entity, &mut *component);
-    }
-
-    /// Function to run the on set hook.
-    unsafe extern "C" fn run_set<Func>(iter: *mut sys::ecs_iter_t)
-    where
-        Func: FnMut(EntityView, &mut T) + 'static,
-    {
-        let ctx: *mut ComponentBindingCtx = unsafe { (*iter).callback_ctx as *mut _ };
-        let on_set = unsafe { (*ctx).on_set.unwrap() };
-        let on_set = on_set as *mut Func;
-        let on_set = unsafe { &mut *on_set };
+        let hook = unsafe { &mut *(func as *mut Func) };
         let world = unsafe { WorldRef::from_ptr((*iter).world) };
-        let entity = EntityView::new_from(world, *(*iter).entities);
         let component: *mut T = unsafe { ecs_field::<T>(iter, 0) };
-        on_set(entity, unsafe { &mut *component });
+        for i in 0..unsafe { (*iter).count } as usize {
+            let entity = EntityView::new_from(world, unsafe { *(*iter).entities.add(i) });
+            hook(entity, unsafe { &mut *component.add(i) });
+        }
     }
 
-    /// Function to run the on remove hook.
-    unsafe extern "C" fn run_remove<Func>(iter: *mut sys::ecs_iter_t)
-    where
-        Func: FnMut(EntityView, &mut T) + 'static,
+    /// Function to run the on replace hook.
+    unsafe fn run_replace<Func>(
+        func: *mut c_void,
+        entity: EntityView,
+        old: *const c_void,
+        new: *const c_void,
+    ) where
+        Func: FnMut(EntityView, &T, &T) + 'static,
     {
-        let ctx: *mut ComponentBindingCtx = unsafe { (*iter).callback_ctx as *mut _ };
-        let on_remove = unsafe { (*ctx).on_remove.unwrap() };
-        let on_remove = on_remove as *mut Func;
-        let on_remove = unsafe { &mut *on_remove };
-        let world = unsafe { WorldRef::from_ptr((*iter).world) };
-        let entity = EntityView::new_from(world, *(*iter).entities);
-        let component: *mut T = unsafe { ecs_field::<T>(iter, 0) };
-        on_remove(entity, unsafe { &mut *component });
+        let on_replace = unsafe { &mut *(func as *mut Func) };
+        on_replace(entity, unsafe { &*(old as *const T) }, unsafe {
+            &*(new as *const T)
+        });
     }
 }
 
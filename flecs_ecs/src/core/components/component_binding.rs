@@ -1,15 +1,53 @@
 #![doc(hidden)]
+use std::cell::RefCell;
 use std::ffi::c_void;
 
+use crate::core::callback_panic::catch_callback_panic;
+use crate::core::*;
+use crate::sys;
+
 type EcsCtxFreeT = unsafe extern "C" fn(*mut c_void);
 
+/// Invokes a hook closure for the entities of a hook iterator.
+pub(crate) type RunHookT = unsafe fn(*mut c_void, *mut sys::ecs_iter_t);
+
+/// Invokes a typed `on_replace` hook with the entity, the old and the new value.
+pub(crate) type RunReplaceT = unsafe fn(*mut c_void, EntityView, *const c_void, *const c_void);
+
+/// Runs a lifecycle closure on a single value, see [`LifecycleBuilder`].
+pub(crate) type RunValueT = unsafe fn(*mut c_void, *mut c_void);
+
+/// Runs a copy closure that writes a copy of the value at `src` (3rd argument) to `dst`.
+pub(crate) type RunCopyT = unsafe fn(*mut c_void, *mut c_void, *const c_void);
+
+pub(crate) struct LifecycleAction<R> {
+    pub(crate) func: *mut c_void,
+    pub(crate) free: EcsCtxFreeT,
+    pub(crate) run: R,
+}
+
+pub(crate) struct HookEntry<R> {
+    id: u64,
+    func: *mut c_void,
+    free: EcsCtxFreeT,
+    run: R,
+    removed: bool,
+}
+
 pub(crate) struct ComponentBindingCtx {
-    pub(crate) on_add: Option<*mut c_void>,
-    pub(crate) on_remove: Option<*mut c_void>,
-    pub(crate) on_set: Option<*mut c_void>,
-    pub(crate) free_on_add: Option<EcsCtxFreeT>,
-    pub(crate) free_on_remove: Option<EcsCtxFreeT>,
-    pub(crate) free_on_set: Option<EcsCtxFreeT>,
+    on_add: Vec<HookEntry<RunHookT>>,
+    on_remove: Vec<HookEntry<RunHookT>>,
+    on_set: Vec<HookEntry<RunHookT>>,
+    on_replace: Vec<HookEntry<RunReplaceT>>,
+    pub(crate) ctor: Option<LifecycleAction<RunValueT>>,
+    pub(crate) dtor: Option<LifecycleAction<RunValueT>>,
+    pub(crate) copy: Option<LifecycleAction<RunCopyT>>,
+    pub(crate) on_move: Option<LifecycleAction<RunValueT>>,
+    /// Values that were moved into another value, which flecs destroys afterwards.
+    moved_from: RefCell<Vec<*mut c_void>>,
+    next_hook_id: u64,
+    // hooks that are removed while the hooks are running are only freed afterwards
+    running: u32,
 }
 
 impl Drop for ComponentBindingCtx {
@@ -18,53 +56,228 @@ impl Drop for ComponentBindingCtx {
             return;
         }
 
-        if let Some(on_add) = self.on_add {
-            if let Some(free_on_add) = self.free_on_add {
-                unsafe { free_on_add(on_add) };
-            }
-        }
-        if let Some(on_remove) = self.on_remove {
-            if let Some(free_on_remove) = self.free_on_remove {
-                unsafe { free_on_remove(on_remove) };
-            }
+        for (func, free) in self
+            .on_add
+            .iter()
+            .chain(self.on_remove.iter())
+            .chain(self.on_set.iter())
+            .map(|entry| (entry.func, entry.free))
+            .chain(self.on_replace.iter().map(|entry| (entry.func, entry.free)))
+        {
+            unsafe { free(func) };
         }
-        if let Some(on_set) = self.on_set {
-            if let Some(free_on_set) = self.free_on_set {
-                unsafe { free_on_set(on_set) };
-            }
+
+        for (func, free) in [&self.ctor, &self.dtor, &self.on_move]
+            .into_iter()
+            .flatten()
+            .map(|action| (action.func, action.free))
+            .chain(self.copy.iter().map(|action| (action.func, action.free)))
+        {
+            unsafe { free(func) };
         }
     }
 }
 
-#[allow(clippy::derivable_impls)]
 impl Default for ComponentBindingCtx {
     fn default() -> Self {
         Self {
-            on_add: None,
-            on_remove: None,
-            on_set: None,
-            free_on_add: None,
-            free_on_remove: None,
-            free_on_set: None,
+            on_add: Vec::new(),
+            on_remove: Vec::new(),
+            on_set: Vec::new(),
+            on_replace: Vec::new(),
+            ctor: None,
+            dtor: None,
+            copy: None,
+            on_move: None,
+            moved_from: RefCell::new(Vec::new()),
+            next_hook_id: 1,
+            running: 0,
         }
     }
 }
+
 impl ComponentBindingCtx {
-    pub(crate) fn new(
-        on_add: Option<*mut c_void>,
-        on_remove: Option<*mut c_void>,
-        on_set: Option<*mut c_void>,
-        free_on_add: Option<EcsCtxFreeT>,
-        free_on_remove: Option<EcsCtxFreeT>,
-        free_on_set: Option<EcsCtxFreeT>,
-    ) -> Self {
-        Self {
-            on_add,
-            on_remove,
-            on_set,
-            free_on_add,
-            free_on_remove,
-            free_on_set,
+    /// Remembers that the value at `ptr` was moved, so that destroying it is skipped.
+    pub(crate) fn set_moved_from(&self, ptr: *mut c_void) {
+        self.moved_from.borrow_mut().push(ptr);
+    }
+
+    /// Returns whether the value at `ptr` was moved, and forgets it.
+    pub(crate) fn take_moved_from(&self, ptr: *mut c_void) -> bool {
+        let mut moved_from = self.moved_from.borrow_mut();
+        match moved_from.iter().position(|&moved| moved == ptr) {
+            Some(index) => {
+                moved_from.swap_remove(index);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn next_id(&mut self) -> u64 {
+        let id = self.next_hook_id;
+        self.next_hook_id += 1;
+        id
+    }
+
+    fn iter_hooks(&mut self, kind: HookKind) -> &mut Vec<HookEntry<RunHookT>> {
+        match kind {
+            HookKind::OnAdd => &mut self.on_add,
+            HookKind::OnRemove => &mut self.on_remove,
+            HookKind::OnSet => &mut self.on_set,
+            HookKind::OnReplace => unreachable!("on_replace hooks are not invoked by an iterator"),
+        }
+    }
+
+    /// Appends a hook that is invoked by the `on_add`, `on_remove` or `on_set` hook of flecs.
+    pub(crate) fn insert_hook(
+        &mut self,
+        kind: HookKind,
+        func: *mut c_void,
+        free: EcsCtxFreeT,
+        run: RunHookT,
+    ) -> u64 {
+        let id = self.next_id();
+        self.iter_hooks(kind).push(HookEntry {
+            id,
+            func,
+            free,
+            run,
+            removed: false,
+        });
+        id
+    }
+
+    /// Appends an `on_replace` hook.
+    pub(crate) fn insert_replace_hook(
+        &mut self,
+        func: *mut c_void,
+        free: EcsCtxFreeT,
+        run: RunReplaceT,
+    ) -> u64 {
+        let id = self.next_id();
+        self.on_replace.push(HookEntry {
+            id,
+            func,
+            free,
+            run,
+            removed: false,
+        });
+        id
+    }
+
+    /// Removes a hook, returns `false` if there is no hook with the id.
+    pub(crate) fn remove_hook(&mut self, kind: HookKind, id: u64) -> bool {
+        let removed = match kind {
+            HookKind::OnReplace => Self::mark_removed(&mut self.on_replace, id),
+            kind => Self::mark_removed(self.iter_hooks(kind), id),
+        };
+
+        if removed && self.running == 0 {
+            self.purge();
+        }
+        removed
+    }
+
+    pub(crate) fn has_replace_hooks(&self) -> bool {
+        self.on_replace.iter().any(|entry| !entry.removed)
+    }
+
+    fn mark_removed<R>(entries: &mut [HookEntry<R>], id: u64) -> bool {
+        match entries
+            .iter_mut()
+            .find(|entry| entry.id == id && !entry.removed)
+        {
+            Some(entry) => {
+                entry.removed = true;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn purge(&mut self) {
+        fn purge_entries<R>(entries: &mut Vec<HookEntry<R>>) {
+            entries.retain(|entry| {
+                if entry.removed {
+                    unsafe { (entry.free)(entry.func) };
+                }
+                !entry.removed
+            });
+        }
+
+        purge_entries(&mut self.on_add);
+        purge_entries(&mut self.on_remove);
+        purge_entries(&mut self.on_set);
+        purge_entries(&mut self.on_replace);
+    }
+
+    /// Runs the hooks of a slot one after the other, in registration order.
+    ///
+    /// The entries are re-read on every step through the raw pointer, as hooks are allowed to
+    /// register or remove hooks of the same component.
+    unsafe fn run_iter_hooks(ctx: *mut Self, kind: HookKind, iter: *mut sys::ecs_iter_t) {
+        unsafe {
+            (*ctx).running += 1;
+            let mut index = 0;
+            while let Some(entry) = (*ctx).iter_hooks(kind).get(index) {
+                let (func, run, removed) = (entry.func, entry.run, entry.removed);
+                if !removed {
+                    catch_callback_panic((*iter).world, || run(func, iter));
+                }
+                index += 1;
+            }
+            (*ctx).running -= 1;
+            if (*ctx).running == 0 {
+                (*ctx).purge();
+            }
+        }
+    }
+
+    /// Runs the `on_replace` hooks for a component value that is about to be overwritten.
+    pub(crate) unsafe fn run_replace_hooks(
+        ctx: *mut Self,
+        entity: EntityView,
+        old: *const c_void,
+        new: *const c_void,
+    ) {
+        unsafe {
+            (*ctx).running += 1;
+            let mut index = 0;
+            loop {
+                let on_replace = &(*ctx).on_replace;
+                let Some(entry) = on_replace.get(index) else {
+                    break;
+                };
+                let (func, run, removed) = (entry.func, entry.run, entry.removed);
+                if !removed {
+                    catch_callback_panic(entity.world_ptr(), || run(func, entity, old, new));
+                }
+                index += 1;
+            }
+            (*ctx).running -= 1;
+            if (*ctx).running == 0 {
+                (*ctx).purge();
+            }
         }
     }
+
+    pub(crate) unsafe extern "C" fn run_on_add(iter: *mut sys::ecs_iter_t) {
+        unsafe { Self::run_iter_hooks((*iter).callback_ctx as *mut Self, HookKind::OnAdd, iter) };
+    }
+
+    pub(crate) unsafe extern "C" fn run_on_remove(iter: *mut sys::ecs_iter_t) {
+        unsafe {
+            Self::run_iter_hooks((*iter).callback_ctx as *mut Self, HookKind::OnRemove, iter);
+        };
+    }
+
+    pub(crate) unsafe extern "C" fn run_on_set(iter: *mut sys::ecs_iter_t) {
+        unsafe { Self::run_iter_hooks((*iter).callback_ctx as *mut Self, HookKind::OnSet, iter) };
+    }
+
+    /// Function to free a hook closure.
+    pub(crate) unsafe extern "C" fn free_hook<Func>(func: *mut c_void) {
+        unsafe { drop(Box::from_raw(func as *mut Func)) };
+    }
 }
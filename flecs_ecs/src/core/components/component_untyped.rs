@@ -1,6 +1,7 @@
-use std::ops::Deref;
+use std::{ffi::c_void, ops::Deref};
 
 use crate::core::*;
+use crate::sys;
 
 /// Untyped component class.
 #[derive(Clone, Copy)]
@@ -43,6 +44,260 @@ impl<'a> UntypedComponent<'a> {
     pub fn as_entity(&self) -> EntityView<'a> {
         self.entity
     }
+
+    /// Get the binding context for the component.
+    ///
+    /// The context holds the hook closures and lifecycle overrides that are registered with the
+    /// Rust API. Creating it doesn't install any hooks, see [`UntypedComponent::install_hook()`].
+    ///
+    /// # See also
+    ///
+    /// * C++ API: `component::get_binding_context`
+    #[doc(alias = "component::get_binding_context")]
+    pub(crate) fn binding_context(&mut self) -> &mut ComponentBindingCtx {
+        let mut type_hooks: sys::ecs_type_hooks_t = self.get_hooks();
+        let mut binding_ctx: *mut ComponentBindingCtx = type_hooks.binding_ctx as *mut _;
+
+        if binding_ctx.is_null() {
+            let new_binding_ctx = Box::<ComponentBindingCtx>::default();
+            binding_ctx = Box::into_raw(new_binding_ctx);
+            type_hooks.binding_ctx = binding_ctx as *mut c_void;
+            type_hooks.binding_ctx_free = Some(Self::binding_ctx_drop);
+            unsafe {
+                sys::ecs_set_hooks_id(self.world.world_ptr_mut(), *self.entity.id, &type_hooks);
+            };
+        }
+        unsafe { &mut *binding_ctx }
+    }
+
+    /// Installs the trampoline that runs the hook closures of `kind` in the hook slot of flecs,
+    /// if it isn't installed yet. `on_replace` hooks are invoked by `set` and don't have a slot.
+    fn install_hook(&self, kind: HookKind) {
+        let mut type_hooks = self.get_hooks();
+        let (slot, trampoline): (_, unsafe extern "C" fn(*mut sys::ecs_iter_t)) = match kind {
+            HookKind::OnAdd => (&mut type_hooks.on_add, ComponentBindingCtx::run_on_add),
+            HookKind::OnRemove => (
+                &mut type_hooks.on_remove,
+                ComponentBindingCtx::run_on_remove,
+            ),
+            HookKind::OnSet => (&mut type_hooks.on_set, ComponentBindingCtx::run_on_set),
+            HookKind::OnReplace => return,
+        };
+
+        match *slot {
+            Some(installed) if installed as usize == trampoline as usize => {}
+            installed => {
+                ecs_assert!(
+                    installed.is_none(),
+                    FlecsErrorCode::InvalidOperation,
+                    "{:?} hook of component {} is already set by a hook that isn't registered with the Rust API",
+                    kind,
+                    self.entity.path().unwrap_or_default()
+                );
+                *slot = Some(trampoline);
+                unsafe {
+                    sys::ecs_set_hooks_id(self.world.world_ptr_mut(), *self.entity.id, &type_hooks);
+                };
+            }
+        }
+    }
+
+    /// Get the type hooks for the component.
+    ///
+    /// # See also
+    ///
+    /// * C++ API: `component::get_hooks`
+    #[doc(alias = "component::get_hooks")]
+    pub fn get_hooks(&self) -> sys::ecs_type_hooks_t {
+        let type_hooks: *const sys::ecs_type_hooks_t =
+            unsafe { sys::ecs_get_hooks_id(self.world.world_ptr(), *self.entity.id) };
+        if type_hooks.is_null() {
+            sys::ecs_type_hooks_t::default()
+        } else {
+            unsafe { *type_hooks }
+        }
+    }
+
+    /// Function to free the binding context.
+    ///
+    /// # See also
+    ///
+    /// * C++ API: `component::binding_ctx_free`
+    #[doc(alias = "component::binding_ctx_free")]
+    pub(crate) unsafe extern "C" fn binding_ctx_drop(ptr: *mut c_void) {
+        unsafe { drop(Box::from_raw(ptr as *mut ComponentBindingCtx)) };
+    }
+
+    /// Appends a hook closure to the hooks of `kind`.
+    pub(crate) fn insert_hook<Func: 'static>(
+        &mut self,
+        kind: HookKind,
+        func: Func,
+        run: RunHookT,
+    ) -> HookHandle<'a> {
+        self.install_hook(kind);
+        let func = Box::into_raw(Box::new(func)) as *mut c_void;
+        let id = self.binding_context().insert_hook(
+            kind,
+            func,
+            ComponentBindingCtx::free_hook::<Func>,
+            run,
+        );
+        HookHandle::new(self.world, self.entity.id, kind, id)
+    }
+
+    /// Register on add hook.
+    ///
+    /// The hook receives the entity and the raw bytes of the component value, which makes it
+    /// possible to attach hooks to components that don't have a Rust type, such as components
+    /// created at runtime. A component can have multiple hooks, which are invoked in the order
+    /// they were registered.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use flecs_ecs::prelude::*;
+    /// use std::cell::Cell;
+    /// use std::rc::Rc;
+    ///
+    /// #[derive(Component)]
+    /// struct Position {
+    ///     x: i32,
+    ///     y: i32,
+    /// }
+    ///
+    /// let world = World::new();
+    ///
+    /// let id = world.component::<Position>().id();
+    /// let added = Rc::new(Cell::new(0));
+    /// let added_ref = added.clone();
+    ///
+    /// world
+    ///     .component_untyped_id(id)
+    ///     .on_add(move |_entity, bytes| {
+    ///         assert_eq!(bytes.len(), std::mem::size_of::<Position>());
+    ///         added_ref.set(added_ref.get() + 1);
+    ///     });
+    ///
+    /// world.entity().set(Position { x: 1, y: 2 });
+    ///
+    /// assert_eq!(added.get(), 1);
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// * [`UntypedComponent::on_add_handle()`]
+    /// * [`Component::on_add()`]
+    /// * C++ API: `component::on_add`
+    #[doc(alias = "component::on_add")]
+    pub fn on_add<Func>(&mut self, func: Func) -> &mut Self
+    where
+        Func: FnMut(EntityView, &mut [u8]) + 'static,
+    {
+        self.on_add_handle(func);
+        self
+    }
+
+    /// Register on add hook and return a handle that can be used to remove it.
+    ///
+    /// # See also
+    ///
+    /// * [`UntypedComponent::on_add()`]
+    /// * [`HookHandle::remove()`]
+    pub fn on_add_handle<Func>(&mut self, func: Func) -> HookHandle<'a>
+    where
+        Func: FnMut(EntityView, &mut [u8]) + 'static,
+    {
+        self.insert_hook(HookKind::OnAdd, func, Self::run_hook::<Func>)
+    }
+
+    /// Register on remove hook.
+    ///
+    /// The hook receives the entity and the raw bytes of the component value.
+    ///
+    /// # See also
+    ///
+    /// * [`UntypedComponent::on_add()`]
+    /// * [`UntypedComponent::on_remove_handle()`]
+    /// * [`Component::on_remove()`]
+    /// * C++ API: `component::on_remove`
+    #[doc(alias = "component::on_remove")]
+    pub fn on_remove<Func>(&mut self, func: Func) -> &mut Self
+    where
+        Func: FnMut(EntityView, &mut [u8]) + 'static,
+    {
+        self.on_remove_handle(func);
+        self
+    }
+
+    /// Register on remove hook and return a handle that can be used to remove it.
+    ///
+    /// # See also
+    ///
+    /// * [`UntypedComponent::on_remove()`]
+    /// * [`HookHandle::remove()`]
+    pub fn on_remove_handle<Func>(&mut self, func: Func) -> HookHandle<'a>
+    where
+        Func: FnMut(EntityView, &mut [u8]) + 'static,
+    {
+        self.insert_hook(HookKind::OnRemove, func, Self::run_hook::<Func>)
+    }
+
+    /// Register on set hook.
+    ///
+    /// The hook receives the entity and the raw bytes of the component value.
+    ///
+    /// # See also
+    ///
+    /// * [`UntypedComponent::on_add()`]
+    /// * [`UntypedComponent::on_set_handle()`]
+    /// * [`Component::on_set()`]
+    /// * C++ API: `component::on_set`
+    #[doc(alias = "component::on_set")]
+    pub fn on_set<Func>(&mut self, func: Func) -> &mut Self
+    where
+        Func: FnMut(EntityView, &mut [u8]) + 'static,
+    {
+        self.on_set_handle(func);
+        self
+    }
+
+    /// Register on set hook and return a handle that can be used to remove it.
+    ///
+    /// # See also
+    ///
+    /// * [`UntypedComponent::on_set()`]
+    /// * [`HookHandle::remove()`]
+    pub fn on_set_handle<Func>(&mut self, func: Func) -> HookHandle<'a>
+    where
+        Func: FnMut(EntityView, &mut [u8]) + 'static,
+    {
+        self.insert_hook(HookKind::OnSet, func, Self::run_hook::<Func>)
+    }
+
+    /// Invokes an untyped hook for every entity the hook iterator was invoked for.
+    unsafe fn run_hook<Func>(func: *mut c_void, iter: *mut sys::ecs_iter_t)
+    where
+        Func: FnMut(EntityView, &mut [u8]) + 'static,
+    {
+        let hook = unsafe { &mut *(func as *mut Func) };
+        let iter = unsafe { &*iter };
+        let world = unsafe { WorldRef::from_ptr(iter.world) };
+        // an iterator without a field or data pointer gets an empty slice, as a null pointer
+        // can't be turned into a slice
+        let (ptr, size) = if iter.field_count == 0 || unsafe { (*iter.ptrs).is_null() } {
+            (std::ptr::NonNull::<u8>::dangling().as_ptr(), 0)
+        } else {
+            (unsafe { *iter.ptrs } as *mut u8, unsafe { *iter.sizes }
+                as usize)
+        };
+
+        for i in 0..iter.count as usize {
+            let entity = EntityView::new_from(world, unsafe { *iter.entities.add(i) });
+            let bytes = unsafe { std::slice::from_raw_parts_mut(ptr.add(i * size), size) };
+            hook(entity, bytes);
+        }
+    }
 }
 
 #[cfg(feature = "flecs_meta")]
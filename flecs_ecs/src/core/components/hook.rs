@@ -0,0 +1,106 @@
+//! Handles to the hooks that are registered for a component.
+
+use crate::core::*;
+use crate::sys;
+
+/// The hook slot a closure is registered for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookKind {
+    /// Invoked when the component is added to an entity.
+    OnAdd,
+    /// Invoked when the component is removed from an entity.
+    OnRemove,
+    /// Invoked when the component is set or modified.
+    OnSet,
+    /// Invoked right before an existing component value is overwritten by a `set`.
+    OnReplace,
+}
+
+/// Handle to a hook closure that was registered for a component.
+///
+/// A component can have any number of hooks per [`HookKind`], which are invoked in the order they
+/// were registered. The handle can be used to remove a single hook without affecting the others.
+///
+/// # Example
+///
+/// ```
+/// use flecs_ecs::prelude::*;
+/// use std::cell::Cell;
+/// use std::rc::Rc;
+///
+/// #[derive(Component)]
+/// struct Transform {
+///     x: f32,
+/// }
+///
+/// let world = World::new();
+/// let removed = Rc::new(Cell::new(0));
+///
+/// let (network, audio) = (removed.clone(), removed.clone());
+/// let network_hook = world
+///     .component::<Transform>()
+///     .on_remove_handle(move |_, _| network.set(network.get() + 1));
+/// world
+///     .component::<Transform>()
+///     .on_remove_handle(move |_, _| audio.set(audio.get() + 10));
+///
+/// world.entity().set(Transform { x: 0.0 }).destruct();
+/// assert_eq!(removed.get(), 11);
+///
+/// network_hook.remove();
+///
+/// world.entity().set(Transform { x: 0.0 }).destruct();
+/// assert_eq!(removed.get(), 21);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HookHandle<'a> {
+    world: WorldRef<'a>,
+    component: Entity,
+    kind: HookKind,
+    id: u64,
+}
+
+impl<'a> HookHandle<'a> {
+    pub(crate) fn new(
+        world: impl WorldProvider<'a>,
+        component: impl Into<Entity>,
+        kind: HookKind,
+        id: u64,
+    ) -> Self {
+        Self {
+            world: world.world(),
+            component: component.into(),
+            kind,
+            id,
+        }
+    }
+
+    /// Returns the component the hook is registered for.
+    pub fn component(&self) -> EntityView<'a> {
+        EntityView::new_from(self.world, self.component)
+    }
+
+    /// Returns the hook slot the hook is registered for.
+    pub fn kind(&self) -> HookKind {
+        self.kind
+    }
+
+    /// Removes the hook from the component, other hooks of the component are not affected.
+    ///
+    /// When called from within a hook of the same component, the hook is no longer invoked
+    /// but only dropped once the running hooks have finished.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the hook was removed, `false` if it was already removed.
+    pub fn remove(self) -> bool {
+        let world = self.world.world_ptr_mut();
+        let hooks = unsafe { sys::ecs_get_hooks_id(world, *self.component) };
+        if hooks.is_null() || unsafe { (*hooks).binding_ctx }.is_null() {
+            return false;
+        }
+
+        let binding_ctx = unsafe { &mut *((*hooks).binding_ctx as *mut ComponentBindingCtx) };
+        binding_ctx.remove_hook(self.kind, self.id)
+    }
+}
@@ -0,0 +1,417 @@
+//! Closure based overrides for the lifecycle actions of a component.
+
+use std::{ffi::c_void, marker::PhantomData, ptr};
+
+use crate::core::callback_panic::abort_on_callback_panic;
+use crate::core::*;
+use crate::sys;
+
+/// Builder to override the lifecycle actions (constructor, destructor, copy and move) of a
+/// component with closures, as returned by [`Component::lifecycle()`].
+///
+/// Each method replaces the action right away, an action that is not overridden keeps the
+/// implementation derived from the Rust type (`Default`, `Drop` and `Clone`).
+/// Lifecycle actions can only be changed while the component is not in use yet, so overrides
+/// must be registered before the component is added to any entity.
+///
+/// The overrides are invoked by flecs when it constructs, destroys, copies or moves component
+/// values in its storage. Values that are written from Rust with `set` are written directly,
+/// an old value that is overwritten that way is dropped in place without invoking the
+/// destructor override.
+///
+/// A closure that panics aborts the process, as flecs can't recover from a value that was only
+/// partially constructed, copied or moved.
+///
+/// # Example
+///
+/// ```
+/// use flecs_ecs::prelude::*;
+/// use std::cell::Cell;
+/// use std::rc::Rc;
+///
+/// #[derive(Component)]
+/// struct GpuBuffer {
+///     handle: u32,
+/// }
+///
+/// let world = World::new();
+///
+/// let next_handle = Rc::new(Cell::new(1));
+/// let released = Rc::new(Cell::new(0));
+///
+/// let buffer = world.component::<GpuBuffer>();
+/// buffer
+///     .lifecycle()
+///     .ctor({
+///         let next_handle = next_handle.clone();
+///         move || {
+///             let handle = next_handle.get();
+///             next_handle.set(handle + 1);
+///             GpuBuffer { handle }
+///         }
+///     })
+///     .dtor({
+///         let released = released.clone();
+///         move |_buffer| released.set(released.get() + 1)
+///     });
+///
+/// // `GpuBuffer` doesn't implement `Default`, so it's added by id
+/// let e = world.entity().add_id(buffer);
+/// e.get::<&GpuBuffer>(|buffer| assert_eq!(buffer.handle, 1));
+///
+/// e.remove::<GpuBuffer>();
+/// assert_eq!(released.get(), 1);
+/// ```
+pub struct LifecycleBuilder<'a, T: ComponentId> {
+    component: UntypedComponent<'a>,
+    _marker: PhantomData<T>,
+}
+
+impl<'a, T: ComponentId> LifecycleBuilder<'a, T> {
+    pub(crate) fn new(component: UntypedComponent<'a>) -> Self {
+        Self {
+            component,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Override the constructor, which creates the value of a component that is added
+    /// without a value, e.g. with `add`.
+    ///
+    /// This makes it possible to add components that don't implement `Default` by id, e.g. with
+    /// `add_id`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the component is already in use.
+    pub fn ctor<Func>(&mut self, func: Func) -> &mut Self
+    where
+        Func: FnMut() -> T + 'static,
+    {
+        let action = Self::new_action(func, Self::run_ctor::<Func>);
+        self.install(
+            |ctx| ctx.ctor.replace(action),
+            |hooks| {
+                hooks.ctor = Some(closure_ctor::<T>);
+            },
+        )
+    }
+
+    /// Override the destructor, which receives the values that are destroyed by flecs, e.g.
+    /// when a component is removed or its entity is deleted.
+    ///
+    /// The closure takes ownership of the value, the value is only dropped if the closure
+    /// drops it. This makes it possible to return values to a pool.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the component is already in use.
+    pub fn dtor<Func>(&mut self, func: Func) -> &mut Self
+    where
+        Func: FnMut(T) + 'static,
+    {
+        let action = Self::new_action(func, Self::run_dtor::<Func>);
+        self.install(|ctx| ctx.dtor.replace(action), |_| {})
+    }
+
+    /// Override the copy, which is used when flecs duplicates a component value, e.g. when
+    /// an entity is cloned or an inherited component is overridden.
+    ///
+    /// This makes it possible to copy components that don't implement `Clone`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the component is already in use.
+    pub fn copy<Func>(&mut self, func: Func) -> &mut Self
+    where
+        Func: FnMut(&T) -> T + 'static,
+    {
+        let func = Box::into_raw(Box::new(func)) as *mut c_void;
+        let action = LifecycleAction {
+            func,
+            free: ComponentBindingCtx::free_hook::<Func>,
+            run: Self::run_copy::<Func> as RunCopyT,
+        };
+        self.install(
+            |ctx| ctx.copy.replace(action),
+            |hooks| {
+                hooks.copy = Some(closure_copy::<T>);
+                hooks.copy_ctor = Some(closure_copy_ctor::<T>);
+            },
+        )
+    }
+
+    /// Register a closure that is invoked after flecs moved a value to a new location in
+    /// memory, e.g. when its entity moves to another table, when the last row of a table is
+    /// moved to the row of an entity that is deleted, when a table is resized or when a value
+    /// that was set while deferred is moved to the storage.
+    ///
+    /// Values are moved by copying their bytes, the closure receives the value at its new
+    /// location, which makes it possible to fix up pointers that refer to the value itself.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the component is already in use.
+    pub fn on_move<Func>(&mut self, func: Func) -> &mut Self
+    where
+        Func: FnMut(&mut T) + 'static,
+    {
+        let action = Self::new_action(func, Self::run_on_move::<Func>);
+        self.install(
+            |ctx| ctx.on_move.replace(action),
+            |hooks| {
+                hooks.move_ = Some(closure_move::<T>);
+            },
+        )
+    }
+
+    fn new_action<Func: 'static>(func: Func, run: RunValueT) -> LifecycleAction<RunValueT> {
+        LifecycleAction {
+            func: Box::into_raw(Box::new(func)) as *mut c_void,
+            free: ComponentBindingCtx::free_hook::<Func>,
+            run,
+        }
+    }
+
+    /// Stores an action in the binding context and installs the lifecycle actions that invoke it.
+    fn install<R>(
+        &mut self,
+        store: impl FnOnce(&mut ComponentBindingCtx) -> Option<LifecycleAction<R>>,
+        set_hooks: impl FnOnce(&mut sys::ecs_type_hooks_t),
+    ) -> &mut Self {
+        let world = self.component.world;
+        let id = *self.component.entity.id;
+        ecs_assert!(
+            !unsafe { sys::ecs_id_in_use(world.world_ptr(), id) },
+            FlecsErrorCode::InvalidOperation,
+            "lifecycle actions of component '{}' can't be changed while it is in use",
+            std::any::type_name::<T>()
+        );
+
+        let ctx = self.component.binding_context();
+        if let Some(previous) = store(ctx) {
+            unsafe { (previous.free)(previous.func) };
+        }
+
+        let mut hooks = self.component.get_hooks();
+        hooks.dtor = Some(closure_dtor::<T>);
+        hooks.move_dtor = Some(closure_move_dtor::<T>);
+        hooks.ctor_move_dtor = Some(closure_ctor_move_dtor::<T>);
+        set_hooks(&mut hooks);
+        unsafe { sys::ecs_set_hooks_id(world.world_ptr_mut(), id, &hooks) };
+
+        if hooks.move_.is_some() {
+            // flecs derives a move constructor from `move_`, with which it expects a value that
+            // was moved to a new row to stay valid until its old row is destroyed. Rust values
+            // can't be left behind like that, without a move constructor flecs moves values to
+            // new rows with `ctor_move_dtor`, which ends the lifetime of the old value.
+            unsafe {
+                let type_info = sys::ecs_get_type_info(world.world_ptr(), id);
+                (*(type_info as *mut sys::ecs_type_info_t)).hooks.move_ctor = None;
+            }
+        }
+        self
+    }
+
+    unsafe fn run_ctor<Func: FnMut() -> T>(func: *mut c_void, dst: *mut c_void) {
+        let ctor = unsafe { &mut *(func as *mut Func) };
+        unsafe { ptr::write(dst as *mut T, ctor()) };
+    }
+
+    unsafe fn run_dtor<Func: FnMut(T)>(func: *mut c_void, value: *mut c_void) {
+        let dtor = unsafe { &mut *(func as *mut Func) };
+        dtor(unsafe { ptr::read(value as *mut T) });
+    }
+
+    unsafe fn run_copy<Func: FnMut(&T) -> T>(
+        func: *mut c_void,
+        dst: *mut c_void,
+        src: *const c_void,
+    ) {
+        let copy = unsafe { &mut *(func as *mut Func) };
+        unsafe { ptr::write(dst as *mut T, copy(&*(src as *const T))) };
+    }
+
+    unsafe fn run_on_move<Func: FnMut(&mut T)>(func: *mut c_void, value: *mut c_void) {
+        let on_move = unsafe { &mut *(func as *mut Func) };
+        on_move(unsafe { &mut *(value as *mut T) });
+    }
+}
+
+impl<'a, T: ComponentId> Component<'a, T> {
+    /// Override the lifecycle actions of the component with closures.
+    ///
+    /// # See also
+    ///
+    /// * [`LifecycleBuilder`]
+    pub fn lifecycle(&self) -> LifecycleBuilder<'a, T> {
+        LifecycleBuilder::new(self.base)
+    }
+}
+
+fn lifecycle_ctx<'b>(type_info: *const sys::ecs_type_info_t) -> &'b ComponentBindingCtx {
+    unsafe { &*((*type_info).hooks.binding_ctx as *const ComponentBindingCtx) }
+}
+
+/// Destroys a value with the destructor override, or drops it if there is none.
+unsafe fn destroy<T>(ctx: &ComponentBindingCtx, value: *mut T) {
+    match &ctx.dtor {
+        Some(dtor) => unsafe { (dtor.run)(dtor.func, value as *mut c_void) },
+        None => unsafe { ptr::drop_in_place(value) },
+    }
+}
+
+/// Moves a value by copying its bytes and runs the move override on the new location.
+unsafe fn relocate<T>(ctx: &ComponentBindingCtx, dst: *mut T, src: *mut T) {
+    unsafe { ptr::copy_nonoverlapping(src, dst, 1) };
+    if let Some(on_move) = &ctx.on_move {
+        unsafe { (on_move.run)(on_move.func, dst as *mut c_void) };
+    }
+}
+
+extern "C" fn closure_ctor<T>(
+    ptr: *mut c_void,
+    count: i32,
+    type_info: *const sys::ecs_type_info_t,
+) {
+    abort_on_callback_panic::<T, _>("constructor", || {
+        let ctx = lifecycle_ctx(type_info);
+        let Some(ctor) = &ctx.ctor else {
+            return;
+        };
+        let arr = ptr as *mut T;
+        for i in 0..count as usize {
+            unsafe { (ctor.run)(ctor.func, arr.add(i) as *mut c_void) };
+        }
+    });
+}
+
+extern "C" fn closure_dtor<T>(
+    ptr: *mut c_void,
+    count: i32,
+    type_info: *const sys::ecs_type_info_t,
+) {
+    abort_on_callback_panic::<T, _>("destructor", || {
+        let ctx = lifecycle_ctx(type_info);
+        let arr = ptr as *mut T;
+        for i in 0..count as usize {
+            let value = unsafe { arr.add(i) };
+            if !ctx.take_moved_from(value as *mut c_void) {
+                unsafe { destroy(ctx, value) };
+            }
+        }
+    });
+}
+
+extern "C" fn closure_copy<T>(
+    dst_ptr: *mut c_void,
+    src_ptr: *const c_void,
+    count: i32,
+    type_info: *const sys::ecs_type_info_t,
+) {
+    abort_on_callback_panic::<T, _>("copy", || {
+        let ctx = lifecycle_ctx(type_info);
+        let Some(copy) = &ctx.copy else {
+            return;
+        };
+        let dst_arr = dst_ptr as *mut T;
+        let src_arr = src_ptr as *const T;
+        for i in 0..count as usize {
+            unsafe {
+                destroy(ctx, dst_arr.add(i));
+                (copy.run)(
+                    copy.func,
+                    dst_arr.add(i) as *mut c_void,
+                    src_arr.add(i) as *const c_void,
+                );
+            }
+        }
+    });
+}
+
+extern "C" fn closure_copy_ctor<T>(
+    dst_ptr: *mut c_void,
+    src_ptr: *const c_void,
+    count: i32,
+    type_info: *const sys::ecs_type_info_t,
+) {
+    abort_on_callback_panic::<T, _>("copy", || {
+        let ctx = lifecycle_ctx(type_info);
+        let Some(copy) = &ctx.copy else {
+            return;
+        };
+        let dst_arr = dst_ptr as *mut T;
+        let src_arr = src_ptr as *const T;
+        for i in 0..count as usize {
+            unsafe {
+                (copy.run)(
+                    copy.func,
+                    dst_arr.add(i) as *mut c_void,
+                    src_arr.add(i) as *const c_void,
+                );
+            }
+        }
+    });
+}
+
+extern "C" fn closure_move_dtor<T>(
+    dst_ptr: *mut c_void,
+    src_ptr: *mut c_void,
+    count: i32,
+    type_info: *const sys::ecs_type_info_t,
+) {
+    abort_on_callback_panic::<T, _>("move", || {
+        let ctx = lifecycle_ctx(type_info);
+        let dst_arr = dst_ptr as *mut T;
+        let src_arr = src_ptr as *mut T;
+        for i in 0..count as usize {
+            unsafe {
+                destroy(ctx, dst_arr.add(i));
+                relocate(ctx, dst_arr.add(i), src_arr.add(i));
+            }
+        }
+    });
+}
+
+extern "C" fn closure_ctor_move_dtor<T>(
+    dst_ptr: *mut c_void,
+    src_ptr: *mut c_void,
+    count: i32,
+    type_info: *const sys::ecs_type_info_t,
+) {
+    abort_on_callback_panic::<T, _>("move", || {
+        let ctx = lifecycle_ctx(type_info);
+        let dst_arr = dst_ptr as *mut T;
+        let src_arr = src_ptr as *mut T;
+        for i in 0..count as usize {
+            unsafe { relocate(ctx, dst_arr.add(i), src_arr.add(i)) };
+        }
+    });
+}
+
+/// Moves values into existing values. flecs destroys the values that were moved afterwards,
+/// which is skipped as their bytes now belong to the new location.
+extern "C" fn closure_move<T: ComponentId>(
+    dst_ptr: *mut c_void,
+    src_ptr: *mut c_void,
+    count: i32,
+    type_info: *const sys::ecs_type_info_t,
+) {
+    abort_on_callback_panic::<T, _>("move", || {
+        let ctx = lifecycle_ctx(type_info);
+        // without a constructor, flecs zeroes the value that is moved into, which isn't a value
+        // that can be dropped
+        let constructed = ctx.ctor.is_some() || T::IMPLS_DEFAULT;
+        let dst_arr = dst_ptr as *mut T;
+        let src_arr = src_ptr as *mut T;
+        for i in 0..count as usize {
+            unsafe {
+                if constructed {
+                    destroy(ctx, dst_arr.add(i));
+                }
+                relocate(ctx, dst_arr.add(i), src_arr.add(i));
+            }
+            ctx.set_moved_from(unsafe { src_arr.add(i) } as *mut c_void);
+        }
+    });
+}
@@ -4,6 +4,8 @@ mod cached_ref;
 mod component;
 mod component_binding;
 mod component_untyped;
+mod hook;
+mod lifecycle;
 #[doc(hidden)]
 pub mod lifecycle_traits;
 
@@ -11,5 +13,7 @@ pub use cached_ref::*;
 pub use component::*;
 pub(crate) use component_binding::*;
 pub use component_untyped::*;
+pub use hook::*;
+pub use lifecycle::*;
 #[doc(hidden)]
 pub use lifecycle_traits::*;
@@ -0,0 +1,75 @@
+//! Typed contexts that are owned by a world, system or observer.
+
+use std::any::Any;
+use std::ffi::c_void;
+
+use crate::sys;
+
+type OwnedContext = Box<dyn Any>;
+
+/// Boxes `value` into a context pointer that is freed with [`free_context`].
+pub(crate) fn new_context<C: 'static>(value: C) -> *mut c_void {
+    let context: OwnedContext = Box::new(value);
+    Box::into_raw(Box::new(context)) as *mut c_void
+}
+
+pub(crate) extern "C" fn free_context(ctx: *mut c_void) {
+    if !ctx.is_null() {
+        unsafe { drop(Box::from_raw(ctx as *mut OwnedContext)) };
+    }
+}
+
+/// Returns the typed context stored in `ctx`.
+///
+/// Returns `None` if `ctx` wasn't created with [`new_context`] (which is derived from `ctx_free`)
+/// or if it holds a value of a different type than `C`.
+pub(crate) fn context_ref<'a, C: 'static>(
+    ctx: *mut c_void,
+    ctx_free: sys::ecs_ctx_free_t,
+) -> Option<&'a C> {
+    if !is_owned_context(ctx, ctx_free) {
+        return None;
+    }
+
+    unsafe { (*(ctx as *const OwnedContext)).downcast_ref::<C>() }
+}
+
+/// Mutable version of [`context_ref`].
+///
+/// # Safety
+///
+/// The caller must ensure the context isn't aliased for the returned lifetime.
+pub(crate) unsafe fn context_mut<'a, C: 'static>(
+    ctx: *mut c_void,
+    ctx_free: sys::ecs_ctx_free_t,
+) -> Option<&'a mut C> {
+    if !is_owned_context(ctx, ctx_free) {
+        return None;
+    }
+
+    unsafe { (*(ctx as *mut OwnedContext)).downcast_mut::<C>() }
+}
+
+fn is_owned_context(ctx: *mut c_void, ctx_free: sys::ecs_ctx_free_t) -> bool {
+    !ctx.is_null()
+        && ctx_free.is_some_and(|ctx_free| {
+            ctx_free as *const () == free_context as extern "C" fn(*mut c_void) as *const ()
+        })
+}
+
+/// Replaces the context and free function of a flecs object, freeing the previous context.
+pub(crate) fn replace_context(
+    ctx: &mut *mut c_void,
+    ctx_free: &mut sys::ecs_ctx_free_t,
+    new_ctx: *mut c_void,
+    new_ctx_free: sys::ecs_ctx_free_t,
+) {
+    if *ctx != new_ctx && !ctx.is_null() {
+        if let Some(free) = *ctx_free {
+            unsafe { free(*ctx) };
+        }
+    }
+
+    *ctx = new_ctx;
+    *ctx_free = new_ctx_free;
+}
@@ -4,6 +4,7 @@ use std::{
     ptr::{self, NonNull},
 };
 
+use crate::core::callback_panic::{catch_callback_panic, TableLock};
 use crate::sys;
 use flecs_ecs::core::*;
 use sys::ecs_get_with;
@@ -2460,13 +2461,13 @@ impl<'a> EntityView<'a> {
         let empty = &mut *(empty as *mut Func);
         let iter_count = (*iter).count as usize;
 
-        sys::ecs_table_lock((*iter).world, (*iter).table);
+        catch_callback_panic((*iter).world, || {
+            let _lock = TableLock::new((*iter).world, (*iter).table);
 
-        for _i in 0..iter_count {
-            empty();
-        }
-
-        sys::ecs_table_unlock((*iter).world, (*iter).table);
+            for _i in 0..iter_count {
+                empty();
+            }
+        });
     }
 
     /// Callback of the observe functionality
@@ -2488,17 +2489,17 @@ impl<'a> EntityView<'a> {
         let empty = &mut *(empty as *mut Func);
         let iter_count = (*iter).count as usize;
 
-        sys::ecs_table_lock((*iter).world, (*iter).table);
+        catch_callback_panic((*iter).world, || {
+            let _lock = TableLock::new((*iter).world, (*iter).table);
 
-        for _i in 0..iter_count {
-            let world = WorldRef::from_ptr((*iter).world);
-            empty(&mut EntityView::new_from(
-                world,
-                sys::ecs_field_src(iter, 0),
-            ));
-        }
-
-        sys::ecs_table_unlock((*iter).world, (*iter).table);
+            for _i in 0..iter_count {
+                let world = WorldRef::from_ptr((*iter).world);
+                empty(&mut EntityView::new_from(
+                    world,
+                    sys::ecs_field_src(iter, 0),
+                ));
+            }
+        });
     }
 
     /// Callback of the observe functionality
@@ -2520,15 +2521,15 @@ impl<'a> EntityView<'a> {
         let empty = &mut *(empty as *mut Func);
         let iter_count = (*iter).count as usize;
 
-        sys::ecs_table_lock((*iter).world, (*iter).table);
+        catch_callback_panic((*iter).world, || {
+            let _lock = TableLock::new((*iter).world, (*iter).table);
 
-        for _i in 0..iter_count {
-            let data = (*iter).param as *mut C;
-            let data_ref = &mut *data;
-            empty(data_ref);
-        }
-
-        sys::ecs_table_unlock((*iter).world, (*iter).table);
+            for _i in 0..iter_count {
+                let data = (*iter).param as *mut C;
+                let data_ref = &mut *data;
+                empty(data_ref);
+            }
+        });
     }
 
     /// Callback of the observe functionality
@@ -2550,19 +2551,19 @@ impl<'a> EntityView<'a> {
         let empty = &mut *(empty as *mut Func);
         let iter_count = (*iter).count as usize;
 
-        sys::ecs_table_lock((*iter).world, (*iter).table);
-
-        for _i in 0..iter_count {
-            let data = (*iter).param as *mut C;
-            let data_ref = &mut *data;
-            let world = WorldRef::from_ptr((*iter).world);
-            empty(
-                &mut EntityView::new_from(world, sys::ecs_field_src(iter, 0)),
-                data_ref,
-            );
-        }
+        catch_callback_panic((*iter).world, || {
+            let _lock = TableLock::new((*iter).world, (*iter).table);
 
-        sys::ecs_table_unlock((*iter).world, (*iter).table);
+            for _i in 0..iter_count {
+                let data = (*iter).param as *mut C;
+                let data_ref = &mut *data;
+                let world = WorldRef::from_ptr((*iter).world);
+                empty(
+                    &mut EntityView::new_from(world, sys::ecs_field_src(iter, 0)),
+                    data_ref,
+                );
+            }
+        });
     }
 
     /// Callback to free the memory of the `empty` callback
@@ -1,5 +1,6 @@
 use std::os::raw::c_void;
 
+use crate::core::callback_panic::resume_callback_panic;
 use flecs_ecs::core::*;
 use sys::EcsIsA;
 
@@ -44,7 +45,10 @@ impl<'a> EntityView<'a> {
 
         Self::check_add_id_validity(world, id);
 
-        unsafe { sys::ecs_add_id(world, *self.id, id) }
+        unsafe {
+            sys::ecs_add_id(world, *self.id, id);
+        }
+        resume_callback_panic(self.world.world_ptr());
         self
     }
 
@@ -52,7 +56,10 @@ impl<'a> EntityView<'a> {
         let id = *id.into();
         let world = self.world.world_ptr_mut();
 
-        unsafe { sys::ecs_add_id(world, *self.id, id) }
+        unsafe {
+            sys::ecs_add_id(world, *self.id, id);
+        }
+        resume_callback_panic(self.world.world_ptr());
         self
     }
 
@@ -423,7 +430,10 @@ impl<'a> EntityView<'a> {
     /// * C++ API: `entity_builder::remove`
     #[doc(alias = "entity_builder::remove")]
     pub fn remove_id(self, id: impl IntoId) -> Self {
-        unsafe { sys::ecs_remove_id(self.world.world_ptr_mut(), *self.id, *id.into()) }
+        unsafe {
+            sys::ecs_remove_id(self.world.world_ptr_mut(), *self.id, *id.into());
+        }
+        resume_callback_panic(self.world.world_ptr());
         self
     }
 
@@ -764,6 +774,7 @@ impl<'a> EntityView<'a> {
                 ECS_AUTO_OVERRIDE | *id.into(),
             );
         }
+        resume_callback_panic(self.world.world_ptr());
         self
     }
 
@@ -1033,6 +1044,7 @@ impl<'a> EntityView<'a> {
         ptr: *const c_void,
     ) -> Self {
         sys::ecs_set_id(self.world.world_ptr_mut(), *self.id, *id.into(), size, ptr);
+        resume_callback_panic(self.world.world_ptr());
         self
     }
 
@@ -1134,7 +1146,10 @@ impl<'a> EntityView<'a> {
     /// * C++ API: `entity_builder::enable`
     #[doc(alias = "entity_builder::enable")]
     pub fn enable_self(self) -> Self {
-        unsafe { sys::ecs_enable(self.world.world_ptr_mut(), *self.id, true) }
+        unsafe {
+            sys::ecs_enable(self.world.world_ptr_mut(), *self.id, true);
+        }
+        resume_callback_panic(self.world.world_ptr());
         self
     }
     /// Enables an ID which represents a component or pair.
@@ -1152,7 +1167,10 @@ impl<'a> EntityView<'a> {
     /// * C++ API: `entity_builder::enable`
     #[doc(alias = "entity_builder::enable")]
     pub fn enable_id(self, id: impl IntoId) -> Self {
-        unsafe { sys::ecs_enable_id(self.world.world_ptr_mut(), *self.id, *id.into(), true) }
+        unsafe {
+            sys::ecs_enable_id(self.world.world_ptr_mut(), *self.id, *id.into(), true);
+        }
+        resume_callback_panic(self.world.world_ptr());
         self
     }
 
@@ -1200,7 +1218,10 @@ impl<'a> EntityView<'a> {
     /// * C++ API: `entity_builder::disable`
     #[doc(alias = "entity_builder::disable")]
     pub fn disable_self(self) -> Self {
-        unsafe { sys::ecs_enable(self.world.world_ptr_mut(), *self.id, false) }
+        unsafe {
+            sys::ecs_enable(self.world.world_ptr_mut(), *self.id, false);
+        }
+        resume_callback_panic(self.world.world_ptr());
         self
     }
 
@@ -1218,7 +1239,10 @@ impl<'a> EntityView<'a> {
     /// * C++ API: `entity_builder::disable`
     #[doc(alias = "entity_builder::disable")]
     pub fn disable_id(self, id: impl IntoId) -> Self {
-        unsafe { sys::ecs_enable_id(self.world.world_ptr_mut(), *self.id, *id.into(), false) }
+        unsafe {
+            sys::ecs_enable_id(self.world.world_ptr_mut(), *self.id, *id.into(), false);
+        }
+        resume_callback_panic(self.world.world_ptr());
         self
     }
 
@@ -1395,6 +1419,10 @@ impl<'a> EntityView<'a> {
 
     /// Signal that component or pair was modified.
     ///
+    /// This invokes `OnSet` hooks and observers, but not the `on_replace` hook or
+    /// [`flecs::OnReplace`] observers, as the value from before the modification is no longer
+    /// available. Use [`EntityView::modify()`] to notify them.
+    ///
     /// # Arguments
     ///
     /// * `comp` - The component that was modified.
@@ -1407,11 +1435,18 @@ impl<'a> EntityView<'a> {
     /// * C++ API: `entity::modified`
     #[doc(alias = "entity::modified")]
     pub fn modified_id(self, id: impl IntoId) {
-        unsafe { sys::ecs_modified_id(self.world.world_ptr_mut(), *self.id, *id.into()) }
+        unsafe {
+            sys::ecs_modified_id(self.world.world_ptr_mut(), *self.id, *id.into());
+        }
+        resume_callback_panic(self.world.world_ptr());
     }
 
     /// Signal that component was modified.
     ///
+    /// This invokes `OnSet` hooks and observers, but not the `on_replace` hook or
+    /// [`flecs::OnReplace`] observers, as the value from before the modification is no longer
+    /// available. Use [`EntityView::modify()`] to notify them.
+    ///
     /// # Type Parameters
     ///
     /// * `T` - The type of the component that was modified.
@@ -1434,8 +1469,54 @@ impl<'a> EntityView<'a> {
         self.modified_id(T::get_id(self.world));
     }
 
+    /// Modify the value of a component and signal that it was modified.
+    ///
+    /// The closure receives a copy of the current value, which is then set on the entity. Unlike
+    /// changing the value through `get` and calling [`EntityView::modified()`], this invokes the
+    /// `on_replace` hook and [`flecs::OnReplace`] observers with the value from before the
+    /// modification. An inherited value is copied, after which the entity owns the component.
+    ///
+    /// # Type Parameters
+    ///
+    /// * `T` - The type of the component to modify.
+    ///
+    /// # Arguments
+    ///
+    /// * `func` - The function that modifies the value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the entity doesn't have the component.
+    ///
+    /// # See also
+    ///
+    /// * [`Component::on_replace()`]
+    /// * [`EntityView::modified()`]
+    pub fn modify<T>(self, func: impl FnOnce(&mut T)) -> Self
+    where
+        T: ComponentId + DataComponent + Clone,
+    {
+        let world = self.world.world_ptr_mut();
+        let id = T::id(self.world);
+        let current = unsafe { sys::ecs_get_id(world, *self.id, id) } as *const T;
+        if current.is_null() {
+            panic!(
+                "entity does not have component {} to modify",
+                std::any::type_name::<T>()
+            );
+        }
+
+        let mut value = unsafe { (*current).clone() };
+        func(&mut value);
+        set_helper(world, *self.id, value, id);
+        self
+    }
+
     /// Signal that the first part of a pair was modified.
     ///
+    /// Like [`EntityView::modified()`], this doesn't invoke the `on_replace` hook or
+    /// [`flecs::OnReplace`] observers.
+    ///
     /// # Type Parameters
     ///
     /// * `First` - The first part of the pair.
@@ -1558,7 +1639,10 @@ impl<'a> EntityView<'a> {
     #[doc(alias = "entity::clear")]
     #[allow(clippy::not_unsafe_ptr_arg_deref)]
     pub fn clear(&self) {
-        unsafe { sys::ecs_clear(self.world.world_ptr_mut(), *self.id) }
+        unsafe {
+            sys::ecs_clear(self.world.world_ptr_mut(), *self.id);
+        }
+        resume_callback_panic(self.world.world_ptr());
     }
 
     /// Delete an entity.
@@ -1571,6 +1655,9 @@ impl<'a> EntityView<'a> {
     /// * C++ API: `entity::destruct`
     #[doc(alias = "entity::destruct")]
     pub fn destruct(self) {
-        unsafe { sys::ecs_delete(self.world.world_ptr_mut(), *self.id) }
+        unsafe {
+            sys::ecs_delete(self.world.world_ptr_mut(), *self.id);
+        }
+        resume_callback_panic(self.world.world_ptr());
     }
 }
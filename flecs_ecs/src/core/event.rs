@@ -3,6 +3,7 @@
 use std::marker::PhantomData;
 use std::{alloc::Layout, os::raw::c_void};
 
+use crate::core::callback_panic::resume_callback_panic;
 use crate::core::*;
 use crate::sys;
 
@@ -25,6 +26,7 @@ pub struct EventBuilder<'a, T = ()> {
     pub(crate) desc: sys::ecs_event_desc_t,
     pub(crate) ids: sys::ecs_type_t,
     pub(crate) ids_array: [sys::ecs_id_t; sys::FLECS_EVENT_DESC_MAX as usize],
+    propagate: Option<sys::ecs_entity_t>,
     _phantom: std::marker::PhantomData<T>,
 }
 
@@ -42,6 +44,7 @@ impl<'a, T: ComponentId> EventBuilder<'a, T> {
             desc: Default::default(),
             ids: Default::default(),
             ids_array: Default::default(),
+            propagate: None,
             _phantom: PhantomData,
         };
         obj.desc.event = T::UnderlyingType::id(world);
@@ -74,6 +77,7 @@ impl<'a, T: ComponentId> EventBuilder<'a, T> {
             desc: Default::default(),
             ids: Default::default(),
             ids_array: Default::default(),
+            propagate: None,
             _phantom: PhantomData::<()>,
         };
         obj.desc.event = *event.into();
@@ -183,6 +187,66 @@ impl<'a, T: ComponentId> EventBuilder<'a, T> {
         self
     }
 
+    /// Propagate the event up the hierarchy formed by the relationship `Rel`, e.g. `flecs::ChildOf`.
+    ///
+    /// After the event is emitted for the entity, it is emitted for the target of `Rel` of the
+    /// entity, then for the target of that entity and so on, until the root is reached or an
+    /// observer calls [`TableIter::stop_propagation()`]. The same ids and payload are emitted
+    /// at every level.
+    ///
+    /// Propagation requires the event to be emitted for an entity with
+    /// [`EventBuilder::entity()`], and is not supported by [`EventBuilder::enqueue()`].
+    ///
+    /// # Type parameters
+    ///
+    /// * `Rel` - The relationship to propagate the event along
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use flecs_ecs::prelude::*;
+    ///
+    /// #[derive(Component)]
+    /// struct Widget;
+    ///
+    /// #[derive(Component)]
+    /// struct Click;
+    ///
+    /// let world = World::new();
+    ///
+    /// let window = world.entity_named("window").add::<Widget>();
+    /// let panel = world.entity_named("panel").add::<Widget>().child_of_id(window);
+    /// let button = world.entity_named("button").add::<Widget>().child_of_id(panel);
+    ///
+    /// let panel_id = panel.id();
+    /// world
+    ///     .observer::<Click, ()>()
+    ///     .with::<Widget>()
+    ///     .each_iter(move |it, index, _| {
+    ///         let widget = it.entity(index);
+    ///         println!("{} received click", widget.name());
+    ///         if widget == panel_id {
+    ///             // the click is handled, don't notify the window
+    ///             it.stop_propagation();
+    ///         }
+    ///     });
+    ///
+    /// world
+    ///     .event()
+    ///     .add::<Widget>()
+    ///     .entity(button)
+    ///     .propagate::<flecs::ChildOf>()
+    ///     .emit(&Click);
+    ///
+    /// // Output:
+    /// //  button received click
+    /// //  panel received click
+    /// ```
+    pub fn propagate<Rel: ComponentId>(&mut self) -> &mut Self {
+        self.propagate = Some(Rel::id(self.world));
+        self
+    }
+
     /// Set the table to emit for the event.
     ///
     /// # Arguments
@@ -203,7 +267,14 @@ impl<'a, T: ComponentId> EventBuilder<'a, T> {
         self
     }
 
+    /// Emit the event, immediately invoking the observers that match it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the event is observed with [`ObserverBuilder::each_event()`] and `T` is not
+    /// the payload type the observers expect.
     pub fn emit(&mut self, data: &T) {
+        check_event_payload::<T>(self.world, self.desc.event);
         let ids = &mut self.ids;
         let ids_array = &mut self.ids_array;
         let desc = &mut self.desc;
@@ -216,10 +287,29 @@ impl<'a, T: ComponentId> EventBuilder<'a, T> {
 
         desc.ids = ids;
         desc.observable = world.real_world().world_ptr_mut() as *mut c_void;
-        unsafe { sys::ecs_emit(world.world_ptr_mut(), desc) };
+
+        let scope = EmitScope::enter(world, desc.event);
+        match self.propagate {
+            Some(rel) => emit_propagated(world, desc, rel, &scope),
+            None => unsafe { sys::ecs_emit(world.world_ptr_mut(), desc) },
+        }
+        drop(scope);
+        resume_callback_panic(world.world_ptr());
     }
 
+    /// Enqueue the event, the observers are invoked when the deferred operations are flushed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the event is observed with [`ObserverBuilder::each_event()`] and `T` is not
+    /// the payload type the observers expect.
     pub fn enqueue(&mut self, data: T) {
+        check_event_payload::<T>(self.world, self.desc.event);
+        ecs_assert!(
+            self.propagate.is_none(),
+            FlecsErrorCode::InvalidOperation,
+            "propagating events can't be enqueued"
+        );
         let ids = &mut self.ids;
         let ids_array = &mut self.ids_array;
         let desc = &mut self.desc;
@@ -238,5 +328,241 @@ impl<'a, T: ComponentId> EventBuilder<'a, T> {
                 std::alloc::dealloc(desc.param as *mut u8, Layout::new::<T>());
             }
         };
+        resume_callback_panic(world.world_ptr());
+    }
+}
+
+thread_local! {
+    /// The events that are being emitted on this thread, innermost last, see [`EmitScope`].
+    static EMITS: std::cell::RefCell<Vec<EmitState>> = const { std::cell::RefCell::new(Vec::new()) };
+}
+
+struct EmitState {
+    world: *const sys::ecs_world_t,
+    event: u64,
+    stopped: bool,
+}
+
+/// Tracks an event that is emitted with an [`EventBuilder`] for as long as its observers run,
+/// so that [`TableIter::stop_propagation()`] can stop the propagation of that event only.
+///
+/// Every emit has its own entry, which is removed when the scope is dropped, also when an
+/// observer panics.
+struct EmitScope {
+    depth: usize,
+}
+
+impl EmitScope {
+    fn enter(world: WorldRef, event: u64) -> Self {
+        let state = EmitState {
+            world: world.real_world().world_ptr(),
+            event,
+            stopped: false,
+        };
+        let depth = EMITS.with(|emits| {
+            let mut emits = emits.borrow_mut();
+            emits.push(state);
+            emits.len()
+        });
+        Self { depth }
+    }
+
+    fn is_stopped(&self) -> bool {
+        EMITS.with(|emits| emits.borrow()[self.depth - 1].stopped)
+    }
+}
+
+impl Drop for EmitScope {
+    fn drop(&mut self) {
+        EMITS.with(|emits| emits.borrow_mut().truncate(self.depth - 1));
+    }
+}
+
+/// Stops the propagation of the innermost event that is being emitted on this thread, if it is
+/// the event of the observer.
+pub(crate) fn stop_propagation(world: WorldRef, event: u64) {
+    let world = world.real_world().world_ptr();
+    EMITS.with(|emits| {
+        if let Some(state) = emits.borrow_mut().last_mut() {
+            if state.world == world && state.event == event {
+                state.stopped = true;
+            }
+        }
+    });
+}
+
+/// Emits an event for an entity and its ancestors (targets of `rel`), until an observer stops
+/// the propagation.
+fn emit_propagated(
+    world: WorldRef,
+    desc: &sys::ecs_event_desc_t,
+    rel: sys::ecs_entity_t,
+    scope: &EmitScope,
+) {
+    ecs_assert!(
+        desc.entity != 0,
+        FlecsErrorCode::InvalidOperation,
+        "propagating events must be emitted for an entity"
+    );
+
+    let mut entity = desc.entity;
+    while entity != 0 {
+        // emit modifies the descriptor, so each level gets its own copy
+        let mut level_desc = *desc;
+        level_desc.entity = entity;
+        unsafe { sys::ecs_emit(world.world_ptr_mut(), &mut level_desc) };
+
+        if scope.is_stopped() {
+            break;
+        }
+        entity = unsafe { sys::ecs_get_target(world.world_ptr(), entity, rel, 0) };
+    }
+}
+
+/// Returns the payload id of an event emitted with data of type `T`, or 0 if it has no payload.
+fn payload_id<T: ComponentId>(world: WorldRef) -> u64 {
+    if T::IS_TAG {
+        0
+    } else {
+        T::UnderlyingType::id(world)
+    }
+}
+
+/// An event that is identified by its own type, but carries a payload of another type.
+///
+/// This makes it possible for an observer to receive several events with the same payload, see
+/// [`ObserverBuilder::add_payload_event()`]. The event is emitted with
+/// [`World::payload_event()`].
+///
+/// # Example
+///
+/// ```
+/// use flecs_ecs::prelude::*;
+///
+/// #[derive(Component)]
+/// struct Clicked {
+///     button: u8,
+/// }
+///
+/// #[derive(Component)]
+/// struct DoubleClicked;
+///
+/// impl PayloadEvent for DoubleClicked {
+///     type Payload = Clicked;
+/// }
+///
+/// #[derive(Component)]
+/// struct Button {
+///     id: u32,
+/// }
+///
+/// let world = World::new();
+///
+/// world
+///     .observer::<Clicked, &Button>()
+///     .add_payload_event::<DoubleClicked>()
+///     .each_event(|clicked, _, button| println!("{} clicked with {}", button.id, clicked.button));
+///
+/// let button = world.entity().set(Button { id: 1 });
+///
+/// world
+///     .payload_event::<DoubleClicked>()
+///     .add::<Button>()
+///     .entity(button)
+///     .emit(&Clicked { button: 0 });
+/// ```
+pub trait PayloadEvent: ComponentId {
+    /// The type of the payload the event is emitted with.
+    type Payload: ComponentId;
+}
+
+/// Records the payload type of the events of an observer that is built with
+/// [`ObserverBuilder::each_event()`], for as long as the observer exists.
+///
+/// The registrations are counted per event, as several observers can observe the same event.
+/// They are removed when the callback of the observer, which owns this value, is dropped.
+pub(crate) struct EventPayloadRegistration {
+    world: *mut sys::ecs_world_t,
+    events: Vec<u64>,
+}
+
+impl EventPayloadRegistration {
+    /// Records that `events` carry a payload of type `P`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if one of the events is already observed with a different payload type.
+    pub(crate) fn new<P: ComponentId>(world: WorldRef, events: &[u64]) -> Self {
+        let payload = payload_id::<P>(world);
+        // events are added one by one, so that a panic only removes the registered ones
+        let mut registration = Self {
+            world: world.world_ptr_mut(),
+            events: Vec::with_capacity(events.len()),
+        };
+
+        for &event in events {
+            let (previous, count) = world
+                .world_ctx_mut()
+                .event_payloads
+                .entry(event)
+                .or_insert((payload, 0));
+
+            if *previous != payload {
+                let previous = *previous;
+                panic!(
+                    "event `{}` is observed with payload type `{}`, but was already observed with payload type `{}`",
+                    EntityView::new_from(world, event).path().unwrap_or_default(),
+                    std::any::type_name::<P>(),
+                    EntityView::new_from(world, previous).path().unwrap_or_default()
+                );
+            }
+            *count += 1;
+            registration.events.push(event);
+        }
+        registration
+    }
+}
+
+impl Drop for EventPayloadRegistration {
+    fn drop(&mut self) {
+        let world = unsafe { WorldRef::from_ptr(self.world) };
+        let payloads = &mut world.world_ctx_mut().event_payloads;
+        for event in &self.events {
+            if let Some((_, count)) = payloads.get_mut(event) {
+                *count -= 1;
+                if *count == 0 {
+                    payloads.remove(event);
+                }
+            }
+        }
+    }
+}
+
+/// Checks that `event` is emitted with the payload type its typed observers expect.
+fn check_event_payload<T: ComponentId>(world: WorldRef, event: u64) {
+    let Some(&(expected, _)) = world.world_ctx().event_payloads.get(&event) else {
+        return;
+    };
+
+    if expected != payload_id::<T>(world) {
+        let event = EntityView::new_from(world, event)
+            .path()
+            .unwrap_or_default();
+        let expected = EntityView::new_from(world, expected)
+            .path()
+            .unwrap_or_default();
+        if T::IS_TAG {
+            panic!(
+                "event `{}` was emitted without payload, but its observers expect a payload of type `{}`",
+                event, expected
+            );
+        } else {
+            panic!(
+                "event `{}` was emitted with payload type `{}`, but its observers expect a payload of type `{}`",
+                event,
+                std::any::type_name::<T>(),
+                expected
+            );
+        }
     }
 }
@@ -355,6 +355,18 @@ create_pre_registered_component!(
     ECS_ON_SET,
     "Event. Invoked whenever a component is assigned a new value."
 );
+/// Event. Invoked right before a `set` overwrites an existing component value.
+///
+/// Observers for this event see the old value in their component fields, while the new value is
+/// passed as event payload and can be accessed with [`TableIter::param_untyped()`] cast to the
+/// component type. Unlike the other builtin events, this event is emitted by the Rust API.
+///
+/// # See also
+///
+/// * [`Component::on_replace()`](crate::core::Component::on_replace)
+#[derive(flecs_ecs_derive::Component)]
+pub struct OnReplace;
+
 create_pre_registered_component!(OnTableCreate, ECS_ON_TABLE_CREATE);
 create_pre_registered_component!(OnTableDelete, ECS_ON_TABLE_DELETE);
 create_pre_registered_component!(OnTableEmpty, ECS_ON_TABLE_EMPTY);
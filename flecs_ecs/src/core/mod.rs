@@ -4,6 +4,7 @@ pub mod c_types;
 pub(crate) mod cloned_tuple;
 pub mod component_registration;
 mod components;
+pub(crate) mod context;
 mod entity;
 mod entity_view;
 mod event;
@@ -13,10 +14,14 @@ mod id;
 mod id_view;
 mod observer;
 mod observer_builder;
+pub(crate) mod observer_order;
 mod query;
 pub mod query_builder;
+mod query_error;
+mod query_info;
 mod query_iter;
 pub(crate) mod query_tuple;
+mod query_var;
 pub mod table;
 pub mod term;
 pub mod utility;
@@ -33,10 +38,11 @@ pub(crate) use cloned_tuple::*;
 pub use component_registration::*;
 #[doc(inline)]
 pub use components::*;
+pub(crate) use context::*;
 pub use entity::Entity;
 pub use entity_view::EntityView;
 pub use entity_view::EntityViewMap;
-pub use event::EventBuilder;
+pub use event::{EventBuilder, PayloadEvent};
 pub(crate) use get_tuple::*;
 pub use id::Id;
 pub use id_view::IdView;
@@ -45,9 +51,14 @@ pub use observer_builder::ObserverBuilder;
 pub use query::Query;
 #[doc(hidden)]
 pub use query_builder::*;
+pub(crate) use query_error::*;
+pub use query_error::{QueryError, QueryErrorKind};
+pub use query_info::QueryInfo;
 pub use query_iter::QueryIter;
 #[doc(hidden)]
 pub use query_tuple::*;
+pub(crate) use query_var::{register_query, var_name};
+pub use query_var::{QueryVar, QueryVarId, QueryVarValue};
 #[doc(hidden)]
 pub use table::*;
 #[doc(hidden)]
@@ -3,6 +3,7 @@ use std::ops::DerefMut;
 use std::ptr::NonNull;
 use std::{ops::Deref, os::raw::c_void};
 
+use crate::core::observer_order::{self, ObserverOrder};
 use crate::core::*;
 use crate::sys;
 
@@ -58,7 +59,14 @@ impl<'a> Observer<'a> {
             );
         }
 
+        let run = observer_order::set_observer_run(world.world(), &mut desc);
         let id = unsafe { sys::ecs_observer_init(world.world_ptr_mut(), &desc) };
+        if let Some(run) = run {
+            observer_order::observer_created(run, id);
+        }
+        if id != 0 {
+            register_query(unsafe { (*sys::ecs_observer_get(world.world_ptr(), id)).query });
+        }
         let entity = EntityView::new_from(world.world(), id);
 
         Self { entity }
@@ -73,30 +81,130 @@ impl<'a> Observer<'a> {
 
     /// Set the context for the observer
     ///
+    /// The observer takes ownership of the value, which is dropped when the context is replaced or
+    /// when the observer is deleted. The context can be accessed from within the observer with
+    /// [`TableIter::context()`].
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The context to set.
+    ///
     /// # See also
     ///
+    /// * [`Observer::typed_context()`]
     /// * C++ API: `observer::ctx`
     #[doc(alias = "observer::ctx")]
-    pub fn set_context(&mut self, context: *mut c_void) {
-        let desc: sys::ecs_observer_desc_t = sys::ecs_observer_desc_t {
-            entity: *self.id,
-            ctx: context,
-            ..Default::default()
-        };
-
-        unsafe {
-            sys::ecs_observer_init(self.world.world_ptr_mut(), &desc);
-        }
+    pub fn set_typed_context<C: 'static>(&mut self, value: C) {
+        self.set_context_ptr_with_free(new_context(value), Some(free_context));
     }
 
     /// Get the context for the observer
     ///
+    /// # Returns
+    ///
+    /// The context, or `None` if no context is set or if it is not of type `C`.
+    ///
+    /// # See also
+    ///
+    /// * [`Observer::set_typed_context()`]
+    /// * C++ API: `observer::ctx`
+    #[doc(alias = "observer::ctx")]
+    pub fn typed_context<C: 'static>(&self) -> Option<&C> {
+        let observer = self.observer_ptr();
+        unsafe { context_ref((*observer).ctx, (*observer).ctx_free) }
+    }
+
+    /// Set a raw context pointer for the observer
+    ///
+    /// The previous context is freed if it has a free function.
+    ///
     /// # See also
     ///
+    /// * [`Observer::context_ptr()`]
     /// * C++ API: `observer::ctx`
     #[doc(alias = "observer::ctx")]
+    pub fn set_context_ptr(&mut self, context: *mut c_void) {
+        self.set_context_ptr_with_free(context, None);
+    }
+
+    /// Get the raw context pointer for the observer
+    ///
+    /// # See also
+    ///
+    /// * C++ API: `observer::ctx`
+    #[doc(alias = "observer::ctx")]
+    pub fn context_ptr(&self) -> *mut c_void {
+        unsafe { (*self.observer_ptr()).ctx }
+    }
+
+    /// Set a raw context pointer for the observer
+    ///
+    /// # See also
+    ///
+    /// * [`Observer::set_context_ptr()`]
+    #[deprecated(note = "renamed to `set_context_ptr`, use `set_typed_context` for an owned value")]
+    pub fn set_context(&mut self, context: *mut c_void) {
+        self.set_context_ptr(context);
+    }
+
+    /// Get the raw context pointer for the observer
+    ///
+    /// # See also
+    ///
+    /// * [`Observer::context_ptr()`]
+    #[deprecated(note = "renamed to `context_ptr`, use `typed_context` for an owned value")]
     pub fn context(&self) -> *mut c_void {
-        unsafe { (*sys::ecs_observer_get(self.world.world_ptr_mut(), *self.id)).ctx }
+        self.context_ptr()
+    }
+
+    /// Set the priority of the observer.
+    ///
+    /// Observers that are ordered are invoked in order of priority, from low to high. Ordering
+    /// applies to observers that are notified of the same event for the same component, see
+    /// [`ObserverBuilder::priority()`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the observer is built with `run` or isn't built with an [`ObserverBuilder`].
+    ///
+    /// # See also
+    ///
+    /// * [`Observer::priority()`]
+    /// * [`ObserverBuilder::priority()`]
+    pub fn set_priority(&mut self, priority: i32) {
+        let order = ObserverOrder {
+            priority: Some(priority),
+            ..Default::default()
+        };
+        if let Err(err) = observer_order::order_observer(self.world, *self.id(), &order) {
+            panic!("{}", err);
+        }
+    }
+
+    /// Get the priority of the observer, 0 if it isn't ordered.
+    ///
+    /// # See also
+    ///
+    /// * [`Observer::set_priority()`]
+    pub fn priority(&self) -> i32 {
+        observer_order::priority(self.world, *self.id())
+    }
+
+    fn observer_ptr(&self) -> *mut sys::ecs_observer_t {
+        let observer = unsafe { sys::ecs_observer_get(self.world.world_ptr(), *self.id) };
+        ecs_assert!(
+            !observer.is_null(),
+            FlecsErrorCode::InvalidParameter,
+            "entity is not an observer"
+        );
+        observer as *mut sys::ecs_observer_t
+    }
+
+    // the context is updated in place, as reinitializing the observer with `ecs_observer_init`
+    // would also free the callback contexts that hold the Rust closures.
+    fn set_context_ptr_with_free(&mut self, ctx: *mut c_void, ctx_free: sys::ecs_ctx_free_t) {
+        let observer = unsafe { &mut *self.observer_ptr() };
+        replace_context(&mut observer.ctx, &mut observer.ctx_free, ctx, ctx_free);
     }
 
     /// Get the query for the observer
@@ -2,7 +2,9 @@
 
 use std::{default, ffi::c_void};
 
+use crate::core::event::EventPayloadRegistration;
 use crate::core::internals::*;
+use crate::core::observer_order::{self, ObserverOrder};
 use crate::core::private::internal_SystemAPI;
 use crate::core::*;
 use crate::sys;
@@ -19,6 +21,7 @@ pub struct ObserverBuilder<'a, P = (), T: QueryTuple = ()> {
     world: WorldRef<'a>,
     event_count: usize,
     is_instanced: bool,
+    order: ObserverOrder,
     _phantom: std::marker::PhantomData<&'a (T, P)>,
 }
 
@@ -40,6 +43,7 @@ impl<'a, P: ComponentId, T: QueryTuple> ObserverBuilder<'a, P, T> {
             term_builder: TermBuilder::default(),
             event_count: 1,
             is_instanced: false,
+            order: ObserverOrder::default(),
             world: world.world(),
             _phantom: std::marker::PhantomData,
         };
@@ -72,6 +76,7 @@ impl<'a, P: ComponentId, T: QueryTuple> ObserverBuilder<'a, P, T> {
             term_builder: TermBuilder::default(),
             event_count: 1,
             is_instanced: false,
+            order: ObserverOrder::default(),
             world: world.world(),
             _phantom: std::marker::PhantomData,
         };
@@ -88,6 +93,163 @@ impl<'a, P: ComponentId, T: QueryTuple> ObserverBuilder<'a, P, T> {
         T::populate(&mut obj);
         obj
     }
+
+    /// Specify an additional event for when the observer should run, which carries a payload
+    /// of the same type as the event of the observer.
+    ///
+    /// Unlike [`ObserverBuilder::add_event()`], this keeps the payload type of the builder,
+    /// so that the payload can be accessed with [`ObserverBuilder::each_event()`].
+    /// Events with a payload can be emitted with [`World::payload_event()`].
+    ///
+    /// # Type Parameters
+    ///
+    /// * `E` - The event to add
+    ///
+    /// # See also
+    ///
+    /// * [`ObserverBuilder::add_payload_event_id()`]
+    /// * [`ObserverBuilder::each_event()`]
+    pub fn add_payload_event<E>(&mut self) -> &mut Self
+    where
+        E: PayloadEvent<Payload = P>,
+    {
+        self.add_payload_event_id(E::id(self.world))
+    }
+
+    /// Specify an additional event for when the observer should run, which carries a payload
+    /// of the same type as the event of the observer.
+    ///
+    /// Unlike [`ObserverBuilder::add_payload_event()`], the payload type of the event is only
+    /// checked when the event is emitted, which panics if it doesn't match.
+    /// Events with a payload can be emitted with [`World::event_with_payload()`].
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The event to add
+    ///
+    /// # See also
+    ///
+    /// * [`ObserverBuilder::add_payload_event()`]
+    /// * [`ObserverBuilder::each_event()`]
+    pub fn add_payload_event_id(&mut self, event: impl Into<Entity>) -> &mut Self {
+        self.desc.events[self.event_count] = *event.into();
+        self.event_count += 1;
+        self
+    }
+
+    /// Build the observer with a callback that receives the event payload, the entity and the
+    /// components of the entity.
+    ///
+    /// The payload type is the event type of the observer, the same type applies to events added
+    /// with [`ObserverBuilder::add_payload_event()`] or
+    /// [`ObserverBuilder::add_payload_event_id()`]. Emitting one of the events of the observer
+    /// with a payload of a different type, or without payload, panics. The payload types are
+    /// registered for as long as the observer exists.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use flecs_ecs::prelude::*;
+    ///
+    /// #[derive(Component)]
+    /// struct Position {
+    ///     x: f32,
+    ///     y: f32,
+    /// }
+    ///
+    /// #[derive(Component)]
+    /// struct Resized {
+    ///     width: f32,
+    ///     height: f32,
+    /// }
+    ///
+    /// let world = World::new();
+    ///
+    /// world
+    ///     .observer::<Resized, &Position>()
+    ///     .each_event(|resized, entity, pos| {
+    ///         println!(
+    ///             "{} at ({}, {}) resized to {}x{}",
+    ///             entity, pos.x, pos.y, resized.width, resized.height
+    ///         );
+    ///     });
+    ///
+    /// let widget = world.entity().set(Position { x: 10.0, y: 20.0 });
+    ///
+    /// world.event().add::<Position>().entity(widget).emit(&Resized {
+    ///     width: 100.0,
+    ///     height: 50.0,
+    /// });
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// * [`ObserverBuilder::add_payload_event()`]
+    /// * [`World::event()`]
+    /// * [`World::event_with_payload()`]
+    pub fn each_event<Func>(&mut self, mut func: Func) -> Observer<'a>
+    where
+        Func: FnMut(&P::UnderlyingType, EntityView, T::TupleType<'_>) + 'static,
+    {
+        const {
+            assert!(
+                !P::IS_TAG,
+                "called `.each_event()` on an observer for a ZST / tag event, which has no payload"
+            );
+        }
+
+        // the registration is owned by the callback, so it is removed with the observer
+        let registration =
+            EventPayloadRegistration::new::<P>(self.world, &self.desc.events[..self.event_count]);
+
+        self.each_iter(move |it, index, components| {
+            let _registration = &registration;
+            if unsafe { it.param_untyped() }.is_null() {
+                panic!(
+                    "event `{}` was emitted without payload, but the observer expects a payload of type `{}`",
+                    it.event().path().unwrap_or_default(),
+                    std::any::type_name::<P>()
+                );
+            }
+            func(it.param(), it.entity(index), components);
+        })
+    }
+}
+
+impl<'a, T: QueryTuple> ObserverBuilder<'a, flecs::Monitor, T> {
+    /// Create a monitor observer that only invokes its callback for one of the two events of
+    /// a monitor, `OnAdd` when an entity starts matching the query or `OnRemove` when it stops
+    /// matching the query.
+    pub(crate) fn new_match(world: impl WorldProvider<'a>, event: u64) -> Self {
+        let mut obj = Self::new(world);
+        let run = if event == ECS_ON_ADD {
+            run_on_enter as unsafe extern "C" fn(_)
+        } else {
+            run_on_leave as unsafe extern "C" fn(_)
+        };
+        obj.set_desc_run(Some(run));
+        obj
+    }
+}
+
+unsafe extern "C" fn run_on_enter(it: *mut sys::ecs_iter_t) {
+    unsafe { run_on_match(it, ECS_ON_ADD) };
+}
+
+unsafe extern "C" fn run_on_leave(it: *mut sys::ecs_iter_t) {
+    unsafe { run_on_match(it, ECS_ON_REMOVE) };
+}
+
+/// Invokes the callback of a monitor observer if the iterator is for `event`.
+unsafe fn run_on_match(it: *mut sys::ecs_iter_t, event: u64) {
+    unsafe {
+        if (*it).event != event {
+            return;
+        }
+        if let Some(callback) = (*it).callback {
+            callback(it);
+        }
+    }
 }
 
 impl<'a, P, T: QueryTuple> ObserverBuilder<'a, P, T> {
@@ -98,6 +260,7 @@ impl<'a, P, T: QueryTuple> ObserverBuilder<'a, P, T> {
             term_builder: TermBuilder::default(),
             event_count: 0,
             is_instanced: false,
+            order: ObserverOrder::default(),
             world: world.world(),
             _phantom: std::marker::PhantomData,
         };
@@ -130,6 +293,7 @@ impl<'a, P, T: QueryTuple> ObserverBuilder<'a, P, T> {
             event_count: 0,
             world: world.world(),
             is_instanced: false,
+            order: ObserverOrder::default(),
             _phantom: std::marker::PhantomData,
         };
 
@@ -183,6 +347,115 @@ impl<'a, P, T: QueryTuple> ObserverBuilder<'a, P, T> {
         unsafe { std::mem::transmute(self) }
     }
 
+    /// Set the priority of the observer.
+    ///
+    /// Observers that are ordered are invoked in order of priority, from low to high, and in
+    /// the order in which they are ordered if they have the same priority. Ordering applies to
+    /// observers that are notified of the same event for the same component, also if their
+    /// queries are different: an observer of `(&Health, &Name)` is ordered relative to an
+    /// observer of `&mut Health` when `Health` is set. The component must be matched on the same
+    /// source. Observers that aren't ordered are invoked in an unspecified order relative to
+    /// them. Observers that are built with `run` can't be ordered.
+    ///
+    /// # Arguments
+    ///
+    /// * `priority` - The priority of the observer
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use flecs_ecs::prelude::*;
+    ///
+    /// #[derive(Component)]
+    /// struct Health(i32);
+    ///
+    /// #[derive(Component)]
+    /// struct Name(&'static str);
+    ///
+    /// let world = World::new();
+    ///
+    /// // created first, but runs after the validation observer
+    /// world
+    ///     .observer::<flecs::OnSet, (&Health, &Name)>()
+    ///     .priority(1)
+    ///     .each(|(health, name)| println!("{} has {} health", name.0, health.0));
+    ///
+    /// world
+    ///     .observer::<flecs::OnSet, &mut Health>()
+    ///     .priority(-1)
+    ///     .each(|health| health.0 = health.0.clamp(0, 100));
+    ///
+    /// world.entity().set(Name("Bob")).set(Health(150)); // prints "Bob has 100 health"
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// * [`ObserverBuilder::before()`]
+    /// * [`ObserverBuilder::after()`]
+    /// * [`Observer::set_priority()`]
+    pub fn priority(&mut self, priority: i32) -> &mut Self {
+        self.order.priority = Some(priority);
+        self
+    }
+
+    /// Invoke the observer before another observer, regardless of their priority.
+    ///
+    /// The other observer must be notified of an event for a component that this observer is
+    /// notified of as well, see [`ObserverBuilder::priority()`]. Building the observer panics if
+    /// it isn't, or if the constraints form a cycle.
+    ///
+    /// # Arguments
+    ///
+    /// * `observer` - The observer to invoke this observer before
+    ///
+    /// # See also
+    ///
+    /// * [`ObserverBuilder::after()`]
+    pub fn before(&mut self, observer: impl Into<Entity>) -> &mut Self {
+        self.order.before.push(*observer.into());
+        self
+    }
+
+    /// Invoke the observer after another observer, regardless of their priority.
+    ///
+    /// The other observer must be notified of an event for a component that this observer is
+    /// notified of as well, see [`ObserverBuilder::priority()`]. Building the observer panics if
+    /// it isn't, or if the constraints form a cycle.
+    ///
+    /// # Arguments
+    ///
+    /// * `observer` - The observer to invoke this observer after
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use flecs_ecs::prelude::*;
+    ///
+    /// #[derive(Component)]
+    /// struct Health(i32);
+    ///
+    /// let world = World::new();
+    ///
+    /// let clamp = world
+    ///     .observer::<flecs::OnSet, &mut Health>()
+    ///     .each(|health| health.0 = health.0.clamp(0, 100));
+    ///
+    /// world
+    ///     .observer::<flecs::OnSet, &Health>()
+    ///     .after(clamp)
+    ///     .each(|health| assert!(health.0 <= 100));
+    ///
+    /// world.entity().set(Health(150));
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// * [`ObserverBuilder::before()`]
+    pub fn after(&mut self, observer: impl Into<Entity>) -> &mut Self {
+        self.order.after.push(*observer.into());
+        self
+    }
+
     /// Invoke observer for anything that matches its query on creation
     ///
     /// # Arguments
@@ -253,6 +526,14 @@ where
                 );
             }
         }
+        if self.order.is_ordered() {
+            if let Err(err) =
+                observer_order::order_observer(self.world, *observer.id(), &self.order)
+            {
+                observer.destruct();
+                panic!("{}", err);
+            }
+        }
         observer
     }
 }
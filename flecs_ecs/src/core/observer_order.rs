@@ -0,0 +1,485 @@
+//! Invoking observers in order of their priority and `before`/`after` constraints.
+//!
+//! flecs invokes the observers of an event in an unspecified order. Observers that are built
+//! with a callback get a run action, which invokes the callback as flecs would have until the
+//! observer is ordered. flecs still notifies an ordered observer, but its run action skips the
+//! notification. Instead, the event is delivered by a dispatcher, a flecs observer for one event
+//! and one term, which notifies the ordered observers that have the same event and term in
+//! order. Observers with different queries are ordered relative to each other for the events and
+//! terms they have in common.
+
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::ffi::c_void;
+use std::rc::Rc;
+
+use crate::core::*;
+use crate::sys;
+
+/// The ordering of an observer that is configured with an [`ObserverBuilder`].
+#[derive(Default)]
+pub(crate) struct ObserverOrder {
+    pub(crate) priority: Option<i32>,
+    pub(crate) before: Vec<u64>,
+    pub(crate) after: Vec<u64>,
+}
+
+impl ObserverOrder {
+    pub(crate) fn is_ordered(&self) -> bool {
+        self.priority.is_some() || !self.before.is_empty() || !self.after.is_empty()
+    }
+}
+
+/// The ordered observers of a world.
+#[derive(Default)]
+pub(crate) struct ObserverGroups {
+    members: Vec<Rc<Member>>,
+    /// The dispatchers by the event and term they observe, each is owned by its flecs observer.
+    dispatchers: HashMap<Vec<u64>, *mut Dispatcher, fxhash::FxBuildHasher>,
+    next_seq: u64,
+}
+
+/// The run context of an observer that is built with a callback.
+struct ObserverRun {
+    world: *mut sys::ecs_world_t,
+    entity: Cell<u64>,
+    /// Whether the observer is notified by a dispatcher instead of by flecs.
+    ordered: Cell<bool>,
+}
+
+struct Dispatcher {
+    entity: u64,
+    /// The observers that are notified, in order. The dispatcher notifies a snapshot, so that
+    /// observers can be added and removed while it is invoked.
+    order: Rc<[(Rc<Member>, *mut sys::ecs_observer_t)]>,
+}
+
+struct Member {
+    entity: u64,
+    observer: *mut sys::ecs_observer_t,
+    /// The events and terms of the observer, with the flecs observer that is notified of them.
+    keys: Vec<(Vec<u64>, *mut sys::ecs_observer_t)>,
+    priority: Cell<i32>,
+    // observers with the same priority are invoked in the order in which they were ordered
+    seq: u64,
+    before: RefCell<Vec<u64>>,
+    after: RefCell<Vec<u64>>,
+    removed: Cell<bool>,
+    last_event_id: Cell<i32>,
+}
+
+thread_local! {
+    /// The observer that a dispatcher is notifying on this thread.
+    static DISPATCHING: Cell<u64> = const { Cell::new(0) };
+}
+
+/// Set the run action of an observer that is built with a callback, which makes it possible to
+/// order the observer. Returns the run context, which gets the observer entity once it is created.
+pub(crate) fn set_observer_run(
+    world: WorldRef,
+    desc: &mut sys::ecs_observer_desc_t,
+) -> Option<*mut c_void> {
+    if desc.run.is_some() || desc.callback.is_none() || !desc.run_ctx.is_null() {
+        return None;
+    }
+    let run = Box::into_raw(Box::new(ObserverRun {
+        world: world.world_ptr_mut(),
+        entity: Cell::new(0),
+        ordered: Cell::new(false),
+    }));
+    desc.run = Some(run_observer);
+    desc.run_ctx = run as *mut c_void;
+    desc.run_ctx_free = Some(free_observer_run);
+    Some(run as *mut c_void)
+}
+
+/// Set the observer entity of a run context returned by [`set_observer_run`].
+pub(crate) fn observer_created(run: *mut c_void, observer: u64) {
+    unsafe { (*(run as *const ObserverRun)).entity.set(observer) };
+}
+
+/// Returns the priority of an observer, 0 if it isn't ordered.
+pub(crate) fn priority(world: WorldRef, observer: u64) -> i32 {
+    world
+        .world_ctx()
+        .observer_groups
+        .members
+        .iter()
+        .find(|member| member.entity == observer)
+        .map_or(0, |member| member.priority.get())
+}
+
+/// Orders an observer relative to the other ordered observers that have an event and a term in
+/// common with it. The observers it is ordered relative to are ordered as well.
+///
+/// Returns an error if an observer isn't built with a callback, if the observer is ordered
+/// relative to an observer it has no event and term in common with, or if the constraints form
+/// a cycle. Nothing is changed when an error is returned.
+pub(crate) fn order_observer(
+    world: WorldRef,
+    observer: u64,
+    order: &ObserverOrder,
+) -> Result<(), String> {
+    observer_run(world, observer)?;
+    let keys = notified_keys(observer_ptr(world, observer));
+    for &other in order.before.iter().chain(&order.after) {
+        observer_run(world, other)?;
+        let other_keys = notified_keys(observer_ptr(world, other));
+        if !keys
+            .iter()
+            .any(|(key, _)| other_keys.iter().any(|(other, _)| other == key))
+        {
+            return Err(format!(
+                "observer `{}` can only be ordered relative to observers of the same event and component, but `{}` has no event and component in common with it",
+                observer_name(world, observer),
+                observer_name(world, other)
+            ));
+        }
+    }
+
+    // validate the new constraints before any observer is ordered
+    let priority = order.priority.unwrap_or_else(|| priority(world, observer));
+    let members = &world.world_ctx().observer_groups.members;
+    if let Err(cycle) = sort_members(
+        members,
+        Some((observer, priority, &order.before, &order.after)),
+    ) {
+        let names: Vec<String> = cycle
+            .iter()
+            .map(|&observer| observer_name(world, observer))
+            .collect();
+        return Err(format!(
+            "the ordering constraints of observers form a cycle: {}",
+            names.join(", ")
+        ));
+    }
+
+    for &other in order.before.iter().chain(&order.after) {
+        join(world, other);
+    }
+    let member = join(world, observer);
+    member.priority.set(priority);
+    member.before.borrow_mut().extend(&order.before);
+    member.after.borrow_mut().extend(&order.after);
+    update_dispatchers(world);
+    Ok(())
+}
+
+/// Returns the run context of an observer, or an error if it isn't built with a callback.
+fn observer_run<'a>(world: WorldRef, observer: u64) -> Result<&'a ObserverRun, String> {
+    let o = unsafe { sys::ecs_observer_get(world.world_ptr(), observer) };
+    if o.is_null() {
+        return Err(format!(
+            "entity `{}` is not an observer",
+            observer_name(world, observer)
+        ));
+    }
+    let o = unsafe { &*o };
+    if o.run.map(|run| run as *const ()) != Some(run_observer as *const ()) {
+        return Err(format!(
+            "observer `{}` is built with `run` or without an `ObserverBuilder`, which can't be ordered, use `each` or `each_iter` instead",
+            observer_name(world, observer)
+        ));
+    }
+    Ok(unsafe { &*(o.run_ctx as *const ObserverRun) })
+}
+
+/// Orders an observer that isn't ordered yet.
+fn join(world: WorldRef, observer: u64) -> Rc<Member> {
+    let groups = &mut world.world_ctx_mut().observer_groups;
+    if let Some(member) = groups.members.iter().find(|m| m.entity == observer) {
+        return member.clone();
+    }
+
+    let o = observer_ptr(world, observer);
+    let member = Rc::new(Member {
+        entity: observer,
+        observer: o,
+        keys: notified_keys(o),
+        priority: Cell::new(0),
+        seq: groups.next_seq,
+        before: RefCell::new(Vec::new()),
+        after: RefCell::new(Vec::new()),
+        removed: Cell::new(false),
+        last_event_id: Cell::new(0),
+    });
+    groups.next_seq += 1;
+    groups.members.push(member.clone());
+    unsafe { (*((*o).run_ctx as *const ObserverRun)).ordered.set(true) };
+
+    for (key, notified) in &member.keys {
+        if !world
+            .world_ctx()
+            .observer_groups
+            .dispatchers
+            .contains_key(key)
+        {
+            let dispatcher = create_dispatcher(world, key, *notified);
+            let groups = &mut world.world_ctx_mut().observer_groups;
+            groups.dispatchers.insert(key.clone(), dispatcher);
+        }
+    }
+    member
+}
+
+/// Sorts the ordered observers, and updates the observers that dispatchers notify. Dispatchers
+/// that no longer notify an observer are deleted.
+fn update_dispatchers(world: WorldRef) {
+    let groups = &mut world.world_ctx_mut().observer_groups;
+    let order = sort_members(&groups.members, None).unwrap_or_else(|_| unreachable!());
+    let mut unused = Vec::new();
+    for (key, &dispatcher) in &groups.dispatchers {
+        let dispatcher = unsafe { &mut *dispatcher };
+        dispatcher.order = order
+            .iter()
+            .flat_map(|member| {
+                member
+                    .keys
+                    .iter()
+                    .filter(|(member_key, _)| member_key == key)
+                    .map(|&(_, notified)| (member.clone(), notified))
+            })
+            .collect();
+        if dispatcher.order.is_empty() {
+            unused.push((key.clone(), dispatcher.entity));
+        }
+    }
+    for (key, entity) in unused {
+        // the dispatcher is freed with its observer, which may still be invoking it
+        groups.dispatchers.remove(&key);
+        unsafe { sys::ecs_delete(world.world_ptr_mut(), entity) };
+    }
+}
+
+/// Returns the events and terms that flecs notifies an observer of, with the flecs observer that
+/// is notified. flecs notifies multi term observers through an observer for each term.
+fn notified_keys(observer: *mut sys::ecs_observer_t) -> Vec<(Vec<u64>, *mut sys::ecs_observer_t)> {
+    let mut children = std::ptr::null();
+    let count = unsafe { sys::ecs_rust_observer_children(observer, &mut children) };
+    let notified = if count == 0 {
+        vec![observer]
+    } else {
+        unsafe { std::slice::from_raw_parts(children, count as usize) }.to_vec()
+    };
+
+    let mut keys = Vec::new();
+    for o in notified {
+        let (o, query) = unsafe { (&*o, &*(*o).query) };
+        let events = &o.events[..o.event_count as usize];
+        let term = &query.terms[0];
+        let flags = query.flags & (sys::EcsQueryMatchPrefab | sys::EcsQueryMatchDisabled);
+        for &event in events {
+            let key = vec![
+                event,
+                term.id,
+                term.src.id,
+                term.first.id,
+                term.second.id,
+                term.trav,
+                term.oper as u64,
+                flags as u64,
+            ];
+            keys.push((key, o as *const _ as *mut sys::ecs_observer_t));
+        }
+    }
+    keys
+}
+
+/// Creates the flecs observer of a dispatcher, with the event and the term of an observer that
+/// it notifies.
+fn create_dispatcher(
+    world: WorldRef,
+    key: &[u64],
+    notified: *const sys::ecs_observer_t,
+) -> *mut Dispatcher {
+    let dispatcher = Box::into_raw(Box::new(Dispatcher {
+        entity: 0,
+        order: Rc::new([]),
+    }));
+
+    let mut desc = sys::ecs_observer_desc_t::default();
+    desc.events[0] = key[0];
+    unsafe {
+        let query = &*(*notified).query;
+        let term = &query.terms[0];
+        desc.query.terms[0] = sys::ecs_term_t {
+            id: term.id,
+            src: term.src,
+            first: term.first,
+            second: term.second,
+            trav: term.trav,
+            oper: term.oper,
+            ..Default::default()
+        };
+        desc.query.flags = sys::EcsQueryIsInstanced
+            | (query.flags & (sys::EcsQueryMatchPrefab | sys::EcsQueryMatchDisabled));
+    }
+    desc.run = Some(dispatch);
+    desc.run_ctx = dispatcher as *mut c_void;
+    desc.run_ctx_free = Some(free_dispatcher);
+    unsafe { (*dispatcher).entity = sys::ecs_observer_init(world.world_ptr_mut(), &desc) };
+    dispatcher
+}
+
+/// Sorts the ordered observers, optionally with an observer that is about to be ordered or to
+/// change its constraints. Returns the observers that form a cycle if the constraints can't be
+/// satisfied.
+#[allow(clippy::type_complexity)]
+fn sort_members(
+    members: &[Rc<Member>],
+    candidate: Option<(u64, i32, &[u64], &[u64])>,
+) -> Result<Rc<[Rc<Member>]>, Vec<u64>> {
+    struct Node {
+        entity: u64,
+        priority: i32,
+        seq: u64,
+        before: Vec<u64>,
+        after: Vec<u64>,
+    }
+
+    let mut nodes: Vec<Node> = members
+        .iter()
+        .filter(|member| !member.removed.get())
+        .map(|member| Node {
+            entity: member.entity,
+            priority: member.priority.get(),
+            seq: member.seq,
+            before: member.before.borrow().clone(),
+            after: member.after.borrow().clone(),
+        })
+        .collect();
+
+    if let Some((entity, priority, before, after)) = candidate {
+        let index = match nodes.iter().position(|node| node.entity == entity) {
+            Some(index) => index,
+            None => {
+                nodes.push(Node {
+                    entity,
+                    priority,
+                    seq: u64::MAX,
+                    before: Vec::new(),
+                    after: Vec::new(),
+                });
+                nodes.len() - 1
+            }
+        };
+        nodes[index].priority = priority;
+        nodes[index].before.extend(before);
+        nodes[index].after.extend(after);
+        // observers that aren't ordered yet are ordered as well
+        for &other in before.iter().chain(after) {
+            if !nodes.iter().any(|node| node.entity == other) {
+                nodes.push(Node {
+                    entity: other,
+                    priority: 0,
+                    seq: u64::MAX,
+                    before: Vec::new(),
+                    after: Vec::new(),
+                });
+            }
+        }
+    }
+
+    // incoming[i] holds the nodes that must be invoked before node i
+    let index_of = |entity: u64| nodes.iter().position(|node| node.entity == entity);
+    let mut incoming: Vec<Vec<usize>> = vec![Vec::new(); nodes.len()];
+    for (i, node) in nodes.iter().enumerate() {
+        for target in node.before.iter().filter_map(|&e| index_of(e)) {
+            incoming[target].push(i);
+        }
+        for source in node.after.iter().filter_map(|&e| index_of(e)) {
+            incoming[i].push(source);
+        }
+    }
+
+    let mut done = vec![false; nodes.len()];
+    let mut order = Vec::with_capacity(nodes.len());
+    while order.len() < nodes.len() {
+        let next = (0..nodes.len())
+            .filter(|&i| !done[i] && incoming[i].iter().all(|&j| done[j]))
+            .min_by_key(|&i| (nodes[i].priority, nodes[i].seq));
+        match next {
+            Some(i) => {
+                done[i] = true;
+                order.push(i);
+            }
+            None => {
+                return Err((0..nodes.len())
+                    .filter(|&i| !done[i])
+                    .map(|i| nodes[i].entity)
+                    .collect());
+            }
+        }
+    }
+
+    Ok(order
+        .into_iter()
+        .filter_map(|i| members.iter().find(|m| m.entity == nodes[i].entity))
+        .cloned()
+        .collect())
+}
+
+fn observer_name(world: WorldRef, observer: u64) -> String {
+    let entity = EntityView::new_from(world, observer);
+    entity.path().unwrap_or_else(|| entity.id().to_string())
+}
+
+fn observer_ptr(world: WorldRef, observer: u64) -> *mut sys::ecs_observer_t {
+    unsafe { sys::ecs_observer_get(world.world_ptr(), observer) as *mut sys::ecs_observer_t }
+}
+
+/// The run action of observers that are built with a callback, which invokes the callback as
+/// flecs would have, unless the observer is ordered and notified by flecs instead of by its
+/// dispatcher.
+unsafe extern "C" fn run_observer(it: *mut sys::ecs_iter_t) {
+    unsafe {
+        let run = &*((*it).run_ctx as *const ObserverRun);
+        if run.ordered.get() && DISPATCHING.replace(0) != run.entity.get() {
+            return;
+        }
+        if let Some(callback) = (*it).callback {
+            callback(it);
+        }
+    }
+}
+
+/// The run action of a dispatcher, which notifies the ordered observers of its event and term.
+unsafe extern "C" fn dispatch(it: *mut sys::ecs_iter_t) {
+    let dispatcher = unsafe { &*((*it).run_ctx as *const Dispatcher) };
+    let order = dispatcher.order.clone();
+    for (member, notified) in order.iter() {
+        if member.removed.get() {
+            continue;
+        }
+        let mut last_event_id = member.last_event_id.get();
+        DISPATCHING.set(member.entity);
+        unsafe {
+            sys::ecs_rust_observer_invoke(it, member.observer, *notified, &mut last_event_id);
+        }
+        DISPATCHING.set(0);
+        member.last_event_id.set(last_event_id);
+    }
+}
+
+/// Frees the run context of an observer, and removes the observer from the ordered observers.
+unsafe extern "C" fn free_observer_run(ptr: *mut c_void) {
+    let run = unsafe { Box::from_raw(ptr as *mut ObserverRun) };
+    if !run.ordered.get() || unsafe { sys::ecs_is_fini(run.world) } {
+        return;
+    }
+
+    let world = unsafe { WorldRef::from_ptr(run.world) };
+    let groups = &mut world.world_ctx_mut().observer_groups;
+    if let Some(index) = groups
+        .members
+        .iter()
+        .position(|member| member.entity == run.entity.get())
+    {
+        groups.members.remove(index).removed.set(true);
+    }
+    update_dispatchers(world);
+}
+
+unsafe extern "C" fn free_dispatcher(ptr: *mut c_void) {
+    drop(unsafe { Box::from_raw(ptr as *mut Dispatcher) });
+}
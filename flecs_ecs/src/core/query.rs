@@ -210,6 +210,7 @@ where
         if query_ptr.is_null() {
             panic!("Failed to create query, this is due to the user creating an invalid query. Most likely by using `expr` with a wrong expression.");
         }
+        register_query(query_ptr);
 
         unsafe {
             let world_ctx = ecs_get_binding_ctx(world_ptr) as *mut WorldCtx;
@@ -226,6 +227,68 @@ where
         }
     }
 
+    /// Create a new query from a query expression, checking the leading terms against `T`.
+    ///
+    /// Errors logged by flecs while parsing the expression are captured and returned
+    /// as a [`QueryError`] instead of being printed.
+    ///
+    /// # Arguments
+    ///
+    /// * `world` - The world to create the query in
+    /// * `expr` - The query expression in the flecs query language
+    pub(crate) fn try_new_from_expr<'a>(
+        world: impl WorldProvider<'a>,
+        expr: &str,
+    ) -> Result<Self, QueryError> {
+        let world = world.world();
+        let c_expr = std::ffi::CString::new(expr).map_err(|e| {
+            QueryError::new(
+                QueryErrorKind::Parse,
+                expr,
+                None,
+                Some(e.nul_position()),
+                "expression contains a nul character",
+            )
+        })?;
+
+        let desc = sys::ecs_query_desc_t {
+            expr: c_expr.as_ptr(),
+            ..Default::default()
+        };
+        let (query_ptr, errors) =
+            capture_parse_errors(|| unsafe { sys::ecs_query_init(world.world_ptr_mut(), &desc) });
+
+        if query_ptr.is_null() {
+            return Err(match errors.first() {
+                Some(log) => parse_error_from_log(expr, log),
+                None => QueryError::new(
+                    QueryErrorKind::Parse,
+                    expr,
+                    None,
+                    None,
+                    "failed to create query from expression",
+                ),
+            });
+        }
+
+        register_query(query_ptr);
+        let query = unsafe {
+            let world_ctx = ecs_get_binding_ctx(world.world_ptr()) as *mut WorldCtx;
+            (*world_ctx).inc_query_ref_count();
+
+            Self {
+                query: NonNull::new_unchecked(query_ptr),
+                world_ctx: NonNull::new_unchecked(world_ctx),
+                _phantom: PhantomData,
+            }
+        };
+
+        // on failure the query is dropped, which releases it again
+        validate_query_terms::<T>(world, unsafe { query.query.as_ref() }, expr)?;
+
+        Ok(query)
+    }
+
     pub(crate) fn new_from_entity<'a>(
         world: impl WorldProvider<'a>,
         entity: impl Into<Entity>,
@@ -322,6 +385,22 @@ where
         unsafe { sys::ecs_query_changed(self.query.as_ptr()) }
     }
 
+    /// Get information about the query and its cache, such as the number of matched tables
+    /// and the memory used by the cache.
+    ///
+    /// # Returns
+    ///
+    /// A snapshot of the query information at the time of the call.
+    ///
+    /// # See also
+    ///
+    /// * [`QueryInfo`]
+    /// * C API: `ecs_query_count`
+    #[doc(alias = "ecs_query_count")]
+    pub fn info(&self) -> QueryInfo {
+        QueryInfo::new(self.query.as_ptr())
+    }
+
     /// Get info for group
     ///
     /// # Arguments
@@ -340,8 +419,47 @@ where
         unsafe { sys::ecs_query_get_group_info(self.query.as_ptr(), *group_id.into()) }
     }
 
+    /// Get the typed context for a group
+    ///
+    /// The context is created by the closure passed to [`QueryBuilderImpl::on_group_create_with()`].
+    ///
+    /// # Type Parameters
+    ///
+    /// * `C` - The type of the group context
+    ///
+    /// # Arguments
+    ///
+    /// * `group_id` - The group id to get context for
+    ///
+    /// # Returns
+    ///
+    /// Returns the group context, or `None` if the group doesn't exist, has no context or if
+    /// the context is not of type `C`.
+    ///
+    /// # See also
+    ///
+    /// * [`Query::group_context_ptr()`]
+    /// * C++ API: `query_base::group_ctx`
+    #[doc(alias = "query_base::group_ctx")]
+    pub fn group_context<C: 'static>(&self, group_id: impl Into<Entity>) -> Option<&C> {
+        let group_by_ctx = GroupByContext::from_query(self.query.as_ptr())?;
+        if !group_by_ctx.is_group_type::<C>() {
+            return None;
+        }
+
+        let ctx = self.group_context_ptr(group_id);
+        if ctx.is_null() {
+            None
+        } else {
+            Some(unsafe { &*(ctx as *const C) })
+        }
+    }
+
     /// Get context for group
     ///
+    /// Returns the context as returned by the `extern "C"` callback passed to
+    /// [`QueryBuilderImpl::on_group_create()`].
+    ///
     /// # Arguments
     ///
     /// * `group_id` - The group id to get context for
@@ -352,9 +470,10 @@ where
     ///
     /// # See also
     ///
+    /// * [`Query::group_context()`]
     /// * C++ API: `query_base::group_ctx`
     #[doc(alias = "query_base::group_ctx")]
-    pub fn group_context(&self, group_id: impl Into<Entity>) -> *mut c_void {
+    pub fn group_context_ptr(&self, group_id: impl Into<Entity>) -> *mut c_void {
         let group_info = self.group_info(group_id);
 
         if !group_info.is_null() {
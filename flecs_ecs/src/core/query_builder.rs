@@ -1,7 +1,9 @@
 //! Builder for [`Query`].
 
 use std::ffi::c_void;
+use std::ptr::NonNull;
 
+use crate::core::callback_panic::catch_callback_panic;
 use crate::core::internals::*;
 use crate::core::*;
 use crate::sys;
@@ -205,6 +207,12 @@ where
     #[doc(alias = "node_builder::build")]
     fn build(&mut self) -> Self::BuiltType {
         let world = self.world;
+        // change detection of `Changed` terms requires a cached query
+        if T::CHANGED_FIELDS != 0
+            && self.desc.cache_kind == QueryCacheKind::Default as sys::ecs_query_cache_kind_t
+        {
+            self.desc.cache_kind = QueryCacheKind::Auto as sys::ecs_query_cache_kind_t;
+        }
         let query = Query::<T>::new_from_desc(world, &mut self.desc);
         for string_parts in self.term_builder.str_ptrs_to_free.iter() {
             unsafe {
@@ -1060,6 +1068,182 @@ pub trait QueryBuilderImpl<'a>: TermBuilderImpl<'a> {
         desc.on_group_delete = action;
         self
     }
+
+    /// Group and sort matched tables with a closure.
+    ///
+    /// Same as [`group_by_fn`](Self::group_by_fn), but accepts a Rust closure that is owned
+    /// by the query and dropped with it, instead of an `extern "C"` function.
+    ///
+    /// # Type Parameters
+    ///
+    /// * `T`: The component passed to the closure, e.g. the relationship to group by.
+    ///
+    /// # Arguments
+    ///
+    /// * `group_by`: Closure that returns the group id for a table.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use flecs_ecs::prelude::*;
+    ///
+    /// #[derive(Component)]
+    /// struct Cell {
+    ///     x: i32,
+    /// }
+    ///
+    /// let world = World::new();
+    ///
+    /// let query = world
+    ///     .query::<&Cell>()
+    ///     .group_by_with::<Cell>(|table, _| table.find_type_index::<Cell>().unwrap() as u64)
+    ///     .build();
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// * [`QueryBuilderImpl::group_by_id_with()`]
+    /// * C++ API: `query_builder_i::group_by`
+    #[doc(alias = "query_builder_i::group_by")]
+    fn group_by_with<T>(&mut self, group_by: impl FnMut(Table, Id) -> u64 + 'static) -> &mut Self
+    where
+        T: ComponentId,
+    {
+        let id = T::id(self.world());
+        self.group_by_id_with(id, group_by)
+    }
+
+    /// Group and sort matched tables with a closure.
+    ///
+    /// This is similar to `group_by_with<T>`, but uses a component identifier instead.
+    ///
+    /// # Arguments
+    ///
+    /// * `component`: The component passed to the closure.
+    /// * `group_by`: Closure that returns the group id for a table.
+    ///
+    /// # See also
+    ///
+    /// * [`QueryBuilderImpl::group_by_with()`]
+    /// * C++ API: `query_builder_i::group_by`
+    #[doc(alias = "query_builder_i::group_by")]
+    fn group_by_id_with(
+        &mut self,
+        component: impl Into<Entity>,
+        group_by: impl FnMut(Table, Id) -> u64 + 'static,
+    ) -> &mut Self {
+        let desc = self.query_desc_mut();
+        group_by_context(desc).group_by = Some(Box::new(group_by));
+        desc.group_by_callback = Some(GroupByContext::group_by);
+        desc.group_by = *component.into();
+        self
+    }
+
+    /// Create a typed context for each group of the query.
+    ///
+    /// The closure is invoked when a new group is created and returns the context of the group,
+    /// which can be retrieved with [`Query::group_context()`]. The context is owned by the query,
+    /// it is dropped when the group is deleted or when the query is destructed.
+    ///
+    /// # Type Parameters
+    ///
+    /// * `C`: The type of the group context.
+    ///
+    /// # Arguments
+    ///
+    /// * `on_create`: Closure that receives the world and group id and returns the group context.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use flecs_ecs::prelude::*;
+    ///
+    /// #[derive(Component)]
+    /// struct Cell;
+    ///
+    /// struct CellInfo {
+    ///     id: u64,
+    /// }
+    ///
+    /// let world = World::new();
+    ///
+    /// let cell = world.entity();
+    /// world.entity().add_first::<Cell>(cell);
+    ///
+    /// let query = world
+    ///     .query::<()>()
+    ///     .with_first::<Cell>(flecs::Wildcard::ID)
+    ///     .group_by::<Cell>()
+    ///     .on_group_create_with(|_world, group_id| CellInfo { id: group_id })
+    ///     .build();
+    ///
+    /// query.run(|mut it| {
+    ///     while it.next() {
+    ///         let info = query.group_context::<CellInfo>(it.group_id()).unwrap();
+    ///         assert_eq!(info.id, cell.id());
+    ///     }
+    /// });
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// * [`QueryBuilderImpl::on_group_delete_with()`]
+    /// * [`Query::group_context()`]
+    /// * C++ API: `query_builder_i::on_group_create`
+    #[doc(alias = "query_builder_i::on_group_create")]
+    fn on_group_create_with<C>(
+        &mut self,
+        mut on_create: impl FnMut(WorldRef, u64) -> C + 'static,
+    ) -> &mut Self
+    where
+        C: 'static,
+    {
+        let desc = self.query_desc_mut();
+        let ctx = group_by_context(desc);
+        ctx.set_group_type::<C>();
+        ctx.on_create = Some(Box::new(move |world, group_id| {
+            Box::into_raw(Box::new(on_create(world, group_id))) as *mut c_void
+        }));
+        desc.on_group_create = Some(GroupByContext::on_group_create);
+        desc.on_group_delete = Some(GroupByContext::on_group_delete);
+        self
+    }
+
+    /// Specify a closure that is invoked when a group with a typed context is deleted.
+    ///
+    /// The closure receives the context created by [`on_group_create_with`](Self::on_group_create_with)
+    /// by value. It is not invoked for groups that don't have a context.
+    ///
+    /// # Type Parameters
+    ///
+    /// * `C`: The type of the group context, must be the same as for `on_group_create_with`.
+    ///
+    /// # Arguments
+    ///
+    /// * `on_delete`: Closure that receives the world, group id and group context.
+    ///
+    /// # See also
+    ///
+    /// * [`QueryBuilderImpl::on_group_create_with()`]
+    /// * C++ API: `query_builder_i::on_group_delete`
+    #[doc(alias = "query_builder_i::on_group_delete")]
+    fn on_group_delete_with<C>(
+        &mut self,
+        mut on_delete: impl FnMut(WorldRef, u64, C) + 'static,
+    ) -> &mut Self
+    where
+        C: 'static,
+    {
+        let desc = self.query_desc_mut();
+        let ctx = group_by_context(desc);
+        ctx.set_group_type::<C>();
+        ctx.on_delete = Some(Box::new(move |world, group_id, group_ctx| {
+            let group_ctx = unsafe { Box::from_raw(group_ctx as *mut C) };
+            on_delete(world, group_id, *group_ctx);
+        }));
+        desc.on_group_delete = Some(GroupByContext::on_group_delete);
+        self
+    }
 }
 
 pub trait OrderByFn<T>
@@ -1083,7 +1267,10 @@ where
         where
             F: Fn(Entity, &T, Entity, &T) -> i32,
         {
-            (unsafe { std::mem::transmute_copy::<_, F>(&()) })(e1, e1_data, e2, e2_data)
+            catch_callback_panic(std::ptr::null(), || {
+                (unsafe { std::mem::transmute_copy::<_, F>(&()) })(e1, e1_data, e2, e2_data)
+            })
+            .unwrap_or_default()
         }
 
         output::<F, T>
@@ -1113,9 +1300,132 @@ where
         where
             F: Fn(Entity, *const c_void, Entity, *const c_void) -> i32,
         {
-            (unsafe { std::mem::transmute_copy::<_, F>(&()) })(e1, e1_data, e2, e2_data)
+            catch_callback_panic(std::ptr::null(), || {
+                (unsafe { std::mem::transmute_copy::<_, F>(&()) })(e1, e1_data, e2, e2_data)
+            })
+            .unwrap_or_default()
         }
 
         output::<F>
     }
 }
+
+type GroupByClosure = Box<dyn FnMut(Table, Id) -> u64>;
+type GroupCreateClosure = Box<dyn FnMut(WorldRef, u64) -> *mut c_void>;
+type GroupDeleteClosure = Box<dyn FnMut(WorldRef, u64, *mut c_void)>;
+type GroupCtxDrop = unsafe fn(*mut c_void);
+
+/// The `group_by` context of queries that use the closure based group callbacks.
+#[derive(Default)]
+pub(crate) struct GroupByContext {
+    group_by: Option<GroupByClosure>,
+    on_create: Option<GroupCreateClosure>,
+    on_delete: Option<GroupDeleteClosure>,
+    /// Type of the group contexts and the function that drops them.
+    group_type: Option<(std::any::TypeId, GroupCtxDrop)>,
+}
+
+impl GroupByContext {
+    fn set_group_type<C: 'static>(&mut self) {
+        unsafe fn drop_group_ctx<C>(group_ctx: *mut c_void) {
+            drop(Box::from_raw(group_ctx as *mut C));
+        }
+
+        let type_id = std::any::TypeId::of::<C>();
+        ecs_assert!(
+            self.group_type.map_or(true, |(id, _)| id == type_id),
+            FlecsErrorCode::InvalidParameter,
+            "on_group_create_with and on_group_delete_with must use the same group context type"
+        );
+        self.group_type = Some((type_id, drop_group_ctx::<C>));
+    }
+
+    /// Returns the context if `ctx` is the `group_by` context of a query using the closure based callbacks.
+    pub(crate) fn from_query<'a>(query: *const sys::ecs_query_t) -> Option<&'a Self> {
+        let mut ctx_free: sys::ecs_ctx_free_t = None;
+        let ctx = unsafe { sys::ecs_rust_query_group_by_ctx(query, &mut ctx_free) };
+        if ctx.is_null() || !Self::is_owned(ctx_free) {
+            return None;
+        }
+        Some(unsafe { &*(ctx as *const Self) })
+    }
+
+    /// Returns whether the group context was created by `on_group_create_with::<C>`.
+    pub(crate) fn is_group_type<C: 'static>(&self) -> bool {
+        self.group_type
+            .is_some_and(|(id, _)| id == std::any::TypeId::of::<C>())
+    }
+
+    fn is_owned(ctx_free: sys::ecs_ctx_free_t) -> bool {
+        ctx_free.map(|free| free as *const ()) == Some(Self::free as *const ())
+    }
+
+    extern "C" fn free(ctx: *mut c_void) {
+        unsafe { drop(Box::from_raw(ctx as *mut Self)) };
+    }
+
+    extern "C" fn group_by(
+        world: *mut sys::ecs_world_t,
+        table: *mut sys::ecs_table_t,
+        id: sys::ecs_id_t,
+        ctx: *mut c_void,
+    ) -> u64 {
+        let ctx = unsafe { &mut *(ctx as *mut Self) };
+        catch_callback_panic(world, || {
+            let world = unsafe { WorldRef::from_ptr(world) };
+            let table = Table::new(world, NonNull::new(table).expect("table is null"));
+            let group_by = ctx.group_by.as_mut().expect("group_by closure is not set");
+            group_by(table, Id::new(id))
+        })
+        .unwrap_or_default()
+    }
+
+    extern "C" fn on_group_create(
+        world: *mut sys::ecs_world_t,
+        group_id: u64,
+        ctx: *mut c_void,
+    ) -> *mut c_void {
+        let ctx = unsafe { &mut *(ctx as *mut Self) };
+        let Some(on_create) = ctx.on_create.as_mut() else {
+            return std::ptr::null_mut();
+        };
+        catch_callback_panic(world, || {
+            on_create(unsafe { WorldRef::from_ptr(world) }, group_id)
+        })
+        .unwrap_or(std::ptr::null_mut())
+    }
+
+    extern "C" fn on_group_delete(
+        world: *mut sys::ecs_world_t,
+        group_id: u64,
+        group_ctx: *mut c_void,
+        ctx: *mut c_void,
+    ) {
+        if group_ctx.is_null() {
+            return;
+        }
+
+        let ctx = unsafe { &mut *(ctx as *mut Self) };
+        if let Some(on_delete) = ctx.on_delete.as_mut() {
+            catch_callback_panic(world, || {
+                on_delete(unsafe { WorldRef::from_ptr(world) }, group_id, group_ctx);
+            });
+        } else if let Some((_, drop_group_ctx)) = ctx.group_type {
+            unsafe { drop_group_ctx(group_ctx) };
+        }
+    }
+}
+
+/// Returns the closure based `group_by` context of the query descriptor, creating it if it doesn't exist yet.
+fn group_by_context(desc: &mut sys::ecs_query_desc_t) -> &mut GroupByContext {
+    if !GroupByContext::is_owned(desc.group_by_ctx_free) {
+        ecs_assert!(
+            desc.group_by_ctx.is_null(),
+            FlecsErrorCode::InvalidOperation,
+            "closure based group callbacks can't be combined with a context set by group_by_ctx"
+        );
+        desc.group_by_ctx = Box::into_raw(Box::<GroupByContext>::default()) as *mut c_void;
+        desc.group_by_ctx_free = Some(GroupByContext::free);
+    }
+    unsafe { &mut *(desc.group_by_ctx as *mut GroupByContext) }
+}
@@ -0,0 +1,367 @@
+//! Errors that can occur when creating a [`Query`] from a runtime expression.
+
+use std::cell::RefCell;
+use std::ffi::{c_char, CStr};
+use std::fmt::{Display, Formatter};
+use std::sync::Mutex;
+
+use crate::core::*;
+use crate::sys;
+
+/// The kind of problem that was found while creating a query from an expression.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueryErrorKind {
+    /// The expression could not be parsed or resolved by flecs, e.g. because of a syntax error
+    /// or an identifier that doesn't exist.
+    Parse,
+    /// The expression has fewer terms than the Rust components tuple.
+    MissingTerm,
+    /// The term matches a different (component) id than the one in the Rust components tuple.
+    TypeMismatch,
+    /// The access mode of the term (`[in]`, `[out]`, `[none]`, ...) is not compatible with the
+    /// reference type in the Rust components tuple.
+    AccessMismatch,
+    /// The operator of the term (e.g. `?` or `!`) does not match the Rust components tuple.
+    OperatorMismatch,
+}
+
+/// Error returned by [`World::try_query_from_expr()`] when an expression is not a valid query
+/// or does not match the Rust components tuple.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryError {
+    /// The kind of error.
+    pub kind: QueryErrorKind,
+    /// The index of the term the error applies to, if known.
+    pub term_index: Option<usize>,
+    /// The (zero based) byte offset into the expression the error applies to, if known.
+    pub column: Option<usize>,
+    /// Human readable description of the error.
+    pub message: String,
+}
+
+impl QueryError {
+    pub(crate) fn new(
+        kind: QueryErrorKind,
+        expr: &str,
+        term_index: Option<usize>,
+        column: Option<usize>,
+        message: impl Into<String>,
+    ) -> Self {
+        // derive whichever location is missing from the one we do know
+        let (term_index, column) = match (term_index, column) {
+            (Some(term_index), None) => (Some(term_index), term_column(expr, term_index)),
+            (None, Some(column)) => (Some(term_index_at(expr, column)), Some(column)),
+            location => location,
+        };
+
+        Self {
+            kind,
+            term_index,
+            column,
+            message: message.into(),
+        }
+    }
+}
+
+impl Display for QueryError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match (self.term_index, self.column) {
+            (Some(term), Some(column)) => {
+                write!(f, "term {} (column {}): {}", term, column, self.message)
+            }
+            (Some(term), None) => write!(f, "term {}: {}", term, self.message),
+            (None, Some(column)) => write!(f, "column {}: {}", column, self.message),
+            (None, None) => write!(f, "{}", self.message),
+        }
+    }
+}
+
+impl std::error::Error for QueryError {}
+
+/// Iterates the byte offsets at which each top level term of a query expression starts.
+///
+/// Terms are separated by `,` and `||`, separators inside of `()`, `[]` and `{}` are ignored.
+fn term_starts(expr: &str) -> impl Iterator<Item = usize> + '_ {
+    let bytes = expr.as_bytes();
+    let mut depth = 0i32;
+    let mut i = 0;
+    let mut first = true;
+
+    std::iter::from_fn(move || {
+        if first {
+            first = false;
+            return Some(skip_whitespace(bytes, 0));
+        }
+
+        while i < bytes.len() {
+            let c = bytes[i];
+            i += 1;
+            match c {
+                b'(' | b'[' | b'{' => depth += 1,
+                b')' | b']' | b'}' => depth -= 1,
+                b',' if depth == 0 => return Some(skip_whitespace(bytes, i)),
+                b'|' if depth == 0 && bytes.get(i) == Some(&b'|') => {
+                    i += 1;
+                    return Some(skip_whitespace(bytes, i));
+                }
+                _ => {}
+            }
+        }
+        None
+    })
+}
+
+fn skip_whitespace(bytes: &[u8], mut index: usize) -> usize {
+    while index < bytes.len() && bytes[index].is_ascii_whitespace() {
+        index += 1;
+    }
+    index
+}
+
+/// Returns the byte offset at which the term with index `term_index` starts.
+fn term_column(expr: &str, term_index: usize) -> Option<usize> {
+    term_starts(expr).nth(term_index)
+}
+
+/// Returns the index of the term that contains the byte offset `column`.
+fn term_index_at(expr: &str, column: usize) -> usize {
+    term_starts(expr)
+        .take_while(|&start| start <= column)
+        .count()
+        .saturating_sub(1)
+}
+
+/// The log action that was installed before the capture hook, and the number of threads that
+/// are capturing errors.
+struct LogHook {
+    prev: sys::ecs_os_api_log_t,
+    active: usize,
+}
+
+static LOG_HOOK: Mutex<LogHook> = Mutex::new(LogHook {
+    prev: None,
+    active: 0,
+});
+
+thread_local! {
+    static PARSE_ERRORS: RefCell<Option<Vec<String>>> = const { RefCell::new(None) };
+}
+
+extern "C" fn capture_parse_error(level: i32, file: *const c_char, line: i32, msg: *const c_char) {
+    let captured = PARSE_ERRORS.with(|errors| {
+        let mut errors = errors.borrow_mut();
+        let Some(errors) = errors.as_mut() else {
+            return false;
+        };
+        // only errors are of interest, anything else is forwarded to the original log action.
+        if level != -3 || msg.is_null() {
+            return false;
+        }
+        errors.push(
+            unsafe { CStr::from_ptr(msg) }
+                .to_string_lossy()
+                .into_owned(),
+        );
+        true
+    });
+
+    if !captured {
+        let prev = LOG_HOOK.lock().map_or(None, |hook| hook.prev);
+        if let Some(log) = prev {
+            unsafe { log(level, file, line, msg) };
+        }
+    }
+}
+
+fn is_capture_hook(log: sys::ecs_os_api_log_t) -> bool {
+    log.is_some_and(|log| {
+        log as *const () == capture_parse_error as extern "C" fn(_, _, _, _) as *const ()
+    })
+}
+
+/// Runs `func` while capturing the errors logged by flecs on the current thread instead of printing them.
+///
+/// flecs has no API that returns the error of a failed query, so the log action is replaced by a
+/// hook while any thread is inside this function. The hook only captures errors of the threads
+/// that are capturing, anything else is forwarded to the log action that was installed before.
+/// The previous log action is restored when the last thread leaves this function, unless the
+/// application replaced the log action in the meantime.
+pub(crate) fn capture_parse_errors<R>(func: impl FnOnce() -> R) -> (R, Vec<String>) {
+    let _hook = LogHookGuard::install();
+
+    let prev = PARSE_ERRORS.with(|errors| errors.borrow_mut().replace(Vec::new()));
+    let result = func();
+    let errors = PARSE_ERRORS.with(|errors| {
+        let mut errors = errors.borrow_mut();
+        std::mem::replace(&mut *errors, prev).unwrap_or_default()
+    });
+
+    (result, errors)
+}
+
+/// Keeps the capture hook installed while it is alive.
+struct LogHookGuard;
+
+impl LogHookGuard {
+    fn install() -> Self {
+        let mut hook = LOG_HOOK.lock().unwrap_or_else(|err| err.into_inner());
+        let current = unsafe { sys::ecs_os_api.log_ };
+        // the log action may have been replaced since the hook was installed, in which case
+        // the hook is installed again on top of the new log action
+        if !is_capture_hook(current) {
+            hook.prev = current;
+            unsafe { sys::ecs_os_api.log_ = Some(capture_parse_error) };
+        }
+        hook.active += 1;
+        LogHookGuard
+    }
+}
+
+impl Drop for LogHookGuard {
+    fn drop(&mut self) {
+        let mut hook = LOG_HOOK.lock().unwrap_or_else(|err| err.into_inner());
+        hook.active -= 1;
+        if hook.active == 0 && is_capture_hook(unsafe { sys::ecs_os_api.log_ }) {
+            unsafe { sys::ecs_os_api.log_ = hook.prev };
+        }
+    }
+}
+
+/// Converts an error as logged by flecs while creating a query into a [`QueryError`].
+///
+/// Syntax errors are formatted as:
+///
+/// ```text
+/// <line>: <message>
+/// <expression line>
+///         ^
+/// ```
+///
+/// While errors found when validating the parsed terms are formatted as:
+///
+/// ```text
+/// <message>
+/// expr: <expression>
+///    <term 0>,
+///  > <term 1>
+/// ```
+pub(crate) fn parse_error_from_log(expr: &str, log: &str) -> QueryError {
+    let mut lines = log.lines();
+    let first = lines.next().unwrap_or_default();
+
+    if let Some((line_nr, message)) = first
+        .split_once(": ")
+        .and_then(|(line_nr, message)| Some((line_nr.trim().parse::<usize>().ok()?, message)))
+    {
+        let column = lines
+            .nth(1)
+            .and_then(|caret_line| caret_line.find('^'))
+            .map(|caret| {
+                let line_start: usize = expr
+                    .split_inclusive('\n')
+                    .take(line_nr.saturating_sub(1))
+                    .map(str::len)
+                    .sum();
+                line_start + caret
+            });
+
+        return QueryError::new(QueryErrorKind::Parse, expr, None, column, message);
+    }
+
+    let term_index = lines
+        .filter(|line| !line.starts_with("expr: "))
+        .position(|line| line.starts_with(" > "));
+
+    QueryError::new(QueryErrorKind::Parse, expr, term_index, None, first)
+}
+
+/// Checks that the leading terms of a query match the terms generated for the components tuple `T`.
+pub(crate) fn validate_query_terms<T: QueryTuple>(
+    world: WorldRef,
+    query: &sys::ecs_query_t,
+    expr: &str,
+) -> Result<(), QueryError> {
+    let mut expected: [sys::ecs_term_t; sys::FLECS_TERM_COUNT_MAX as usize] =
+        unsafe { std::mem::zeroed() };
+    T::register_ids_descriptor_at(world.world_ptr_mut(), &mut expected[..], &mut 0);
+
+    let count = T::COUNT as usize;
+    if (query.term_count as usize) < count {
+        return Err(QueryError::new(
+            QueryErrorKind::MissingTerm,
+            expr,
+            Some(query.term_count as usize),
+            Some(expr.len()),
+            format!(
+                "expression has {} term(s), but the components tuple requires {}",
+                query.term_count, count
+            ),
+        ));
+    }
+
+    for (index, (term, expected)) in query.terms.iter().zip(expected.iter()).enumerate() {
+        if index >= count {
+            break;
+        }
+
+        let error = |kind, message: String| QueryError::new(kind, expr, Some(index), None, message);
+
+        if term.field_index as usize != index {
+            return Err(error(
+                QueryErrorKind::OperatorMismatch,
+                "term is part of an `||` chain, which can't be matched by the components tuple"
+                    .to_string(),
+            ));
+        }
+
+        if term.id != expected.id {
+            return Err(error(
+                QueryErrorKind::TypeMismatch,
+                format!(
+                    "expected `{}`, found `{}`",
+                    IdView::new_from(world, expected.id).to_str(),
+                    IdView::new_from(world, term.id).to_str()
+                ),
+            ));
+        }
+
+        let expected_oper = OperKind::from(expected.oper as sys::ecs_oper_kind_t);
+        let oper = OperKind::from(term.oper as sys::ecs_oper_kind_t);
+        if oper != expected_oper {
+            return Err(error(
+                QueryErrorKind::OperatorMismatch,
+                format!(
+                    "expected operator `{:?}`, found `{:?}`",
+                    expected_oper, oper
+                ),
+            ));
+        }
+
+        let expected_inout = InOutKind::from(expected.inout as sys::ecs_inout_kind_t);
+        let inout = InOutKind::from(term.inout as sys::ecs_inout_kind_t);
+        let compatible = match expected_inout {
+            // read only access is satisfied by any term that provides data to read
+            InOutKind::In => matches!(inout, InOutKind::In | InOutKind::InOut | InOutKind::Default),
+            _ => matches!(
+                inout,
+                InOutKind::InOut | InOutKind::Out | InOutKind::Default
+            ),
+        };
+        if !compatible {
+            return Err(error(
+                QueryErrorKind::AccessMismatch,
+                format!(
+                    "access mode `{:?}` is not compatible with `{}`",
+                    inout,
+                    if expected_inout == InOutKind::In {
+                        "&T"
+                    } else {
+                        "&mut T"
+                    }
+                ),
+            ));
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,101 @@
+//! Runtime information about a [`Query`] and its cache.
+
+use crate::core::*;
+use crate::sys;
+
+/// Information about a query and its cache, as returned by [`Query::info()`].
+///
+/// Use this to find out which queries match many tables or use a lot of memory for their cache.
+/// The counts are read from the query cache at the time [`Query::info()`] is called, without
+/// evaluating the query. Queries without a cache report 0 for the counts of matched results,
+/// entities and tables.
+///
+/// # Example
+///
+/// ```
+/// use flecs_ecs::prelude::*;
+///
+/// #[derive(Component)]
+/// struct Position {
+///     x: f32,
+///     y: f32,
+/// }
+///
+/// #[derive(Component)]
+/// struct Npc;
+///
+/// let world = World::new();
+///
+/// world.entity().set(Position { x: 0.0, y: 0.0 });
+/// world.entity().set(Position { x: 0.0, y: 0.0 }).add::<Npc>();
+///
+/// let query = world.query::<&Position>().set_cached().build();
+///
+/// let info = query.info();
+/// assert!(info.is_cached);
+/// assert_eq!(info.table_count, 2);
+/// assert_eq!(info.entity_count, 2);
+/// assert!(info.cache_memory > 0);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QueryInfo {
+    /// The caching policy of the query.
+    pub cache_kind: QueryCacheKind,
+    /// Whether the query has a cache. Queries with [`QueryCacheKind::Auto`] only have a cache
+    /// if at least one of their terms is cacheable.
+    pub is_cached: bool,
+    /// Number of terms of the query.
+    pub term_count: i32,
+    /// Number of fields returned by the query.
+    pub field_count: i32,
+    /// Number of variables of the query, including the builtin `$this` variable.
+    pub var_count: i32,
+    /// Number of times the query has been evaluated.
+    pub eval_count: i32,
+    /// Number of results of the query cache. Only tracked for cached queries.
+    ///
+    /// For queries that also have terms that aren't cached, this counts the results of the cached
+    /// terms, which can be more than the query returns.
+    pub result_count: i32,
+    /// Number of entities in the tables matched by the query cache. Only tracked for cached
+    /// queries, with the same caveat as [`QueryInfo::result_count`].
+    pub entity_count: i32,
+    /// Number of non-empty tables matched by the query. Only tracked for cached queries.
+    pub table_count: i32,
+    /// Number of empty tables matched by the query. Only tracked for cached queries.
+    pub empty_table_count: i32,
+    /// Number of groups of the query, when it uses `group_by`. Only tracked for cached queries.
+    pub group_count: i32,
+    /// Number of times tables have been matched with or unmatched from the cache.
+    pub match_count: i32,
+    /// Number of times the cache has been rematched, e.g. because of a change in a relationship
+    /// the query traverses.
+    pub rematch_count: i32,
+    /// Approximate memory used by the query cache in bytes, `0` for uncached queries.
+    pub cache_memory: usize,
+}
+
+impl QueryInfo {
+    pub(crate) fn new(query: *const sys::ecs_query_t) -> Self {
+        let mut cache: sys::ecs_rust_query_cache_info_t = Default::default();
+        let is_cached = unsafe { sys::ecs_rust_query_cache_info(query, &mut cache) };
+        let query = unsafe { &*query };
+
+        Self {
+            cache_kind: QueryCacheKind::from(query.cache_kind),
+            is_cached,
+            term_count: query.term_count as i32,
+            field_count: query.field_count as i32,
+            var_count: query.var_count as i32,
+            eval_count: query.eval_count,
+            result_count: cache.result_count,
+            entity_count: cache.entity_count,
+            table_count: cache.table_count,
+            empty_table_count: cache.empty_table_count,
+            group_count: cache.group_count,
+            match_count: cache.match_count,
+            rematch_count: cache.rematch_count,
+            cache_memory: cache.memory as usize,
+        }
+    }
+}
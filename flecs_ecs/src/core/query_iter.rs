@@ -63,7 +63,7 @@ where
     ///
     /// # Arguments
     ///
-    /// * `var_id`: the variable id to set
+    /// * `var`: the variable to set, either a [`QueryVar`] or the variable index
     ///
     /// * `value`: the value to set
     ///
@@ -71,7 +71,8 @@ where
     ///
     /// * C++ API: `iter_iterable::set_var`
     #[doc(alias = "iter_iterable::set_var")]
-    pub fn set_var(&mut self, var_id: i32, value: impl Into<Entity>) -> &mut Self {
+    pub fn set_var(&mut self, var: impl QueryVarId, value: impl Into<Entity>) -> &mut Self {
+        let var_id = var.var_index(self.iter.query);
         ecs_assert!(var_id != -1, FlecsErrorCode::InvalidParameter, 0);
         unsafe { sys::ecs_iter_set_var(&mut self.iter, var_id, *value.into()) };
         self
@@ -81,7 +82,7 @@ where
     ///
     /// # Arguments
     ///
-    /// * `var_id`: the variable id to set
+    /// * `var`: the variable to set, either a [`QueryVar`] or the variable index
     ///
     /// * `range`: the range to set
     ///
@@ -89,7 +90,8 @@ where
     ///
     /// * C++ API: `iter_iterable::set_var`
     #[doc(alias = "iter_iterable::set_var")]
-    pub fn set_var_table(&mut self, var_id: i32, table: impl IntoTableRange) -> &mut Self {
+    pub fn set_var_table(&mut self, var: impl QueryVarId, table: impl IntoTableRange) -> &mut Self {
+        let var_id = var.var_index(self.iter.query);
         ecs_assert!(var_id != -1, FlecsErrorCode::InvalidParameter, 0);
         unsafe { sys::ecs_iter_set_var_as_range(&mut self.iter, var_id, &table.range_raw()) };
         self
@@ -77,6 +77,10 @@ pub trait IterableTypeOperation {
     type OnlyType: ComponentOrPairId;
     type OnlyPairType: ComponentId;
     const ONE: i32 = 1;
+    /// Whether the field gives mutable access to the component.
+    const IS_MUT: bool = false;
+    /// Whether the field is wrapped in [`Changed`].
+    const IS_CHANGED: bool = false;
 
     fn populate_term(term: &mut sys::ecs_term_t);
     fn create_tuple_data<'a>(array_components_data: *mut u8, index: usize) -> Self::ActualType<'a>;
@@ -163,6 +167,7 @@ where
     type SliceType<'w> = &'w mut [<T as ComponentOrPairId>::CastType];
     type OnlyType = T;
     type OnlyPairType = <T as ComponentOrPairId>::CastType;
+    const IS_MUT: bool = true;
 
     fn populate_term(term: &mut sys::ecs_term_t) {
         term.inout = InOutKind::InOut as i16;
@@ -288,6 +293,7 @@ where
     type SliceType<'w> = Option<&'w mut [<T as ComponentOrPairId>::CastType]>;
     type OnlyType = T;
     type OnlyPairType = <T as ComponentOrPairId>::CastType;
+    const IS_MUT: bool = true;
 
     fn populate_term(term: &mut sys::ecs_term_t) {
         term.inout = InOutKind::InOut as i16;
@@ -346,11 +352,170 @@ where
     }
 }
 
+/// Query term wrapper that only matches tables in which the wrapped component may have changed
+/// since the last time the query iterated them.
+///
+/// Tables are skipped when any of the `Changed` fields did not change, so `(Changed<&A>, Changed<&B>)`
+/// only visits tables in which both `A` and `B` changed. A change is either a table that a query
+/// or system with an `&mut` field for the component visited, a [`EntityView::modified()`] or set
+/// operation, or entities being added to or removed from the table.
+///
+/// Change detection is per table and component, not per value: writes are not tracked. Every
+/// table that is visited is marked as changed for all `&mut` fields of the query, also if the
+/// callback didn't modify the component, and a `Changed` field reports the whole table as changed
+/// if a single entity changed. Use `&T` for components that are only read, so that they don't
+/// trigger a change. Skipped tables are not marked as changed, also not for the other `&mut`
+/// fields of the query.
+/// Change detection requires a cached query, which is why queries with `Changed` terms are cached
+/// unless a different cache kind is set explicitly.
+///
+/// The wrapper is only evaluated by the typed iteration functions (`each`, `each_entity`, `each_iter`,
+/// `run_iter` and `find`) and systems using them, [`TableIter::is_field_changed()`] can be
+/// used to do the same in a `run` callback.
+///
+/// # Example
+///
+/// ```
+/// use flecs_ecs::prelude::*;
+///
+/// #[derive(Component)]
+/// struct Transform {
+///     x: f32,
+/// }
+///
+/// #[derive(Component)]
+/// struct Player;
+///
+/// let world = World::new();
+///
+/// let e1 = world.entity().set(Transform { x: 0.0 });
+/// world.entity().set(Transform { x: 0.0 }).add::<Player>();
+///
+/// let replicate = world.query::<Changed<&Transform>>().build();
+///
+/// // the first iteration sees everything as changed
+/// let mut count = 0;
+/// replicate.each(|_| count += 1);
+/// assert_eq!(count, 2);
+///
+/// // nothing changed since the last iteration
+/// count = 0;
+/// replicate.each(|_| count += 1);
+/// assert_eq!(count, 0);
+///
+/// e1.get::<&mut Transform>(|t| t.x = 1.0);
+/// e1.modified::<Transform>();
+///
+/// // change detection is per table, only the table of `e1` is visited
+/// replicate.each(|_| count += 1);
+/// assert_eq!(count, 1);
+/// ```
+pub struct Changed<T>(PhantomData<T>);
+
+impl<T> IterableTypeOperation for Changed<T>
+where
+    T: IterableTypeOperation,
+{
+    type CastType = T::CastType;
+    type ActualType<'w> = T::ActualType<'w>;
+    type SliceType<'w> = T::SliceType<'w>;
+    type OnlyType = T::OnlyType;
+    type OnlyPairType = T::OnlyPairType;
+    const IS_MUT: bool = T::IS_MUT;
+    const IS_CHANGED: bool = true;
+
+    fn populate_term(term: &mut sys::ecs_term_t) {
+        T::populate_term(term);
+    }
+
+    fn create_tuple_data<'a>(array_components_data: *mut u8, index: usize) -> Self::ActualType<'a> {
+        T::create_tuple_data(array_components_data, index)
+    }
+
+    fn create_tuple_with_ref_data<'a>(
+        array_components_data: *mut u8,
+        is_ref: bool,
+        index: usize,
+    ) -> Self::ActualType<'a> {
+        T::create_tuple_with_ref_data(array_components_data, is_ref, index)
+    }
+
+    fn create_tuple_slice_data<'a>(
+        array_components_data: *mut u8,
+        count: usize,
+    ) -> Self::SliceType<'a> {
+        T::create_tuple_slice_data(array_components_data, count)
+    }
+
+    fn create_tuple_slices_with_ref_data<'a>(
+        array_components_data: *mut u8,
+        is_ref_array_components: bool,
+        count: usize,
+    ) -> Self::SliceType<'a> {
+        T::create_tuple_slices_with_ref_data(array_components_data, is_ref_array_components, count)
+    }
+}
+
+/// Returns a bitmask with a bit set for every field for which `fields` is true.
+const fn fields_mask(fields: &[bool]) -> u32 {
+    let mut mask = 0;
+    let mut index = 0;
+    while index < fields.len() {
+        if fields[index] {
+            mask |= 1 << index;
+        }
+        index += 1;
+    }
+    mask
+}
+
+/// Checks the [`Changed`] fields of `T` for the current result of `iter`.
+///
+/// Returns false and skips the result when one of the fields did not change, which
+/// also prevents the fields of the table from being marked as written.
+#[inline(always)]
+pub(crate) fn iter_fields_changed<T: QueryTuple>(iter: &mut sys::ecs_iter_t) -> bool {
+    if T::CHANGED_FIELDS == 0 {
+        return true;
+    }
+
+    for index in 0..T::COUNT {
+        if T::CHANGED_FIELDS & (1 << index) != 0
+            && !unsafe { sys::ecs_rust_iter_field_changed(iter, index) }
+        {
+            unsafe { sys::ecs_iter_skip(iter) };
+            return false;
+        }
+    }
+    true
+}
+
+/// Marks the fields that `T` gives mutable access to as written for the current result of `iter`,
+/// regardless of whether the callback modified them.
+///
+/// Queries created from expressions can have terms with a default access mode, which flecs
+/// treats as written even if `T` only reads them. Fields that are not part of `T` keep their access mode.
+#[inline(always)]
+pub(crate) fn iter_mark_written_fields<T: QueryTuple>(iter: &mut sys::ecs_iter_t) {
+    if T::READONLY_FIELDS == 0
+        || iter.query.is_null()
+        || unsafe { (*iter.query).write_fields } & T::READONLY_FIELDS == 0
+    {
+        return;
+    }
+
+    unsafe { sys::ecs_rust_iter_mark_written(iter, !T::READONLY_FIELDS) };
+}
+
 pub trait QueryTuple: Sized {
     type Pointers: ComponentPointers<Self>;
     type TupleType<'a>;
     type TupleSliceType<'a>;
     const COUNT: i32;
+    /// Bitmask of the fields that are wrapped in [`Changed`].
+    const CHANGED_FIELDS: u32;
+    /// Bitmask of the fields that are only read.
+    const READONLY_FIELDS: u32;
 
     fn create_ptrs(iter: &sys::ecs_iter_t) -> Self::Pointers {
         Self::Pointers::new(iter)
@@ -404,6 +569,8 @@ where
     type TupleType<'w> = A::ActualType<'w>;
     type TupleSliceType<'w> = A::SliceType<'w>;
     const COUNT : i32 = 1;
+    const CHANGED_FIELDS : u32 = A::IS_CHANGED as u32;
+    const READONLY_FIELDS : u32 = !A::IS_MUT as u32;
 
     fn populate<'a>(query: &mut impl QueryBuilderImpl<'a>) {
         let id = <A::OnlyType as ComponentOrPairId>::get_id(query.world());
@@ -640,6 +807,8 @@ macro_rules! impl_iterable {
             )*);
             type Pointers = ComponentsData<Self, { tuple_count!($($t),*) }>;
             const COUNT : i32 = tuple_count!($($t),*);
+            const CHANGED_FIELDS : u32 = fields_mask(&[$($t::IS_CHANGED),*]);
+            const READONLY_FIELDS : u32 = fields_mask(&[$(!$t::IS_MUT),*]);
 
             fn populate<'a>(query: &mut impl QueryBuilderImpl<'a>) {
                 let _world = query.world();
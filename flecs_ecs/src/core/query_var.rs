@@ -0,0 +1,154 @@
+//! Typed handles to the variables of a [`Query`].
+
+use std::ffi::CStr;
+
+use crate::core::*;
+use crate::sys;
+
+/// Handle to a variable of a query, such as `$planet` in `(DockedTo, $planet)`.
+///
+/// A `QueryVar` is obtained with [`QueryAPI::var()`] and can be used to set the variable on a
+/// [`QueryIter`] or to read its value from a [`TableIter`]. Unlike raw variable indices, a handle
+/// remembers which query it belongs to, using it with another query is caught by an assert.
+/// Queries are identified by their address and a generation that is assigned when the query is
+/// created, so a handle also doesn't match a new query that is created at the address of a
+/// deleted query.
+///
+/// # Example
+///
+/// ```
+/// use flecs_ecs::prelude::*;
+///
+/// #[derive(Component)]
+/// struct DockedTo;
+///
+/// let world = World::new();
+///
+/// let earth = world.entity_named("Earth");
+/// let mars = world.entity_named("Mars");
+///
+/// let ship = world.entity().add_first::<DockedTo>(earth);
+/// world.entity().add_first::<DockedTo>(mars);
+///
+/// let mut query = world
+///     .query::<()>()
+///     .with_first_name::<DockedTo>("$planet")
+///     .build();
+///
+/// let planet = query.var("planet");
+///
+/// let mut count = 0;
+/// query.set_var(planet, earth).each_iter(|it, index, _| {
+///     assert_eq!(it.entity(index), ship);
+///     assert_eq!(it.get_var(planet), earth);
+///     count += 1;
+/// });
+///
+/// assert_eq!(count, 1);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct QueryVar {
+    index: i32,
+    // only used to check that the handle is used with the query it was created from.
+    query: usize,
+    generation: u64,
+}
+
+/// Assign a generation to a query that is created by the Rust API, which identifies the query in
+/// [`QueryVar`] handles together with its address. A query that is created at the address of a
+/// deleted query gets a new generation.
+pub(crate) fn register_query(query: *const sys::ecs_query_t) {
+    let world = unsafe { WorldRef::from_ptr((*query).real_world) };
+    let world_ctx = world.world_ctx_mut();
+    world_ctx.next_query_generation += 1;
+    let generation = world_ctx.next_query_generation;
+    world_ctx
+        .query_generations
+        .insert(query as usize, generation);
+}
+
+/// Returns the generation of a query, 0 for queries that weren't created by the Rust API.
+fn query_generation(query: *const sys::ecs_query_t) -> u64 {
+    if query.is_null() {
+        return 0;
+    }
+    let world = unsafe { WorldRef::from_ptr((*query).real_world) };
+    world
+        .world_ctx()
+        .query_generations
+        .get(&(query as usize))
+        .copied()
+        .unwrap_or(0)
+}
+
+impl QueryVar {
+    pub(crate) fn new(query: *const sys::ecs_query_t, index: i32) -> Self {
+        Self {
+            index,
+            query: query as usize,
+            generation: query_generation(query),
+        }
+    }
+
+    /// Returns the index of the variable in the query.
+    pub fn index(self) -> i32 {
+        self.index
+    }
+}
+
+impl From<QueryVar> for i32 {
+    fn from(var: QueryVar) -> Self {
+        var.index
+    }
+}
+
+/// Types that can identify a query variable: a raw variable index or a [`QueryVar`] handle.
+#[doc(hidden)]
+pub trait QueryVarId: Copy {
+    fn var_index(self, query: *const sys::ecs_query_t) -> i32;
+}
+
+impl QueryVarId for i32 {
+    #[inline(always)]
+    fn var_index(self, _query: *const sys::ecs_query_t) -> i32 {
+        self
+    }
+}
+
+impl QueryVarId for QueryVar {
+    #[inline(always)]
+    fn var_index(self, query: *const sys::ecs_query_t) -> i32 {
+        ecs_assert!(
+            self.query == query as usize && self.generation == query_generation(query),
+            FlecsErrorCode::InvalidParameter,
+            "query variable is used with a different query than the one it was created from"
+        );
+        self.index
+    }
+}
+
+/// The state of a query variable for the current iterator result, as returned by [`TableIter::vars()`].
+#[derive(Debug, Clone, Copy)]
+pub struct QueryVarValue<'a> {
+    /// Handle to the variable.
+    pub var: QueryVar,
+    /// The name of the variable, `this` for the builtin `$this` variable.
+    pub name: &'a str,
+    /// The entity the variable is set to, if it resolves to a single entity.
+    pub entity: Option<EntityView<'a>>,
+    /// Whether the variable was constrained (set) before iteration started, e.g. with [`QueryIter::set_var()`].
+    pub is_constrained: bool,
+}
+
+pub(crate) fn var_name<'a>(query: *const sys::ecs_query_t, index: i32) -> &'a str {
+    if query.is_null() {
+        return "";
+    }
+
+    let name = unsafe { sys::ecs_query_var_name(query, index) };
+    if name.is_null() {
+        ""
+    } else {
+        unsafe { CStr::from_ptr(name).to_str().unwrap_or("") }
+    }
+}
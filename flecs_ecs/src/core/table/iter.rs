@@ -2,6 +2,7 @@
 use std::marker::PhantomData;
 use std::{ffi::CStr, os::raw::c_void, ptr::NonNull};
 
+use crate::core::callback_panic::resume_callback_panic;
 use crate::core::*;
 use crate::sys;
 
@@ -99,6 +100,20 @@ where
         EntityView::new_from(self.world(), self.iter.event)
     }
 
+    /// Stop the propagation of the event that is being handled.
+    ///
+    /// The event is not emitted for the remaining ancestors of the entity. This has no effect
+    /// on observers that are notified for the current entity, and on events that don't propagate.
+    /// Events that are emitted by an observer propagate independently of the event that is
+    /// handled by the observer.
+    ///
+    /// # See also
+    ///
+    /// * [`EventBuilder::propagate()`]
+    pub fn stop_propagation(&self) {
+        crate::core::event::stop_propagation(self.world(), self.iter.event);
+    }
+
     /// Wrap the event id in the iterator in an [`IdView`] object.
     ///
     /// # See also
@@ -203,13 +218,14 @@ where
     ///
     /// # Arguments
     ///
-    /// * `var_id` - The variable id
+    /// * `var` - The variable, either a [`QueryVar`] or the variable index
     ///
     /// # See also
     ///
     /// * C++ API: `iter::get_var`
     #[doc(alias = "iter::get_var")]
-    pub fn get_var(&self, var_id: i32) -> EntityView<'a> {
+    pub fn get_var(&self, var: impl QueryVarId) -> EntityView<'a> {
+        let var_id = var.var_index(self.iter.query);
         ecs_assert!(var_id != -1, FlecsErrorCode::InvalidParameter, 0);
         let var =
             unsafe { sys::ecs_iter_get_var(self.iter as *const _ as *mut sys::ecs_iter_t, var_id) };
@@ -243,18 +259,98 @@ where
         })
     }
 
-    /// Access ctx.
-    /// ctx contains the context pointer assigned to a system
+    /// Returns whether the variable was constrained (set) before iteration started.
+    ///
+    /// # Arguments
+    ///
+    /// * `var` - The variable, either a [`QueryVar`] or the variable index
+    pub fn is_var_constrained(&self, var: impl QueryVarId) -> bool {
+        let var_id = var.var_index(self.iter.query);
+        unsafe {
+            sys::ecs_iter_var_is_constrained(self.iter as *const _ as *mut sys::ecs_iter_t, var_id)
+        }
+    }
+
+    /// Iterate over all variables of the iterator, including the builtin `$this` variable.
+    ///
+    /// # Returns
+    ///
+    /// An iterator of [`QueryVarValue`], which contains the handle, name, value
+    /// and whether the variable was constrained before iteration started.
     ///
     /// # See also
     ///
+    /// * [`QueryAPI::vars()`]
+    pub fn vars(&self) -> impl Iterator<Item = QueryVarValue<'a>> + '_ {
+        let world = self.real_world();
+        (0..self.iter.variable_count).map(move |index| {
+            let iter = self.iter as *const _ as *mut sys::ecs_iter_t;
+            let entity = unsafe { sys::ecs_iter_get_var(iter, index) };
+            QueryVarValue {
+                var: QueryVar::new(self.iter.query, index),
+                name: var_name(self.iter.query, index),
+                entity: (entity != 0).then(|| EntityView::new_from(world, entity)),
+                is_constrained: unsafe { sys::ecs_iter_var_is_constrained(iter, index) },
+            }
+        })
+    }
+
+    /// Access the context of the system or observer that is being run.
+    ///
+    /// # Returns
+    ///
+    /// The context set with `set_typed_context`, or `None` if no context is set, if the iterator
+    /// doesn't belong to a system or observer, or if the context is not of type `C`.
+    ///
+    /// # See also
+    ///
+    /// * [`TableIter::context_mut()`]
+    /// * [`TableIter::context_ptr()`]
     /// * C++ API: `iter::ctx`
+    #[doc(alias = "iter::ctx")]
+    pub fn context<C: 'static>(&self) -> Option<&'a C> {
+        let ctx_free = self.context_free()?;
+        context_ref(self.iter.ctx, ctx_free)
+    }
+
+    /// Mutably access the context of the system or observer that is being run.
+    ///
+    /// # Returns
+    ///
+    /// The context set with `set_typed_context`, or `None` if no context is set, if the iterator
+    /// doesn't belong to a system or observer, or if the context is not of type `C`.
     ///
     /// # Safety
-    /// - caller must ensure the ctx variable was set to a type accessible as C and is not aliased
+    ///
+    /// The context is shared by every run of the system or observer. The caller must ensure it
+    /// isn't accessed elsewhere while the returned reference is alive, e.g. by a multi-threaded
+    /// system running on another stage or by a nested run of the same observer.
+    ///
+    /// # See also
+    ///
+    /// * [`TableIter::context()`]
+    /// * C++ API: `iter::ctx`
     #[doc(alias = "iter::ctx")]
-    pub unsafe fn context<T>(&mut self) -> &'a mut T {
-        unsafe { &mut *(self.iter.ctx as *mut T) }
+    pub unsafe fn context_mut<C: 'static>(&mut self) -> Option<&'a mut C> {
+        let ctx_free = self.context_free()?;
+        unsafe { context_mut(self.iter.ctx, ctx_free) }
+    }
+
+    fn context_free(&self) -> Option<sys::ecs_ctx_free_t> {
+        if self.iter.ctx.is_null() || self.iter.system == 0 {
+            return None;
+        }
+
+        let world = self.iter.real_world;
+        unsafe {
+            let system = sys::ecs_system_get(world, self.iter.system);
+            if !system.is_null() {
+                Some((*system).ctx_free)
+            } else {
+                let observer = sys::ecs_observer_get(world, self.iter.system);
+                (!observer.is_null()).then(|| (*observer).ctx_free)
+            }
+        }
     }
 
     /// Access ctx.
@@ -731,6 +827,39 @@ where
         unsafe { sys::ecs_iter_changed(self.iter) }
     }
 
+    /// Check if the data of a single field has changed since the last time the
+    /// current table was iterated by this query.
+    ///
+    /// Unlike [`TableIter::is_changed()`], which reports a change if any of the
+    /// fields changed, this only looks at the field with the specified index.
+    /// Adding entities to or removing entities from the table counts as a change of every field.
+    /// Writes are not tracked, a query or system with mutable access to the component that
+    /// visited the table counts as a change, see [`Changed`].
+    ///
+    /// Change detection requires a cached query, for uncached queries and for
+    /// observers this always returns true.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The field index.
+    ///
+    /// # Returns
+    ///
+    /// Returns true if the field has changed.
+    ///
+    /// # See also
+    ///
+    /// * [`Changed`]
+    /// * [`TableIter::is_changed()`]
+    pub fn is_field_changed(&mut self, index: i32) -> bool {
+        ecs_assert!(
+            index >= 0 && index < self.iter.field_count,
+            FlecsErrorCode::InvalidParameter,
+            "field index out of range"
+        );
+        unsafe { sys::ecs_rust_iter_field_changed(self.iter, index) }
+    }
+
     /// Skip current table.
     /// This indicates to the query that the data in the current table is not
     /// modified. By default, iterating a table with a query will mark the
@@ -817,6 +946,7 @@ where
             unsafe {
                 each(self.iter);
             }
+            resume_callback_panic(self.iter.world);
         }
     }
 }
@@ -855,6 +985,10 @@ where
         };
 
         self.iter.flags |= sys::EcsIterIsValid;
+        #[cfg(feature = "flecs_pipeline")]
+        if result {
+            crate::addons::pipeline::count_profiled_entities(self.iter);
+        }
         if result && !self.iter.table.is_null() {
             unsafe {
                 sys::ecs_table_lock(self.iter.world, self.iter.table);
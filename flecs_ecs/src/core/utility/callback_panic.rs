@@ -0,0 +1,159 @@
+//! Panic safety for Rust callbacks that are invoked from C.
+//!
+//! A panic that unwinds out of an `extern "C"` function aborts the process. The trampolines that
+//! invoke closures from C therefore catch panics with [`catch_callback_panic`], which stores the
+//! payload in the world. While a panic is pending, all callbacks of the world are skipped, so that
+//! C finishes the current operation normally (tables are unlocked, deferred mode is ended, ...).
+//! The panic is resumed with [`resume_callback_panic`] once control returns to Rust.
+//!
+//! Component lifecycle callbacks (constructors, destructors, copy and move) are not covered, as
+//! C can't recover from a component that was only partially constructed or moved. The closures
+//! of [`LifecycleBuilder`](crate::core::LifecycleBuilder) abort the process with a message that
+//! names the component instead, see [`abort_on_callback_panic`]. The lifecycle callbacks that
+//! are derived from `Default`, `Drop` and `Clone` abort like any panic that unwinds out of an
+//! `extern "C"` function.
+
+use std::any::Any;
+use std::cell::RefCell;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use crate::core::*;
+use crate::sys;
+
+type PanicPayload = Box<dyn Any + Send>;
+
+thread_local! {
+    /// Panic of a callback that isn't associated with a world, such as an `order_by` comparator.
+    static THREAD_PANIC: RefCell<Option<PanicPayload>> = const { RefCell::new(None) };
+}
+
+/// The panic of a callback that hasn't been resumed yet.
+#[derive(Default)]
+pub(crate) struct CallbackPanic {
+    pending: AtomicBool,
+    payload: Mutex<Option<PanicPayload>>,
+}
+
+impl CallbackPanic {
+    fn is_pending(&self) -> bool {
+        self.pending.load(Ordering::Acquire)
+    }
+
+    fn store(&self, payload: PanicPayload) {
+        let mut slot = self.payload.lock().unwrap_or_else(|e| e.into_inner());
+        // keep the first panic, later ones are usually a consequence of it
+        if slot.is_none() {
+            *slot = Some(payload);
+        }
+        self.pending.store(true, Ordering::Release);
+    }
+
+    fn take(&self) -> Option<PanicPayload> {
+        if !self.is_pending() {
+            return None;
+        }
+        let mut slot = self.payload.lock().unwrap_or_else(|e| e.into_inner());
+        self.pending.store(false, Ordering::Release);
+        slot.take()
+    }
+}
+
+fn callback_panic<'a>(world: *const sys::ecs_world_t) -> Option<&'a CallbackPanic> {
+    if world.is_null() {
+        return None;
+    }
+    let ctx = unsafe { sys::ecs_get_binding_ctx(world) } as *const WorldCtx;
+    if ctx.is_null() {
+        return None;
+    }
+    Some(unsafe { &(*ctx).callback_panic })
+}
+
+/// Runs a callback that is invoked from C, catching a panic instead of unwinding into C.
+///
+/// Returns `None` if the callback panicked, or if it was skipped because a panic of an earlier
+/// callback is still pending. `world` may be null for callbacks that aren't associated with a
+/// world, in which case the panic is stored for the current thread.
+pub(crate) fn catch_callback_panic<R>(
+    world: *const sys::ecs_world_t,
+    callback: impl FnOnce() -> R,
+) -> Option<R> {
+    let slot = callback_panic(world);
+    let pending = match slot {
+        Some(slot) => slot.is_pending(),
+        None => THREAD_PANIC.with(|p| p.borrow().is_some()),
+    };
+    if pending {
+        return None;
+    }
+
+    match panic::catch_unwind(AssertUnwindSafe(callback)) {
+        Ok(result) => Some(result),
+        Err(payload) => {
+            match slot {
+                Some(slot) => slot.store(payload),
+                None => THREAD_PANIC.with(|p| {
+                    p.borrow_mut().get_or_insert(payload);
+                }),
+            }
+            None
+        }
+    }
+}
+
+/// Runs a component lifecycle callback that is invoked from C, aborting the process if it
+/// panics.
+///
+/// # Arguments
+///
+/// * `action` - The lifecycle action, e.g. "constructor", which is named in the message.
+pub(crate) fn abort_on_callback_panic<T, R>(action: &str, callback: impl FnOnce() -> R) -> R {
+    match panic::catch_unwind(AssertUnwindSafe(callback)) {
+        Ok(result) => result,
+        Err(_) => {
+            ecs_abort!(
+                FlecsErrorCode::InvalidOperation,
+                "the {action} of component '{}' panicked, the component storage can't be recovered",
+                std::any::type_name::<T>()
+            );
+        }
+    }
+}
+
+/// Resumes the panic of a callback that was caught by [`catch_callback_panic`], if any.
+///
+/// Called after operations that can invoke callbacks, once control has returned from C. Does
+/// nothing if the thread is already unwinding, as a second panic would abort.
+pub(crate) fn resume_callback_panic(world: *const sys::ecs_world_t) {
+    if std::thread::panicking() {
+        return;
+    }
+    let payload = callback_panic(world)
+        .and_then(CallbackPanic::take)
+        .or_else(|| THREAD_PANIC.with(|p| p.borrow_mut().take()));
+    if let Some(payload) = payload {
+        panic::resume_unwind(payload);
+    }
+}
+
+/// Keeps a table locked while a callback iterates it, unlocking it again when the callback
+/// returns or panics.
+pub(crate) struct TableLock {
+    world: *mut sys::ecs_world_t,
+    table: *mut sys::ecs_table_t,
+}
+
+impl TableLock {
+    pub(crate) unsafe fn new(world: *mut sys::ecs_world_t, table: *mut sys::ecs_table_t) -> Self {
+        unsafe { sys::ecs_table_lock(world, table) };
+        Self { world, table }
+    }
+}
+
+impl Drop for TableLock {
+    fn drop(&mut self) {
+        unsafe { sys::ecs_table_unlock(self.world, self.table) };
+    }
+}
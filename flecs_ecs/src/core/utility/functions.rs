@@ -1,8 +1,12 @@
 #![doc(hidden)]
 //! (internal) utility functions for dealing with ECS identifiers. This module is mostly used internally by the library.
 //! but can be used by the user if needed.
-use std::{ffi::CString, os::raw::c_char};
+use std::{
+    ffi::{c_void, CString},
+    os::raw::c_char,
+};
 
+use crate::core::callback_panic::resume_callback_panic;
 use crate::core::*;
 use crate::sys;
 
@@ -101,6 +105,7 @@ pub fn ecs_add_pair(
             ecs_pair(*first.into(), *second.into()),
         );
     };
+    resume_callback_panic(world);
 }
 
 /// Get the first entity from a pair.
@@ -247,8 +252,20 @@ pub(crate) fn set_helper<T: ComponentId>(
     };
 
     let mut is_new = false;
+    let notify_replace = has_replace_listeners(world, id);
     unsafe {
         if sys::ecs_is_deferred(world) {
+            // when deferred, a component the entity already owns is overwritten in place right
+            // away, so the hooks see the stored value. Components that are added by the command
+            // are only written to a staged value, and don't replace anything.
+            let old = if notify_replace {
+                sys::ecs_get_mut_id(world, entity, id) as *const T
+            } else {
+                std::ptr::null()
+            };
+            if !old.is_null() {
+                notify_replace_helper(world, entity, id, old, &value);
+            }
             if T::NEEDS_DROP {
                 if T::IMPLS_DEFAULT {
                     //use set batching //faster performance, no panic possible
@@ -264,18 +281,17 @@ pub(crate) fn set_helper<T: ComponentId>(
                         //SAFETY: ecs_ensure_modified_id will default initialize the component
                         std::ptr::drop_in_place(comp);
                         std::ptr::write(comp, value);
-                        return;
+                    } else {
+                        // if does not impl default or not have the id
+                        // use insert //slower performance
+                        let ptr = sys::ecs_emplace_id(world, entity, id, &mut is_new) as *mut T;
+
+                        if !is_new {
+                            std::ptr::drop_in_place(ptr);
+                        }
+                        std::ptr::write(ptr, value);
+                        sys::ecs_modified_id(world, entity, id);
                     }
-
-                    // if does not impl default or not have the id
-                    // use insert //slower performance
-                    let ptr = sys::ecs_emplace_id(world, entity, id, &mut is_new) as *mut T;
-
-                    if !is_new {
-                        std::ptr::drop_in_place(ptr);
-                    }
-                    std::ptr::write(ptr, value);
-                    sys::ecs_modified_id(world, entity, id);
                 }
             } else {
                 //if not needs drop, use set batching, faster performance
@@ -289,12 +305,80 @@ pub(crate) fn set_helper<T: ComponentId>(
             let ptr = sys::ecs_emplace_id(world, entity, id, &mut is_new) as *mut T;
 
             if !is_new {
+                if notify_replace {
+                    notify_replace_helper(world, entity, id, ptr, &value);
+                }
                 std::ptr::drop_in_place(ptr);
             }
             std::ptr::write(ptr, value);
             sys::ecs_modified_id(world, entity, id);
         }
     }
+    resume_callback_panic(world);
+}
+
+/// Returns whether component `id` has an `on_replace` hook or [`flecs::OnReplace`] observer,
+/// which allows `set` to skip reading the old value when it doesn't.
+fn has_replace_listeners(world: *mut sys::ecs_world_t, id: u64) -> bool {
+    let world_ref = unsafe { WorldRef::from_ptr(world) };
+    has_replace_hooks(world, id)
+        || (flecs::OnReplace::is_registered_with_world(world_ref)
+            && unsafe { sys::ecs_rust_has_observers(world, flecs::OnReplace::id(world_ref), id) })
+}
+
+/// Returns whether component `id` has an `on_replace` hook.
+fn has_replace_hooks(world: *mut sys::ecs_world_t, id: u64) -> bool {
+    let hooks = unsafe { sys::ecs_get_hooks_id(world, id) };
+    if hooks.is_null() || unsafe { (*hooks).binding_ctx }.is_null() {
+        return false;
+    }
+    unsafe { (*((*hooks).binding_ctx as *const ComponentBindingCtx)).has_replace_hooks() }
+}
+
+/// Invokes the `on_replace` hook and [`flecs::OnReplace`] observers for a component value that
+/// is about to be overwritten.
+///
+/// # Safety
+///
+/// `old` must point to the current value of component `id` of `entity`.
+unsafe fn notify_replace_helper<T: ComponentId>(
+    world: *mut sys::ecs_world_t,
+    entity: u64,
+    id: u64,
+    old: *const T,
+    new: &T,
+) {
+    let world_ref = unsafe { WorldRef::from_ptr(world) };
+
+    if has_replace_hooks(world, id) {
+        let hooks = unsafe { sys::ecs_get_hooks_id(world, id) };
+        unsafe {
+            ComponentBindingCtx::run_replace_hooks(
+                (*hooks).binding_ctx as *mut ComponentBindingCtx,
+                EntityView::new_from(world_ref, entity),
+                old as *const c_void,
+                new as *const T as *const c_void,
+            );
+        }
+    }
+
+    if flecs::OnReplace::is_registered_with_world(world_ref)
+        && unsafe { sys::ecs_rust_has_observers(world, flecs::OnReplace::id(world_ref), id) }
+    {
+        let mut ids = sys::ecs_type_t {
+            array: &id as *const u64 as *mut u64,
+            count: 1,
+        };
+        let mut desc = sys::ecs_event_desc_t {
+            event: flecs::OnReplace::id(world_ref),
+            ids: &mut ids,
+            entity,
+            const_param: new as *const T as *const c_void,
+            observable: world_ref.real_world().world_ptr_mut() as *mut c_void,
+            ..Default::default()
+        };
+        unsafe { sys::ecs_emit(world, &mut desc) };
+    }
 }
 
 /// Remove generation from entity id.
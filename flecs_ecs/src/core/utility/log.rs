@@ -53,3 +53,11 @@ pub fn enable_timedelta_logging(enabled: bool) {
         sys::ecs_log_enable_timedelta(enabled);
     }
 }
+
+/// Logs an error with the flecs logging API.
+pub(crate) fn log_error(msg: &str) {
+    let msg = std::ffi::CString::new(msg.replace('\0', "")).unwrap_or_default();
+    unsafe {
+        sys::ecs_log_(-3, std::ptr::null(), 0, c"%s".as_ptr(), msg.as_ptr());
+    }
+}
@@ -1,6 +1,7 @@
 //! contains traits that define what a component is and also the API's for [`Query`][super::Query], [`Observer`][super::Observer] and [`System`][crate::addons::system::System].
 //! Also contains lower level utility functions on ECS IDs. This is mostly used internally by the library.
 
+pub(crate) mod callback_panic;
 mod errors;
 mod functions;
 mod log;
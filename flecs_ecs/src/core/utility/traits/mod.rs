@@ -20,6 +20,7 @@ use crate::core::{ImplementsClone, ImplementsDefault};
 
 #[doc(hidden)]
 pub mod private {
+    use crate::core::callback_panic::{catch_callback_panic, TableLock};
     use crate::core::*;
     use crate::sys;
     use std::ffi::c_void;
@@ -49,6 +50,8 @@ pub mod private {
 
         fn desc_binding_context(&self) -> *mut c_void;
 
+        fn desc_context(&mut self) -> (&mut *mut c_void, &mut sys::ecs_ctx_free_t);
+
         fn set_desc_callback(
             &mut self,
             callback: Option<unsafe extern "C" fn(*mut sys::ecs_iter_t)>,
@@ -71,31 +74,35 @@ pub mod private {
             Func: FnMut(T::TupleType<'_>),
         {
             let iter = unsafe { &mut *iter };
-            iter.flags |= sys::EcsIterCppEach;
+            catch_callback_panic(iter.world, || {
+                iter.flags |= sys::EcsIterCppEach;
 
-            let each = &mut *(iter.callback_ctx as *mut Func);
+                let each = &mut *(iter.callback_ctx as *mut Func);
 
-            let mut components_data = T::create_ptrs(&*iter);
-            let iter_count = {
-                if iter.count == 0 && iter.table.is_null() {
-                    1_usize
-                } else {
-                    iter.count as usize
+                if !iter_fields_changed::<T>(iter) {
+                    return;
                 }
-            };
 
-            if !CALLED_FROM_RUN {
-                sys::ecs_table_lock(iter.world, iter.table);
-            }
+                let mut components_data = T::create_ptrs(&*iter);
+                let iter_count = {
+                    if iter.count == 0 && iter.table.is_null() {
+                        1_usize
+                    } else {
+                        iter.count as usize
+                    }
+                };
 
-            for i in 0..iter_count {
-                let tuple = components_data.get_tuple(i);
-                each(tuple);
-            }
+                let lock = (!CALLED_FROM_RUN).then(|| TableLock::new(iter.world, iter.table));
 
-            if !CALLED_FROM_RUN {
-                sys::ecs_table_unlock(iter.world, iter.table);
-            }
+                for i in 0..iter_count {
+                    let tuple = components_data.get_tuple(i);
+                    each(tuple);
+                }
+
+                drop(lock);
+
+                iter_mark_written_fields::<T>(iter);
+            });
         }
 
         /// Callback of the `each_entity` functionality
@@ -114,42 +121,46 @@ pub mod private {
             Func: FnMut(EntityView, T::TupleType<'_>),
         {
             let iter = unsafe { &mut *iter };
-            iter.flags |= sys::EcsIterCppEach;
-
-            let each_entity = &mut *(iter.callback_ctx as *mut Func);
-
-            let mut components_data = T::create_ptrs(&*iter);
-            let iter_count = {
-                if iter.count == 0 && iter.table.is_null() {
-                    // If query has no This terms, count can be 0. Since each does not
-                    // have an entity parameter, just pass through components
-                    1_usize
-                } else {
-                    iter.count as usize
-                }
-            };
+            catch_callback_panic(iter.world, || {
+                iter.flags |= sys::EcsIterCppEach;
 
-            ecs_assert!(
-                iter.count > 0,
-                FlecsErrorCode::InvalidOperation,
-                "no entities returned, use each() without flecs::entity argument",
-            );
+                let each_entity = &mut *(iter.callback_ctx as *mut Func);
 
-            if !CALLED_FROM_RUN {
-                sys::ecs_table_lock(iter.world, iter.table);
-            }
+                if !iter_fields_changed::<T>(iter) {
+                    return;
+                }
 
-            for i in 0..iter_count {
-                let world = WorldRef::from_ptr(iter.world);
-                let entity = EntityView::new_from(world, *iter.entities.add(i));
-                let tuple = components_data.get_tuple(i);
+                let mut components_data = T::create_ptrs(&*iter);
+                let iter_count = {
+                    if iter.count == 0 && iter.table.is_null() {
+                        // If query has no This terms, count can be 0. Since each does not
+                        // have an entity parameter, just pass through components
+                        1_usize
+                    } else {
+                        iter.count as usize
+                    }
+                };
+
+                ecs_assert!(
+                    iter.count > 0,
+                    FlecsErrorCode::InvalidOperation,
+                    "no entities returned, use each() without flecs::entity argument",
+                );
+
+                let lock = (!CALLED_FROM_RUN).then(|| TableLock::new(iter.world, iter.table));
+
+                for i in 0..iter_count {
+                    let world = WorldRef::from_ptr(iter.world);
+                    let entity = EntityView::new_from(world, *iter.entities.add(i));
+                    let tuple = components_data.get_tuple(i);
+
+                    each_entity(entity, tuple);
+                }
 
-                each_entity(entity, tuple);
-            }
+                drop(lock);
 
-            if !CALLED_FROM_RUN {
-                sys::ecs_table_unlock(iter.world, iter.table);
-            }
+                iter_mark_written_fields::<T>(iter);
+            });
         }
 
         /// Callback of the `each_iter` functionality
@@ -167,28 +178,36 @@ pub mod private {
             Func: FnMut(TableIter<false, P>, usize, T::TupleType<'_>),
         {
             let iter = unsafe { &mut *iter };
-            iter.flags |= sys::EcsIterCppEach;
+            catch_callback_panic(iter.world, || {
+                iter.flags |= sys::EcsIterCppEach;
 
-            let each_iter = &mut *(iter.callback_ctx as *mut Func);
+                let each_iter = &mut *(iter.callback_ctx as *mut Func);
 
-            let mut components_data = T::create_ptrs(&*iter);
-            let iter_count = {
-                if iter.count == 0 && iter.table.is_null() {
-                    1_usize
-                } else {
-                    iter.count as usize
+                if !iter_fields_changed::<T>(iter) {
+                    return;
                 }
-            };
 
-            sys::ecs_table_lock(iter.world, iter.table);
+                let mut components_data = T::create_ptrs(&*iter);
+                let iter_count = {
+                    if iter.count == 0 && iter.table.is_null() {
+                        1_usize
+                    } else {
+                        iter.count as usize
+                    }
+                };
 
-            for i in 0..iter_count {
-                let iter_t = TableIter::new(iter);
-                let tuple = components_data.get_tuple(i);
+                let lock = TableLock::new(iter.world, iter.table);
 
-                each_iter(iter_t, i, tuple);
-            }
-            sys::ecs_table_unlock(iter.world, iter.table);
+                for i in 0..iter_count {
+                    let iter_t = TableIter::new(iter);
+                    let tuple = components_data.get_tuple(i);
+
+                    each_iter(iter_t, i, tuple);
+                }
+                drop(lock);
+
+                iter_mark_written_fields::<T>(iter);
+            });
         }
 
         /// Callback of the `iter_only` functionality
@@ -207,15 +226,26 @@ pub mod private {
         {
             unsafe {
                 let iter = &mut *iter;
-                let run = &mut *(iter.run_ctx as *mut Func);
-                let mut iter_t = TableIter::new(&mut *iter);
-                iter_t.iter_mut().flags &= !sys::EcsIterIsValid;
-                run(iter_t);
+                let ran = catch_callback_panic(iter.world, || {
+                    let run = &mut *(iter.run_ctx as *mut Func);
+                    let mut iter_t = TableIter::new(&mut *iter);
+                    iter_t.iter_mut().flags &= !sys::EcsIterIsValid;
+                    run(iter_t);
+                });
                 // ecs_assert!(
                 //     iter.flags & sys::EcsIterIsValid == 0,
                 //     FlecsErrorCode::InvalidOperation,
                 //     "iterators must be manually finished with ecs_iter_fini"
                 // );
+
+                // a panic can leave the iterator in the middle of a table, finish it so that
+                // the table is unlocked and the iterator resources are freed
+                if ran.is_none() && iter.flags & sys::EcsIterIsValid != 0 {
+                    if !iter.table.is_null() {
+                        sys::ecs_table_unlock(iter.world, iter.table);
+                    }
+                    sys::ecs_iter_fini(iter);
+                }
             }
         }
 
@@ -234,23 +264,31 @@ pub mod private {
             Func: FnMut(TableIter<false, P>, T::TupleSliceType<'_>),
         {
             let iter = &mut *iter;
-            let run_iter = &mut *(iter.callback_ctx as *mut Func);
-
-            let mut components_data = T::create_ptrs(&*iter);
-            let iter_count = {
-                if iter.count == 0 {
-                    1_usize
-                } else {
-                    iter.count as usize
+            catch_callback_panic(iter.world, || {
+                let run_iter = &mut *(iter.callback_ctx as *mut Func);
+
+                if !iter_fields_changed::<T>(iter) {
+                    return;
                 }
-            };
 
-            sys::ecs_table_lock(iter.world, iter.table);
+                let mut components_data = T::create_ptrs(&*iter);
+                let iter_count = {
+                    if iter.count == 0 {
+                        1_usize
+                    } else {
+                        iter.count as usize
+                    }
+                };
+
+                let lock = TableLock::new(iter.world, iter.table);
+
+                let tuple = components_data.get_slice(iter_count);
+                let iter_t = TableIter::new(&mut *iter);
+                run_iter(iter_t, tuple);
+                drop(lock);
 
-            let tuple = components_data.get_slice(iter_count);
-            let iter_t = TableIter::new(&mut *iter);
-            run_iter(iter_t, tuple);
-            sys::ecs_table_unlock(iter.world, iter.table);
+                iter_mark_written_fields::<T>(iter);
+            });
         }
 
         extern "C" fn free_callback<Func>(ptr: *mut c_void) {
@@ -1,5 +1,6 @@
 use std::ffi::c_char;
 
+use crate::core::callback_panic::catch_callback_panic;
 use flecs_ecs::core::*;
 use flecs_ecs::sys;
 
@@ -47,6 +48,10 @@ where
             iter.flags |= sys::EcsIterCppEach;
 
             while self.iter_next(&mut iter) {
+                if !iter_fields_changed::<T>(&mut iter) {
+                    continue;
+                }
+
                 let mut components_data = T::create_ptrs(&iter);
                 let iter_count = {
                     if iter.count == 0 && iter.table.is_null() {
@@ -64,6 +69,7 @@ where
                 }
 
                 sys::ecs_table_unlock(self.world_ptr_mut(), iter.table);
+                iter_mark_written_fields::<T>(&mut iter);
             }
         }
     }
@@ -88,6 +94,10 @@ where
             iter.flags |= sys::EcsIterCppEach;
 
             while self.iter_next(&mut iter) {
+                if !iter_fields_changed::<T>(&mut iter) {
+                    continue;
+                }
+
                 let mut components_data = T::create_ptrs(&iter);
                 let iter_count = {
                     if iter.count == 0 && iter.table.is_null() {
@@ -118,6 +128,7 @@ where
                 }
 
                 sys::ecs_table_unlock(world, iter.table);
+                iter_mark_written_fields::<T>(&mut iter);
             }
         }
     }
@@ -172,6 +183,10 @@ where
             iter.flags |= sys::EcsIterCppEach;
 
             while self.iter_next(&mut iter) {
+                if !iter_fields_changed::<T>(&mut iter) {
+                    continue;
+                }
+
                 let mut components_data = T::create_ptrs(&iter);
                 let iter_count = {
                     if iter.count == 0 && iter.table.is_null() {
@@ -191,6 +206,7 @@ where
                 }
 
                 sys::ecs_table_unlock(world, iter.table);
+                iter_mark_written_fields::<T>(&mut iter);
             }
         }
     }
@@ -218,6 +234,10 @@ where
             let world = self.world_ptr_mut();
 
             while self.iter_next(&mut iter) {
+                if !iter_fields_changed::<T>(&mut iter) {
+                    continue;
+                }
+
                 let mut components_data = T::create_ptrs(&iter);
                 let iter_count = iter.count as usize;
 
@@ -233,6 +253,7 @@ where
                 }
 
                 sys::ecs_table_unlock(world, iter.table);
+                iter_mark_written_fields::<T>(&mut iter);
             }
             entity
         }
@@ -264,6 +285,10 @@ where
             let world = self.world_ptr_mut();
 
             while self.iter_next(&mut iter) {
+                if !iter_fields_changed::<T>(&mut iter) {
+                    continue;
+                }
+
                 let mut components_data = T::create_ptrs(&iter);
                 let iter_count = iter.count as usize;
 
@@ -281,6 +306,7 @@ where
                 }
 
                 sys::ecs_table_unlock(world, iter.table);
+                iter_mark_written_fields::<T>(&mut iter);
             }
             entity_result
         }
@@ -315,6 +341,10 @@ where
             let world = self.world_ptr_mut();
 
             while self.iter_next(&mut iter) {
+                if !iter_fields_changed::<T>(&mut iter) {
+                    continue;
+                }
+
                 let mut components_data = T::create_ptrs(&iter);
                 let iter_count = {
                     if iter.count == 0 {
@@ -337,6 +367,7 @@ where
                 }
 
                 sys::ecs_table_unlock(world, iter.table);
+                iter_mark_written_fields::<T>(&mut iter);
             }
             entity_result
         }
@@ -424,6 +455,10 @@ where
             let world = self.world_ptr_mut();
 
             while self.iter_next(&mut iter) {
+                if !iter_fields_changed::<T>(&mut iter) {
+                    continue;
+                }
+
                 let mut components_data = T::create_ptrs(&iter);
                 let iter_count = iter.count as usize;
 
@@ -434,6 +469,7 @@ where
                 func(iter_t, tuple);
 
                 sys::ecs_table_unlock(world, iter.table);
+                iter_mark_written_fields::<T>(&mut iter);
             }
         }
     }
@@ -872,6 +908,60 @@ where
         }
     }
 
+    /// Find a variable by name and return a typed handle to it.
+    ///
+    /// # Arguments
+    ///
+    /// * `name`: the name of the variable, without the `$` prefix
+    ///
+    /// # Returns
+    ///
+    /// The variable handle, or `None` if the query has no variable with this name.
+    ///
+    /// # See also
+    ///
+    /// * [`QueryAPI::var()`]
+    fn try_var(&self, name: &str) -> Option<QueryVar> {
+        self.find_var(name)
+            .map(|index| QueryVar::new(self.query_ptr(), index))
+    }
+
+    /// Find a variable by name and return a typed handle to it.
+    ///
+    /// The handle can be passed to [`QueryAPI::set_var()`], [`QueryIter::set_var()`] and
+    /// [`TableIter::get_var()`] instead of a raw variable index.
+    ///
+    /// # Arguments
+    ///
+    /// * `name`: the name of the variable, without the `$` prefix
+    ///
+    /// # Panics
+    ///
+    /// Panics if the query has no variable with this name, use [`QueryAPI::try_var()`] if you are unsure.
+    ///
+    /// # See also
+    ///
+    /// * [`QueryAPI::try_var()`]
+    /// * [`QueryAPI::vars()`]
+    fn var(&self, name: &str) -> QueryVar {
+        self.try_var(name)
+            .unwrap_or_else(|| panic!("query has no variable named `{}`", name))
+    }
+
+    /// Returns the handles and names of all variables of the query, including the builtin `$this` variable.
+    ///
+    /// # See also
+    ///
+    /// * [`QueryAPI::var()`]
+    /// * [`TableIter::vars()`]
+    fn vars(&self) -> Vec<(QueryVar, &str)> {
+        let query = self.query_ptr();
+        let var_count = unsafe { (*query).var_count as i32 };
+        (0..var_count)
+            .map(|index| (QueryVar::new(query, index), var_name(query, index)))
+            .collect()
+    }
+
     fn plan(&self) -> String {
         let query = self.query_ptr();
         let result: *mut c_char = unsafe { sys::ecs_query_plan(query as *const _) };
@@ -981,7 +1071,7 @@ where
     ///
     /// # Arguments
     ///
-    /// * `var_id`: the variable id to set
+    /// * `var`: the variable to set, either a [`QueryVar`] or the variable index
     ///
     /// * `value`: the value to set
     ///
@@ -989,9 +1079,9 @@ where
     ///
     /// * C++ API: `iterable::set_var`
     #[doc(alias = "iterable::set_var")]
-    fn set_var(&mut self, var_id: i32, value: impl Into<Entity>) -> QueryIter<P, T> {
+    fn set_var(&mut self, var: impl QueryVarId, value: impl Into<Entity>) -> QueryIter<P, T> {
         let mut iter = self.iterable();
-        iter.set_var(var_id, value);
+        iter.set_var(var, value);
         iter
     }
 
@@ -999,7 +1089,7 @@ where
     ///
     /// # Arguments
     ///
-    /// * `var_id`: the variable id to set
+    /// * `var`: the variable to set, either a [`QueryVar`] or the variable index
     ///
     /// * `range`: the range to set
     ///
@@ -1007,9 +1097,13 @@ where
     ///
     /// * C++ API: `iter_iterable::set_var`
     #[doc(alias = "iter_iterable::set_var")]
-    fn set_var_table(&mut self, var_id: i32, table: impl IntoTableRange) -> QueryIter<P, T> {
+    fn set_var_table(
+        &mut self,
+        var: impl QueryVarId,
+        table: impl IntoTableRange,
+    ) -> QueryIter<P, T> {
         let mut iter = self.iterable();
-        iter.set_var_table(var_id, table);
+        iter.set_var_table(var, table);
         iter
     }
 
@@ -1053,15 +1147,23 @@ where
     T: QueryTuple,
     Func: FnMut(T::TupleType<'_>),
 {
-    let func = &mut *((*iter).callback_ctx as *mut Func);
+    catch_callback_panic((*iter).world, || {
+        let func = &mut *((*iter).callback_ctx as *mut Func);
 
-    let mut components_data = T::create_ptrs(&*iter);
-    let iter_count = (*iter).count as usize;
+        if !iter_fields_changed::<T>(&mut *iter) {
+            return;
+        }
 
-    for i in 0..iter_count {
-        let tuple = components_data.get_tuple(i);
-        func(tuple);
-    }
+        let mut components_data = T::create_ptrs(&*iter);
+        let iter_count = (*iter).count as usize;
+
+        for i in 0..iter_count {
+            let tuple = components_data.get_tuple(i);
+            func(tuple);
+        }
+
+        iter_mark_written_fields::<T>(&mut *iter);
+    });
 }
 
 unsafe extern "C" fn __internal_query_execute_each_entity<T, Func>(iter: *mut sys::ecs_iter_t)
@@ -1069,15 +1171,23 @@ where
     T: QueryTuple,
     Func: FnMut(EntityView, T::TupleType<'_>),
 {
-    let func = &mut *((*iter).callback_ctx as *mut Func);
+    catch_callback_panic((*iter).world, || {
+        let func = &mut *((*iter).callback_ctx as *mut Func);
 
-    let mut components_data = T::create_ptrs(&*iter);
-    let iter_count = (*iter).count as usize;
-    let world = WorldRef::from_ptr((*iter).world);
+        if !iter_fields_changed::<T>(&mut *iter) {
+            return;
+        }
 
-    for i in 0..iter_count {
-        let tuple = components_data.get_tuple(i);
+        let mut components_data = T::create_ptrs(&*iter);
+        let iter_count = (*iter).count as usize;
+        let world = WorldRef::from_ptr((*iter).world);
 
-        func(EntityView::new_from(world, *(*iter).entities.add(i)), tuple);
-    }
+        for i in 0..iter_count {
+            let tuple = components_data.get_tuple(i);
+
+            func(EntityView::new_from(world, *(*iter).entities.add(i)), tuple);
+        }
+
+        iter_mark_written_fields::<T>(&mut *iter);
+    });
 }
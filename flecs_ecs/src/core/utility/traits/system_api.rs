@@ -9,13 +9,46 @@ where
 {
     /// Set context
     ///
+    /// The system or observer takes ownership of the value, which is dropped when it is deleted.
+    /// The context can be accessed from within the callback with [`TableIter::context()`].
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The context to set.
+    ///
     /// # See also
     ///
+    /// * [`System::typed_context()`](crate::addons::system::System::typed_context)
+    /// * [`Observer::typed_context()`]
     /// * C++ API: `observer_builder_i::ctx`
     /// * C++ API: `system_builder_i::ctx`
     #[doc(alias = "observer_builder_i::ctx")]
     #[doc(alias = "system_builder_i::ctx")]
-    fn set_context(&mut self, context: *mut c_void) -> &mut Self;
+    fn set_typed_context<C: 'static>(&mut self, value: C) -> &mut Self {
+        let (ctx, ctx_free) = self.desc_context();
+        replace_context(ctx, ctx_free, new_context(value), Some(free_context));
+        self
+    }
+
+    /// Set a raw context pointer
+    ///
+    /// # See also
+    ///
+    /// * C++ API: `observer_builder_i::ctx`
+    /// * C++ API: `system_builder_i::ctx`
+    #[doc(alias = "observer_builder_i::ctx")]
+    #[doc(alias = "system_builder_i::ctx")]
+    fn set_context_ptr(&mut self, context: *mut c_void) -> &mut Self;
+
+    /// Set a raw context pointer
+    ///
+    /// # See also
+    ///
+    /// * [`SystemAPI::set_context_ptr()`]
+    #[deprecated(note = "renamed to `set_context_ptr`, use `set_typed_context` for an owned value")]
+    fn set_context(&mut self, context: *mut c_void) -> &mut Self {
+        self.set_context_ptr(context)
+    }
 
     fn each<Func>(&mut self, func: Func) -> <Self as builder::Builder<'a>>::BuiltType
     where
@@ -582,6 +615,10 @@ macro_rules! implement_reactor_api {
                 self.desc.callback_ctx
             }
 
+            fn desc_context(&mut self) -> (&mut *mut c_void, &mut sys::ecs_ctx_free_t) {
+                (&mut self.desc.ctx, &mut self.desc.ctx_free)
+            }
+
             fn set_desc_callback(
                 &mut self,
                 callback: Option<unsafe extern "C" fn(*mut flecs_ecs_sys::ecs_iter_t)>,
@@ -601,8 +638,8 @@ macro_rules! implement_reactor_api {
         where
             T: QueryTuple,
         {
-            fn set_context(&mut self, context: *mut c_void) -> &mut Self {
-                self.desc.ctx = context;
+            fn set_context_ptr(&mut self, context: *mut c_void) -> &mut Self {
+                replace_context(&mut self.desc.ctx, &mut self.desc.ctx_free, context, None);
                 self
             }
         }
@@ -647,6 +684,10 @@ macro_rules! implement_reactor_api {
                 self.desc.callback_ctx
             }
 
+            fn desc_context(&mut self) -> (&mut *mut c_void, &mut sys::ecs_ctx_free_t) {
+                (&mut self.desc.ctx, &mut self.desc.ctx_free)
+            }
+
             fn set_desc_callback(
                 &mut self,
                 callback: Option<unsafe extern "C" fn(*mut flecs_ecs_sys::ecs_iter_t)>,
@@ -667,8 +708,8 @@ macro_rules! implement_reactor_api {
             T: QueryTuple,
             P: ComponentId,
         {
-            fn set_context(&mut self, context: *mut c_void) -> &mut Self {
-                self.desc.ctx = context;
+            fn set_context_ptr(&mut self, context: *mut c_void) -> &mut Self {
+                replace_context(&mut self.desc.ctx, &mut self.desc.ctx_free, context, None);
                 self
             }
         }
@@ -10,6 +10,7 @@ use crate::addons::system::{System, SystemBuilder};
 #[cfg(feature = "flecs_pipeline")]
 use crate::addons::pipeline::PipelineBuilder;
 
+use crate::core::callback_panic::resume_callback_panic;
 use crate::core::*;
 use crate::sys;
 
@@ -341,6 +342,7 @@ impl World {
         unsafe {
             sys::ecs_frame_end(self.raw_world.as_ptr());
         }
+        resume_callback_panic(self.raw_world.as_ptr());
     }
 
     /// Begin readonly mode.
@@ -467,6 +469,7 @@ impl World {
         unsafe {
             sys::ecs_readonly_end(self.raw_world.as_ptr());
         }
+        resume_callback_panic(self.raw_world.as_ptr());
     }
 
     /// Test whether the current world object is readonly.
@@ -583,7 +586,9 @@ impl World {
     /// * C++ API: `world::defer_end`
     #[doc(alias = "world::defer_end")]
     pub fn defer_end(&self) -> bool {
-        unsafe { sys::ecs_defer_end(self.raw_world.as_ptr()) }
+        let result = unsafe { sys::ecs_defer_end(self.raw_world.as_ptr()) };
+        resume_callback_panic(self.raw_world.as_ptr());
+        result
     }
 
     /// Test whether deferring is enabled.
@@ -655,6 +660,7 @@ impl World {
         unsafe {
             sys::ecs_defer_end(self.raw_world.as_ptr());
         }
+        resume_callback_panic(self.raw_world.as_ptr());
         result
     }
 
@@ -1060,6 +1066,64 @@ impl World {
     /// Set a context value that can be accessed by anyone that has a reference
     /// to the world.
     ///
+    /// The world takes ownership of the value, which is dropped when the context is replaced or
+    /// when the world is destroyed. The value must be `Send + Sync`, as the world can be sent to
+    /// and shared with other threads. The typed context is stored separately from the raw context
+    /// set with [`World::set_context_ptr()`].
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The world context.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use flecs_ecs::prelude::*;
+    ///
+    /// struct Settings {
+    ///     gravity: f32,
+    /// }
+    ///
+    /// let mut world = World::new();
+    ///
+    /// world.set_typed_context(Settings { gravity: 9.81 });
+    ///
+    /// assert_eq!(world.typed_context::<Settings>().unwrap().gravity, 9.81);
+    /// assert!(world.typed_context::<i32>().is_none());
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// * [`World::typed_context()`]
+    /// * [`World::set_context_ptr()`]
+    /// * C++ API: `world::set_ctx`
+    #[doc(alias = "world::set_ctx")]
+    pub fn set_typed_context<C: Send + Sync + 'static>(&mut self, value: C) {
+        self.world_ctx_mut().context = Some(Box::new(value));
+    }
+
+    /// Get the typed world context.
+    ///
+    /// # Returns
+    ///
+    /// The world context, or `None` if no context is set or if it is not of type `C`.
+    ///
+    /// # Example
+    ///
+    /// See [`World::set_typed_context`].
+    ///
+    /// # See also
+    ///
+    /// * [`World::set_typed_context()`]
+    /// * C++ API: `world::get_ctx`
+    #[doc(alias = "world::get_ctx")]
+    pub fn typed_context<C: Send + Sync + 'static>(&self) -> Option<&C> {
+        self.world_ctx().context.as_ref()?.downcast_ref::<C>()
+    }
+
+    /// Set a raw context pointer that can be accessed by anyone that has a reference
+    /// to the world.
+    ///
     /// # Arguments
     ///
     /// * `ctx` - The world context.
@@ -1081,22 +1145,23 @@ impl World {
     ///
     /// let ctx = Box::leak(Box::new(42));
     ///
-    /// world.set_context(ctx as *mut i32 as *mut c_void, Some(free_ctx));
+    /// world.set_context_ptr(ctx as *mut i32 as *mut c_void, Some(free_ctx));
     ///
-    /// assert_eq!(world.context() as *const i32, ctx);
+    /// assert_eq!(world.context_ptr() as *const i32, ctx);
     /// ```
     ///
     /// # See also
     ///
-    /// * [`World::context()`]
+    /// * [`World::context_ptr()`]
+    /// * [`World::set_typed_context()`]
     /// * C++ API: `world::set_ctx`
     #[doc(alias = "world::set_ctx")]
     #[allow(clippy::not_unsafe_ptr_arg_deref)] // this doesn't actually deref the pointer
-    pub fn set_context(&self, ctx: *mut c_void, ctx_free: sys::ecs_ctx_free_t) {
+    pub fn set_context_ptr(&self, ctx: *mut c_void, ctx_free: sys::ecs_ctx_free_t) {
         unsafe { sys::ecs_set_ctx(self.raw_world.as_ptr(), ctx, ctx_free) }
     }
 
-    /// Get world context.
+    /// Get the raw world context pointer.
     ///
     /// # Returns
     ///
@@ -1104,17 +1169,38 @@ impl World {
     ///
     /// # Example
     ///
-    /// See [`World::set_context`].
+    /// See [`World::set_context_ptr`].
     ///
     /// # See also
     ///
-    /// * [`World::set_context()`]
+    /// * [`World::set_context_ptr()`]
     /// * C++ API: `world::get_ctx`
     #[doc(alias = "world::get_ctx")]
-    pub fn context(&self) -> *mut c_void {
+    pub fn context_ptr(&self) -> *mut c_void {
         unsafe { sys::ecs_get_ctx(self.raw_world.as_ptr()) }
     }
 
+    /// Set a raw world context pointer.
+    ///
+    /// # See also
+    ///
+    /// * [`World::set_context_ptr()`]
+    #[deprecated(note = "renamed to `set_context_ptr`, use `set_typed_context` for an owned value")]
+    #[allow(clippy::not_unsafe_ptr_arg_deref)]
+    pub fn set_context(&self, ctx: *mut c_void, ctx_free: sys::ecs_ctx_free_t) {
+        self.set_context_ptr(ctx, ctx_free);
+    }
+
+    /// Get the raw world context pointer.
+    ///
+    /// # See also
+    ///
+    /// * [`World::context_ptr()`]
+    #[deprecated(note = "renamed to `context_ptr`, use `typed_context` for an owned value")]
+    pub fn context(&self) -> *mut c_void {
+        self.context_ptr()
+    }
+
     pub(crate) fn get_context(world: *mut sys::ecs_world_t) -> *mut WorldCtx {
         unsafe { sys::ecs_get_binding_ctx(world) as *mut WorldCtx }
     }
@@ -1660,6 +1746,9 @@ impl World {
 
     /// signal that singleton component was modified.
     ///
+    /// Like [`EntityView::modified()`], this doesn't invoke the `on_replace` hook or
+    /// [`flecs::OnReplace`] observers.
+    ///
     /// # Arguments
     ///
     /// * `id` - The id of the component that was modified.
@@ -1678,6 +1767,9 @@ impl World {
 
     /// Signal that singleton component was modified.
     ///
+    /// Like [`EntityView::modified()`], this doesn't invoke the `on_replace` hook or
+    /// [`flecs::OnReplace`] observers.
+    ///
     /// # Type Parameters
     ///
     /// * `T` - The type of the component that was modified.
@@ -2980,6 +3072,7 @@ impl World {
         unsafe {
             sys::ecs_delete_with(self.raw_world.as_ptr(), *id.into());
         }
+        resume_callback_panic(self.raw_world.as_ptr());
     }
 
     /// Delete all entities with the given component
@@ -3089,6 +3182,7 @@ impl World {
         unsafe {
             sys::ecs_remove_all(self.raw_world.as_ptr(), *id.into());
         }
+        resume_callback_panic(self.raw_world.as_ptr());
     }
 
     /// Remove all instances of the given component from entities
@@ -3868,6 +3962,46 @@ impl World {
     pub fn event<T: ComponentId>(&self) -> EventBuilder<T> {
         EventBuilder::<T>::new(self)
     }
+
+    /// Create a new event builder for an event entity that carries a payload of type `T`.
+    ///
+    /// This makes it possible to emit events that are identified by an entity, but still have
+    /// a typed payload, such as events observed with [`ObserverBuilder::add_payload_event_id()`].
+    ///
+    /// # Type Parameters
+    ///
+    /// * `T` - The payload type.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The event id
+    ///
+    /// # See also
+    ///
+    /// * [`World::event()`]
+    /// * [`ObserverBuilder::each_event()`]
+    pub fn event_with_payload<T: ComponentId>(
+        &self,
+        event: impl Into<Entity>,
+    ) -> EventBuilder<'_, T> {
+        let mut builder = EventBuilder::<T>::new(self);
+        builder.desc.event = *event.into();
+        builder
+    }
+
+    /// Create a new event builder for an event that carries a payload of another type.
+    ///
+    /// # Type Parameters
+    ///
+    /// * `E` - The event.
+    ///
+    /// # See also
+    ///
+    /// * [`World::event_with_payload()`]
+    /// * [`ObserverBuilder::add_payload_event()`]
+    pub fn payload_event<E: PayloadEvent>(&self) -> EventBuilder<'_, E::Payload> {
+        self.event_with_payload::<E::Payload>(E::id(self))
+    }
 }
 
 // Observer mixin implementation
@@ -3957,6 +4091,103 @@ impl World {
     {
         ObserverBuilder::<Event, Components>::new_named(self, name)
     }
+
+    /// Create an observer that is invoked once when an entity starts matching its query.
+    ///
+    /// Unlike an `OnAdd` observer, which is invoked for each term of the query, the observer is
+    /// invoked when the last missing term starts matching. This includes `without` terms, so an
+    /// entity that matches a query with `without::<T>()` also starts matching when `T` is removed.
+    /// The components of the query are passed to the callback. As with `OnAdd` observers, the
+    /// observer is invoked before a component that is added with `set` is assigned, so the
+    /// value of the component that caused the entity to match may not be initialized yet.
+    ///
+    /// The filter applies to callbacks that are set with the `each` functions, a callback set
+    /// with `run` is invoked when entities start and stop matching and can check `it.event()`.
+    ///
+    /// # Type Parameters
+    ///
+    /// * `Components` - The components to match on.
+    ///
+    /// # Returns
+    ///
+    /// Observer builder.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use flecs_ecs::prelude::*;
+    ///
+    /// #[derive(Component, Default)]
+    /// struct Position {
+    ///     x: f32,
+    ///     y: f32,
+    /// }
+    ///
+    /// #[derive(Component, Default)]
+    /// struct Velocity {
+    ///     x: f32,
+    ///     y: f32,
+    /// }
+    ///
+    /// #[derive(Component)]
+    /// struct Frozen;
+    ///
+    /// let world = World::new();
+    ///
+    /// world
+    ///     .on_enter::<(&Position, &Velocity)>()
+    ///     .without::<Frozen>()
+    ///     .each_entity(|e, (pos, vel)| {
+    ///         println!("{} starts moving from ({}, {})", e, pos.x, pos.y);
+    ///     });
+    ///
+    /// world
+    ///     .on_leave::<(&Position, &Velocity)>()
+    ///     .without::<Frozen>()
+    ///     .each_entity(|e, _| println!("{} stops moving", e));
+    ///
+    /// let e = world.entity().add::<Position>(); // no match yet
+    /// e.add::<Velocity>(); // prints "starts moving"
+    /// e.add::<Frozen>(); // prints "stops moving"
+    /// e.remove::<Frozen>(); // prints "starts moving"
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// * [`World::on_leave()`]
+    /// * [`flecs::Monitor`]
+    pub fn on_enter<Components>(&self) -> ObserverBuilder<'_, flecs::Monitor, Components>
+    where
+        Components: QueryTuple,
+    {
+        ObserverBuilder::new_match(self, ECS_ON_ADD)
+    }
+
+    /// Create an observer that is invoked once when an entity stops matching its query.
+    ///
+    /// The observer is invoked when the first term of the query stops matching, e.g. when a
+    /// component is removed, a component of a `without` term is added or the entity is deleted.
+    /// The callback is invoked before the entity changes, so the components of the query can
+    /// still be accessed.
+    ///
+    /// # Type Parameters
+    ///
+    /// * `Components` - The components to match on.
+    ///
+    /// # Returns
+    ///
+    /// Observer builder.
+    ///
+    /// # See also
+    ///
+    /// * [`World::on_enter()`]
+    /// * [`flecs::Monitor`]
+    pub fn on_leave<Components>(&self) -> ObserverBuilder<'_, flecs::Monitor, Components>
+    where
+        Components: QueryTuple,
+    {
+        ObserverBuilder::new_match(self, ECS_ON_REMOVE)
+    }
 }
 
 /// Query mixin implementation
@@ -4063,6 +4294,69 @@ impl World {
         QueryBuilder::<Components>::new_named(self, name)
     }
 
+    /// Create a new uncached [`Query`] from a query expression.
+    ///
+    /// The expression uses the flecs query language, e.g. `"Position, [in] Velocity, !Frozen"`.
+    /// The leading terms of the expression must match `Components` in component type,
+    /// operator and access mode, further terms are only used for matching.
+    /// Unlike [`QueryBuilderImpl::expr()`], an invalid expression does not panic, which makes
+    /// this suitable for queries that come from user input or configuration files.
+    ///
+    /// # Type Parameters
+    ///
+    /// * `Components` - The components to match on.
+    ///
+    /// # Arguments
+    ///
+    /// * `expr` - The query expression.
+    ///
+    /// # Returns
+    ///
+    /// The query, or a [`QueryError`] describing which term is invalid and why.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use flecs_ecs::prelude::*;
+    ///
+    /// #[derive(Component)]
+    /// struct Position {
+    ///     x: f32,
+    ///     y: f32,
+    /// }
+    ///
+    /// let world = World::new();
+    /// world.component_named::<Position>("Position");
+    ///
+    /// let query = world.try_query_from_expr::<&Position>("Position").unwrap();
+    /// assert_eq!(query.field_count(), 1);
+    ///
+    /// let err = world
+    ///     .try_query_from_expr::<&Position>("Position, Foo")
+    ///     .err().unwrap();
+    /// assert_eq!(err.kind, QueryErrorKind::Parse);
+    /// assert_eq!(err.term_index, Some(1));
+    ///
+    /// let err = world
+    ///     .try_query_from_expr::<&mut Position>("[in] Position")
+    ///     .err().unwrap();
+    /// assert_eq!(err.kind, QueryErrorKind::AccessMismatch);
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// * [`World::new_query()`]
+    /// * [`QueryBuilderImpl::expr()`]
+    pub fn try_query_from_expr<Components>(
+        &self,
+        expr: &str,
+    ) -> Result<Query<Components>, QueryError>
+    where
+        Components: QueryTuple,
+    {
+        Query::<Components>::try_new_from_expr(self, expr)
+    }
+
     /// Convert a query entity to a query.
     ///
     /// # Safety
@@ -4400,7 +4694,13 @@ impl World {
     #[doc(alias = "world::progress")]
     #[inline(always)]
     pub fn progress_time(&self, delta_time: f32) -> bool {
-        unsafe { sys::ecs_progress(self.raw_world.as_ptr(), delta_time) }
+        let result = crate::addons::system::progress(self.into(), delta_time);
+        resume_callback_panic(self.raw_world.as_ptr());
+        #[cfg(feature = "flecs_system")]
+        if let Some(error) = self.take_system_error() {
+            log_error(&error.to_string());
+        }
+        result
     }
 
     /// Run pipeline.
@@ -4459,9 +4759,11 @@ impl World {
     #[doc(alias = "world::run_pipeline")]
     #[inline(always)]
     pub fn run_pipeline_id_time(&self, pipeline: impl Into<Entity>, delta_time: super::FTime) {
-        unsafe {
-            sys::ecs_run_pipeline(self.raw_world.as_ptr(), *pipeline.into(), delta_time);
-        }
+        let pipeline = *pipeline.into();
+        crate::addons::pipeline::run_pipeline(self.into(), || unsafe {
+            sys::ecs_run_pipeline(self.raw_world.as_ptr(), pipeline, delta_time);
+        });
+        resume_callback_panic(self.raw_world.as_ptr());
     }
 
     /// Run pipeline.
@@ -4495,9 +4797,11 @@ impl World {
     where
         Component: ComponentType<Struct> + ComponentId,
     {
-        unsafe {
-            sys::ecs_run_pipeline(self.raw_world.as_ptr(), Component::id(self), delta_time);
-        }
+        let pipeline = Component::id(self);
+        crate::addons::pipeline::run_pipeline(self.into(), || unsafe {
+            sys::ecs_run_pipeline(self.raw_world.as_ptr(), pipeline, delta_time);
+        });
+        resume_callback_panic(self.raw_world.as_ptr());
     }
 
     /// Run pipeline.
@@ -1,3 +1,5 @@
+use std::any::Any;
+
 use super::{FlecsArray, FlecsIdMap, World};
 use crate::sys;
 
@@ -6,6 +8,54 @@ pub(crate) struct WorldCtx {
     pub(crate) components: FlecsIdMap,
     pub(crate) components_array: FlecsArray,
     is_panicking: bool,
+    /// The panic of a callback invoked from C that hasn't been resumed yet.
+    pub(crate) callback_panic: super::utility::callback_panic::CallbackPanic,
+    pub(crate) context: Option<Box<dyn Any + Send + Sync>>,
+    /// Whether systems have ordering constraints, which are resolved when a system is built.
+    pub(crate) has_system_order: bool,
+    /// The position of systems with ordering constraints, see `order_systems`.
+    pub(crate) system_order: std::collections::HashMap<super::Entity, u64, fxhash::FxBuildHasher>,
+    /// The payload type (component id) of events that are observed with a typed payload, and the
+    /// number of observers that observe the event with it.
+    pub(crate) event_payloads: std::collections::HashMap<u64, (u64, usize), fxhash::FxBuildHasher>,
+    /// The generation of the queries that are created by the Rust API by their address, see
+    /// `QueryVar`. Entries of deleted queries are replaced when a query reuses their address.
+    pub(crate) query_generations: std::collections::HashMap<usize, u64, fxhash::FxBuildHasher>,
+    pub(crate) next_query_generation: u64,
+    /// The groups of observers that are invoked in order, see `ObserverBuilder::priority`.
+    pub(crate) observer_groups: super::observer_order::ObserverGroups,
+    /// The policy for errors of fallible systems that don't have a policy.
+    #[cfg(feature = "flecs_system")]
+    pub(crate) system_error_policy: crate::addons::system::SystemErrorPolicy,
+    /// The error of a fallible system that is returned by `World::try_progress`.
+    #[cfg(feature = "flecs_system")]
+    pub(crate) system_error: std::sync::Mutex<Option<crate::addons::system::SystemError>>,
+    /// The run actions that are installed on systems, see `system_run`.
+    #[cfg(feature = "flecs_system")]
+    pub(crate) system_runs: std::collections::HashMap<
+        super::Entity,
+        *mut crate::addons::system::SystemRun,
+        fxhash::FxBuildHasher,
+    >,
+    /// The fixed timestep of the world, see `World::set_fixed_timestep`.
+    #[cfg(feature = "flecs_pipeline")]
+    pub(crate) fixed_timestep: Option<crate::addons::pipeline::FixedTimestep>,
+    /// The callback that is invoked before and after a pipeline runs a system.
+    #[cfg(feature = "flecs_pipeline")]
+    pub(crate) system_profiler: Option<Box<crate::addons::pipeline::SystemProfilerFn>>,
+    /// Whether the system profiler is enabled, see `World::enable_system_profiler`.
+    #[cfg(feature = "flecs_pipeline")]
+    pub(crate) system_profiler_enabled: bool,
+    /// Whether a pipeline is running, as only systems that a pipeline runs are profiled.
+    #[cfg(feature = "flecs_pipeline")]
+    pub(crate) running_pipeline: bool,
+    /// Whether the pipeline was stopped by a system error, which skips the remaining systems
+    /// until the pipeline runs again.
+    #[cfg(feature = "flecs_pipeline")]
+    pub(crate) pipeline_stopped: std::sync::atomic::AtomicBool,
+    /// The closures of the app that is running, see `App::run`.
+    #[cfg(feature = "flecs_app")]
+    pub(crate) app_callbacks: *mut crate::addons::app::AppCallbacks<'static>,
 }
 
 impl WorldCtx {
@@ -15,6 +65,32 @@ impl WorldCtx {
             components: Default::default(),
             components_array: vec![0; 500],
             is_panicking: false,
+            callback_panic: Default::default(),
+            context: None,
+            has_system_order: false,
+            system_order: Default::default(),
+            event_payloads: Default::default(),
+            query_generations: Default::default(),
+            next_query_generation: 0,
+            observer_groups: Default::default(),
+            #[cfg(feature = "flecs_system")]
+            system_error_policy: Default::default(),
+            #[cfg(feature = "flecs_system")]
+            system_error: Default::default(),
+            #[cfg(feature = "flecs_system")]
+            system_runs: Default::default(),
+            #[cfg(feature = "flecs_pipeline")]
+            fixed_timestep: None,
+            #[cfg(feature = "flecs_pipeline")]
+            system_profiler: None,
+            #[cfg(feature = "flecs_pipeline")]
+            system_profiler_enabled: false,
+            #[cfg(feature = "flecs_pipeline")]
+            running_pipeline: false,
+            #[cfg(feature = "flecs_pipeline")]
+            pipeline_stopped: Default::default(),
+            #[cfg(feature = "flecs_app")]
+            app_callbacks: std::ptr::null_mut(),
         }
     }
 
@@ -87,3 +87,356 @@ fn temp_test_hook() {
         assert_eq!(unsafe { COUNT_SET_POS }, 3);
     }
 }
+
+#[test]
+fn component_untyped_hooks() {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    let world = World::new();
+    let log = Rc::new(RefCell::new(Vec::new()));
+
+    let id = world.component::<Position>().id();
+    let (add_log, set_log, remove_log) = (log.clone(), log.clone(), log.clone());
+
+    world
+        .component_untyped_id(id)
+        .on_add(move |e, bytes| {
+            assert_eq!(bytes.len(), std::mem::size_of::<Position>());
+            add_log.borrow_mut().push(("add", e.id()));
+        })
+        .on_set(move |e, bytes| {
+            set_log.borrow_mut().push(("set", e.id()));
+            bytes[..4].copy_from_slice(&10i32.to_ne_bytes());
+        })
+        .on_remove(move |e, _| {
+            remove_log.borrow_mut().push(("remove", e.id()));
+        });
+
+    let entity = world.entity().set(Position { x: 1, y: 2 });
+    entity.get::<&Position>(|pos| {
+        assert_eq!(pos.x, 10);
+        assert_eq!(pos.y, 2);
+    });
+
+    entity.remove::<Position>();
+
+    assert_eq!(
+        *log.borrow(),
+        vec![
+            ("add", entity.id()),
+            ("set", entity.id()),
+            ("remove", entity.id())
+        ]
+    );
+}
+
+#[test]
+fn component_on_replace_hook() {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    let world = World::new();
+    let log = Rc::new(RefCell::new(Vec::new()));
+
+    let log_ref = log.clone();
+    world
+        .component::<Position>()
+        .on_replace(move |e, old, new| {
+            log_ref
+                .borrow_mut()
+                .push((e.id(), (old.x, old.y), (new.x, new.y)));
+        });
+
+    // adding the component doesn't replace anything
+    let e = world.entity().set(Position { x: 1, y: 2 });
+    assert!(log.borrow().is_empty());
+
+    e.set(Position { x: 3, y: 4 });
+    assert_eq!(*log.borrow(), vec![(e.id(), (1, 2), (3, 4))]);
+
+    // deferred sets see the value as it is when the set is issued
+    world.defer_begin();
+    e.set(Position { x: 5, y: 6 });
+    world.defer_end();
+    assert_eq!(log.borrow()[1], (e.id(), (3, 4), (5, 6)));
+
+    // pairs that use the component as data type
+    let pair = world
+        .entity()
+        .set_pair::<Position, Likes>(Position { x: 7, y: 8 });
+    pair.set_pair::<Position, Likes>(Position { x: 9, y: 10 });
+    assert_eq!(log.borrow()[2], (pair.id(), (7, 8), (9, 10)));
+
+    e.get::<&Position>(|p| {
+        assert_eq!(p.x, 5);
+        assert_eq!(p.y, 6);
+    });
+    assert_eq!(log.borrow().len(), 3);
+}
+
+#[test]
+fn component_on_replace_hook_deferred_and_modify() {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    #[derive(Component, Clone)]
+    struct Cell {
+        x: i32,
+    }
+
+    let world = World::new();
+    let log = Rc::new(RefCell::new(Vec::new()));
+
+    let log_ref = log.clone();
+    world
+        .component::<Cell>()
+        .on_replace(move |_, old, new| log_ref.borrow_mut().push((old.x, new.x)));
+
+    let e = world.entity().set(Cell { x: 1 });
+
+    // an owned value is overwritten in place, each deferred set sees the stored value
+    world.defer_begin();
+    e.set(Cell { x: 2 });
+    e.set(Cell { x: 3 });
+    world.defer_end();
+    assert_eq!(*log.borrow(), vec![(1, 2), (2, 3)]);
+
+    // a value that is added by the deferred set doesn't replace anything
+    let added = world.entity();
+    world.defer_begin();
+    added.set(Cell { x: 4 });
+    added.set(Cell { x: 5 });
+    world.defer_end();
+    assert_eq!(log.borrow().len(), 2);
+    added.get::<&Cell>(|cell| assert_eq!(cell.x, 5));
+
+    e.modify::<Cell>(|cell| cell.x += 10);
+    assert_eq!(log.borrow()[2], (3, 13));
+    e.get::<&Cell>(|cell| assert_eq!(cell.x, 13));
+
+    world.defer_begin();
+    e.modify::<Cell>(|cell| cell.x += 1);
+    world.defer_end();
+    assert_eq!(log.borrow()[3], (13, 14));
+    assert_eq!(log.borrow().len(), 4);
+}
+
+#[test]
+fn component_multiple_hooks_and_removal() {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    let world = World::new();
+    let log = Rc::new(RefCell::new(Vec::new()));
+
+    let (first, second) = (log.clone(), log.clone());
+    let first_hook = world
+        .component::<Position>()
+        .on_remove_handle(move |_, p| first.borrow_mut().push(("first", p.x)));
+    let second_hook = world
+        .component::<Position>()
+        .on_remove_handle(move |_, p| second.borrow_mut().push(("second", p.x)));
+
+    assert_eq!(first_hook.kind(), HookKind::OnRemove);
+    assert_eq!(
+        first_hook.component(),
+        world.component::<Position>().entity()
+    );
+
+    world.entity().set(Position { x: 1, y: 0 }).destruct();
+    assert_eq!(*log.borrow(), vec![("first", 1), ("second", 1)]);
+
+    assert!(first_hook.remove());
+    assert!(!first_hook.remove());
+
+    world.entity().set(Position { x: 2, y: 0 }).destruct();
+    assert_eq!(log.borrow()[2..], [("second", 2)]);
+
+    // hooks can be added after the component is in use
+    let e = world.entity().set(Position { x: 3, y: 0 });
+    let third = log.clone();
+    world
+        .component::<Position>()
+        .on_remove(move |_, p| third.borrow_mut().push(("third", p.x)));
+    e.destruct();
+    assert_eq!(log.borrow()[3..], [("second", 3), ("third", 3)]);
+
+    assert!(second_hook.remove());
+}
+
+#[test]
+fn component_hooks_install_only_their_slot() {
+    let world = World::new();
+
+    let mut velocity = world.component::<Velocity>();
+    velocity.on_replace(|_, _, _| {});
+    let hooks = velocity.get_hooks();
+    assert!(hooks.on_add.is_none());
+    assert!(hooks.on_remove.is_none());
+    assert!(hooks.on_set.is_none());
+
+    velocity.on_set(|_, _| {});
+    let hooks = velocity.get_hooks();
+    assert!(hooks.on_add.is_none());
+    assert!(hooks.on_remove.is_none());
+    assert!(hooks.on_set.is_some());
+
+    let on_set = hooks.on_set.map(|hook| hook as usize);
+    velocity.on_set(|_, _| {});
+    assert_eq!(
+        velocity.get_hooks().on_set.map(|hook| hook as usize),
+        on_set
+    );
+}
+
+#[test]
+#[should_panic(expected = "isn't registered with the Rust API")]
+fn component_hook_over_foreign_hook() {
+    unsafe extern "C" fn foreign_on_add(_: *mut flecs_ecs::sys::ecs_iter_t) {}
+
+    let world = World::new();
+    let mut mass = world.component::<Mass>();
+    let hooks = flecs_ecs::sys::ecs_type_hooks_t {
+        on_add: Some(foreign_on_add),
+        ..Default::default()
+    };
+    unsafe { flecs_ecs::sys::ecs_set_hooks_id(world.ptr_mut(), *mass.id(), &hooks) };
+
+    mass.on_add(|_, _| {});
+}
+
+#[test]
+fn component_lifecycle_closures() {
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    // doesn't implement `Default` or `Clone`
+    #[derive(Component)]
+    struct Handle {
+        id: u32,
+        // set to `id` by the move fixup
+        moved_id: u32,
+    }
+
+    let world = World::new();
+    let created = Rc::new(Cell::new(0u32));
+    let destroyed = Rc::new(Cell::new(Vec::new()));
+    let moved = Rc::new(Cell::new(0));
+
+    let handle = world.component::<Handle>();
+    handle
+        .lifecycle()
+        .ctor({
+            let created = created.clone();
+            move || {
+                created.set(created.get() + 1);
+                Handle {
+                    id: created.get(),
+                    moved_id: 0,
+                }
+            }
+        })
+        .dtor({
+            let destroyed = destroyed.clone();
+            move |handle| {
+                let mut ids = destroyed.take();
+                ids.push(handle.id);
+                destroyed.set(ids);
+            }
+        })
+        .copy(|handle| Handle {
+            id: handle.id + 100,
+            moved_id: 0,
+        })
+        .on_move({
+            let moved = moved.clone();
+            move |handle| {
+                handle.moved_id = handle.id;
+                moved.set(moved.get() + 1);
+            }
+        });
+
+    let e = world.entity().add_id(handle);
+    assert_eq!(created.get(), 1);
+    e.get::<&Handle>(|h| assert_eq!(h.id, 1));
+
+    // moving the entity to another table moves the value
+    e.add::<Position>();
+    assert!(moved.get() > 0);
+    e.get::<&Handle>(|h| assert_eq!(h.moved_id, 1));
+
+    let copy = e.duplicate(true);
+    copy.get::<&Handle>(|h| assert_eq!(h.id, 101));
+
+    copy.destruct();
+    e.remove::<Handle>();
+    assert_eq!(destroyed.take(), vec![101, 1]);
+}
+
+#[test]
+fn component_lifecycle_on_move_swap() {
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    #[derive(Component)]
+    struct Slot {
+        id: u32,
+        // set to `id` by the move fixup
+        moved_id: u32,
+    }
+
+    let world = World::new();
+    let moved = Rc::new(Cell::new(0));
+
+    let destroyed = Rc::new(Cell::new(Vec::new()));
+
+    let slot = world.component::<Slot>();
+    slot.lifecycle()
+        .on_move({
+            let moved = moved.clone();
+            move |slot| {
+                slot.moved_id = slot.id;
+                moved.set(moved.get() + 1);
+            }
+        })
+        .dtor({
+            let destroyed = destroyed.clone();
+            move |slot| {
+                let mut ids = destroyed.take();
+                ids.push(slot.id);
+                destroyed.set(ids);
+            }
+        });
+
+    let entities: Vec<_> = (1..=3)
+        .map(|id| world.entity().set(Slot { id, moved_id: 0 }))
+        .collect();
+    moved.set(0);
+
+    // deleting the middle row moves the last row into its place
+    entities[1].destruct();
+    assert_eq!(moved.get(), 1);
+    entities[2].get::<&Slot>(|slot| assert_eq!(slot.moved_id, 3));
+
+    // removing the component from the middle row moves the last row as well
+    let e = world.entity().set(Slot { id: 4, moved_id: 0 });
+    moved.set(0);
+    entities[2].remove::<Slot>();
+    assert_eq!(moved.get(), 1);
+    e.get::<&Slot>(|slot| assert_eq!(slot.moved_id, 4));
+
+    // values that are set while deferred are moved to the storage
+    let e = world.entity();
+    world.defer_begin();
+    e.set(Position { x: 1, y: 2 });
+    e.set(Slot { id: 5, moved_id: 0 });
+    e.set(Velocity { x: 1, y: 2 });
+    world.defer_end();
+    e.get::<&Slot>(|slot| assert_eq!(slot.moved_id, 5));
+
+    // the value that was moved to the storage is only destroyed once
+    destroyed.take();
+    e.remove::<Slot>();
+    assert_eq!(destroyed.take(), vec![5]);
+}
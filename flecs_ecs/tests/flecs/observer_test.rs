@@ -951,6 +951,93 @@ fn observer_name_from_root() {
     assert!(ns == o.parent().unwrap());
 }
 
+#[test]
+fn observer_typed_context() {
+    struct Ctx {
+        value: i32,
+    }
+
+    let world = World::new();
+    world.set(Count(0));
+
+    let mut o = world
+        .observer::<flecs::OnSet, &Position>()
+        .set_typed_context(Ctx { value: 2 })
+        .each_iter(|it, _, _| {
+            assert!(it.context::<i32>().is_none());
+            let value = it.context::<Ctx>().unwrap().value;
+            it.world().get::<&mut Count>(|c| c.0 += value);
+        });
+
+    assert_eq!(o.typed_context::<Ctx>().unwrap().value, 2);
+
+    world.entity().set(Position { x: 1, y: 2 });
+    world.get::<&Count>(|c| assert_eq!(c.0, 2));
+
+    o.set_typed_context(Ctx { value: 3 });
+    world.entity().set(Position { x: 1, y: 2 });
+    world.get::<&Count>(|c| assert_eq!(c.0, 5));
+
+    o.set_typed_context(5);
+    assert!(o.typed_context::<Ctx>().is_none());
+    assert_eq!(o.typed_context::<i32>(), Some(&5));
+}
+
+#[test]
+fn observer_on_replace() {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    let world = World::new();
+    let log = Rc::new(RefCell::new(Vec::new()));
+
+    let log_ref = log.clone();
+    world
+        .observer::<flecs::OnReplace, &Position>()
+        .each_iter(move |it, index, old| {
+            let new = unsafe { &*(it.param_untyped() as *const Position) };
+            log_ref
+                .borrow_mut()
+                .push((it.entity(index).id(), (old.x, old.y), (new.x, new.y)));
+        });
+
+    let e = world.entity().set(Position { x: 1, y: 2 });
+    assert!(log.borrow().is_empty());
+
+    e.set(Position { x: 3, y: 4 });
+    assert_eq!(*log.borrow(), vec![(e.id(), (1, 2), (3, 4))]);
+
+    // other components are not reported
+    e.set(Velocity { x: 1, y: 1 });
+    e.set(Velocity { x: 2, y: 2 });
+    assert_eq!(log.borrow().len(), 1);
+}
+
+#[test]
+fn observer_on_replace_wildcard_pair() {
+    let world = World::new();
+    let count = std::rc::Rc::new(std::cell::Cell::new(0));
+
+    let count_ref = count.clone();
+    world
+        .observer::<flecs::OnReplace, ()>()
+        .with::<(Position, flecs::Wildcard)>()
+        .each_iter(move |_, _, _| count_ref.set(count_ref.get() + 1));
+
+    let e = world
+        .entity()
+        .set_pair::<Position, TagA>(Position { x: 1, y: 2 })
+        .set(Position { x: 1, y: 2 });
+    assert_eq!(count.get(), 0);
+
+    e.set_pair::<Position, TagA>(Position { x: 3, y: 4 });
+    assert_eq!(count.get(), 1);
+
+    // the component itself doesn't match the pair wildcard
+    e.set(Position { x: 3, y: 4 });
+    assert_eq!(count.get(), 1);
+}
+
 #[test]
 #[should_panic]
 fn observer_panic_inside() {
@@ -961,3 +1048,529 @@ fn observer_panic_inside() {
     world.observer::<flecs::OnAdd, &Tag>().each(|_| panic!());
     world.add::<Tag>();
 }
+
+#[derive(Component)]
+struct Clicked {
+    button: u8,
+}
+
+#[test]
+fn observer_each_event_typed_payload() {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    let world = World::new();
+    let log = Rc::new(RefCell::new(Vec::new()));
+
+    // an event entity that carries the same payload as `Clicked`
+    let double_clicked = world.entity();
+
+    let log_ref = log.clone();
+    world
+        .observer::<Clicked, &Position>()
+        .add_payload_event_id(double_clicked)
+        .each_event(move |clicked, entity, pos| {
+            log_ref
+                .borrow_mut()
+                .push((entity.id(), clicked.button, pos.x));
+        });
+
+    let e = world.entity().set(Position { x: 10, y: 20 });
+    world
+        .event()
+        .add::<Position>()
+        .entity(e)
+        .emit(&Clicked { button: 1 });
+    world
+        .event_with_payload::<Clicked>(double_clicked)
+        .add::<Position>()
+        .entity(e)
+        .emit(&Clicked { button: 2 });
+
+    assert_eq!(*log.borrow(), vec![(e.id(), 1, 10), (e.id(), 2, 10)]);
+}
+
+#[test]
+#[should_panic(expected = "but its observers expect a payload of type")]
+fn observer_each_event_wrong_payload() {
+    let world = World::new();
+    let double_clicked = world.entity();
+
+    world
+        .observer::<Clicked, &Position>()
+        .add_payload_event_id(double_clicked)
+        .each_event(|_, _, _| {});
+
+    let e = world.entity().set(Position { x: 10, y: 20 });
+    world
+        .event_with_payload::<Velocity>(double_clicked)
+        .add::<Position>()
+        .entity(e)
+        .emit(&Velocity { x: 1, y: 1 });
+}
+
+#[test]
+fn observer_each_event_payload_event_type() {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    #[derive(Component)]
+    struct DoubleClicked;
+
+    impl PayloadEvent for DoubleClicked {
+        type Payload = Clicked;
+    }
+
+    let world = World::new();
+    let log = Rc::new(RefCell::new(Vec::new()));
+
+    let log_ref = log.clone();
+    world
+        .observer::<Clicked, &Position>()
+        .add_payload_event::<DoubleClicked>()
+        .each_event(move |clicked, _, _| log_ref.borrow_mut().push(clicked.button));
+
+    let e = world.entity().set(Position { x: 10, y: 20 });
+    world
+        .payload_event::<DoubleClicked>()
+        .add::<Position>()
+        .entity(e)
+        .emit(&Clicked { button: 3 });
+
+    assert_eq!(*log.borrow(), vec![3]);
+}
+
+#[test]
+fn observer_each_event_payload_removed_with_observer() {
+    let world = World::new();
+    let double_clicked = world.entity();
+
+    let first = world
+        .observer::<Clicked, &Position>()
+        .add_payload_event_id(double_clicked)
+        .each_event(|_, _, _| {});
+    let second = world
+        .observer::<Clicked, &Position>()
+        .add_payload_event_id(double_clicked)
+        .each_event(|_, _, _| {});
+
+    // the payload type stays registered while an observer still uses it
+    first.destruct();
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        world
+            .event_with_payload::<Velocity>(double_clicked)
+            .add::<Position>()
+            .entity(world.entity().set(Position { x: 0, y: 0 }))
+            .emit(&Velocity { x: 1, y: 1 });
+    }));
+    assert!(result.is_err());
+
+    // once the observers are deleted, the event can carry another payload
+    second.destruct();
+    world
+        .observer::<Velocity, &Position>()
+        .add_payload_event_id(double_clicked)
+        .each_event(|_, _, _| {});
+}
+
+#[test]
+fn observer_propagate_event_to_parents() {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    #[derive(Component)]
+    struct Widget;
+
+    let world = World::new();
+    let log = Rc::new(RefCell::new(Vec::new()));
+
+    let window = world.entity().add::<Widget>();
+    let panel = world.entity().add::<Widget>().child_of_id(window);
+    let button = world.entity().add::<Widget>().child_of_id(panel);
+    let (panel_id, button_id) = (panel.id(), button.id());
+
+    let log_ref = log.clone();
+    world
+        .observer::<Clicked, ()>()
+        .with::<Widget>()
+        .each_iter(move |it, index, _| {
+            let widget = it.entity(index).id();
+            log_ref.borrow_mut().push((widget, it.param().button));
+            if it.param().button == 2 && widget == panel_id {
+                it.stop_propagation();
+            }
+        });
+
+    // bubbles up to the root
+    world
+        .event()
+        .add::<Widget>()
+        .entity(button)
+        .propagate::<flecs::ChildOf>()
+        .emit(&Clicked { button: 1 });
+    assert_eq!(
+        *log.borrow(),
+        vec![(button_id, 1), (panel_id, 1), (window.id(), 1)]
+    );
+
+    // stopped by the observer of the panel
+    log.borrow_mut().clear();
+    world
+        .event()
+        .add::<Widget>()
+        .entity(button)
+        .propagate::<flecs::ChildOf>()
+        .emit(&Clicked { button: 2 });
+    assert_eq!(*log.borrow(), vec![(button_id, 2), (panel_id, 2)]);
+
+    // events that don't propagate only notify the entity
+    log.borrow_mut().clear();
+    world
+        .event()
+        .add::<Widget>()
+        .entity(button)
+        .emit(&Clicked { button: 3 });
+    assert_eq!(*log.borrow(), vec![(button_id, 3)]);
+}
+
+#[test]
+fn observer_propagate_nested_emit() {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    #[derive(Component)]
+    struct Widget;
+
+    let world = World::new();
+    let log = Rc::new(RefCell::new(Vec::new()));
+
+    let window = world.entity().add::<Widget>();
+    let button = world.entity().add::<Widget>().child_of_id(window);
+    let tooltip = world.entity().add::<Widget>();
+    let tooltip_id = tooltip.id();
+
+    let log_ref = log.clone();
+    world
+        .observer::<Clicked, ()>()
+        .with::<Widget>()
+        .each_iter(move |it, index, _| {
+            let button = it.param().button;
+            log_ref.borrow_mut().push((it.entity(index).id(), button));
+            match button {
+                // emits an event that doesn't propagate while the click propagates
+                1 => it
+                    .world()
+                    .event()
+                    .add::<Widget>()
+                    .entity(tooltip_id)
+                    .emit(&Clicked { button: 2 }),
+                2 => it.stop_propagation(),
+                _ => panic!("unexpected button"),
+            }
+        });
+
+    world
+        .event()
+        .add::<Widget>()
+        .entity(button)
+        .propagate::<flecs::ChildOf>()
+        .emit(&Clicked { button: 1 });
+    assert_eq!(
+        *log.borrow(),
+        vec![
+            (button.id(), 1),
+            (tooltip_id, 2),
+            (window.id(), 1),
+            (tooltip_id, 2)
+        ]
+    );
+
+    // a panicking observer doesn't leave the propagation of its event behind
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        world
+            .event()
+            .add::<Widget>()
+            .entity(button)
+            .propagate::<flecs::ChildOf>()
+            .emit(&Clicked { button: 3 });
+    }));
+    assert!(result.is_err());
+
+    log.borrow_mut().clear();
+    world
+        .event()
+        .add::<Widget>()
+        .entity(tooltip)
+        .emit(&Clicked { button: 2 });
+    world
+        .event()
+        .add::<Widget>()
+        .entity(button)
+        .propagate::<flecs::ChildOf>()
+        .emit(&Clicked { button: 1 });
+    assert_eq!(log.borrow().len(), 5);
+}
+
+#[test]
+fn observer_priority() {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    let world = World::new();
+    let log = Rc::new(RefCell::new(Vec::new()));
+
+    let mut observers = Vec::new();
+    for priority in [3, -2, 1, 2, -1, 0] {
+        let log_ref = log.clone();
+        observers.push(
+            world
+                .observer::<flecs::OnSet, &Position>()
+                .priority(priority)
+                .each(move |_| log_ref.borrow_mut().push(priority)),
+        );
+    }
+
+    // multi term observers are ordered relative to observers with the same query
+    let multi_log = Rc::new(RefCell::new(Vec::new()));
+    for priority in [20, 10] {
+        let log_ref = multi_log.clone();
+        let observer = world
+            .observer::<flecs::OnSet, (&Position, &Velocity)>()
+            .priority(priority)
+            .each(move |_| log_ref.borrow_mut().push(priority));
+        assert_eq!(observer.priority(), priority);
+    }
+
+    world
+        .entity()
+        .set(Velocity { x: 0, y: 0 })
+        .set(Position { x: 1, y: 2 });
+    assert_eq!(*log.borrow(), vec![-2, -1, 0, 1, 2, 3]);
+    assert_eq!(*multi_log.borrow(), vec![10, 20]);
+
+    log.borrow_mut().clear();
+    observers[0].set_priority(-3);
+    assert_eq!(observers[0].priority(), -3);
+    observers[1].destruct();
+    world.entity().set(Position { x: 1, y: 2 });
+    assert_eq!(*log.borrow(), vec![3, -1, 0, 1, 2]);
+}
+
+#[test]
+fn observer_before_after() {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    let world = World::new();
+    let log = Rc::new(RefCell::new(Vec::new()));
+
+    let log_ref = log.clone();
+    let a = world
+        .observer::<flecs::OnSet, &Position>()
+        .each(move |_| log_ref.borrow_mut().push("a"));
+
+    let log_ref = log.clone();
+    let b = world
+        .observer::<flecs::OnSet, &mut Position>()
+        .priority(-1)
+        .before(a)
+        .each(move |_| log_ref.borrow_mut().push("b"));
+
+    // after wins over the priority of the observer
+    let log_ref = log.clone();
+    world
+        .observer::<flecs::OnSet, &Position>()
+        .priority(-10)
+        .after(a)
+        .each(move |_| log_ref.borrow_mut().push("c"));
+
+    let log_ref = log.clone();
+    world
+        .observer::<flecs::OnSet, &Position>()
+        .after(b)
+        .before(a)
+        .each(move |_| log_ref.borrow_mut().push("d"));
+
+    world.entity().set(Position { x: 1, y: 2 });
+    assert_eq!(*log.borrow(), vec!["b", "d", "a", "c"]);
+
+    // an observer that is deleted no longer constrains the order
+    log.borrow_mut().clear();
+    a.destruct();
+    world.entity().set(Position { x: 1, y: 2 });
+    assert_eq!(*log.borrow(), vec!["c", "b", "d"]);
+}
+
+#[test]
+fn observer_order_cycle() {
+    use std::cell::RefCell;
+    use std::panic::AssertUnwindSafe;
+    use std::rc::Rc;
+
+    let world = World::new();
+    let log = Rc::new(RefCell::new(Vec::new()));
+
+    let log_ref = log.clone();
+    let a = world
+        .observer::<flecs::OnSet, &Position>()
+        .each(move |_| log_ref.borrow_mut().push("a"));
+
+    let log_ref = log.clone();
+    let b = world
+        .observer::<flecs::OnSet, &Position>()
+        .after(a)
+        .each(move |_| log_ref.borrow_mut().push("b"));
+
+    let count = world.count::<flecs::Observer>();
+    let result = std::panic::catch_unwind(AssertUnwindSafe(|| {
+        world
+            .observer_named::<flecs::OnSet, &Position>("Cycle")
+            .after(b)
+            .before(a)
+            .each(|_| {});
+    }));
+    assert!(result.is_err());
+
+    // the observer of the cycle is deleted and doesn't change the order
+    assert_eq!(world.count::<flecs::Observer>(), count);
+    assert!(world.try_lookup("Cycle").is_none());
+    world.entity().set(Position { x: 1, y: 2 });
+    assert_eq!(*log.borrow(), vec!["a", "b"]);
+}
+
+#[test]
+fn observer_order_other_query() {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    let world = World::new();
+    let log = Rc::new(RefCell::new(Vec::new()));
+
+    // the side effect is created first, but runs after the validation
+    let log_ref = log.clone();
+    world
+        .observer::<flecs::OnSet, (&Position, &Velocity)>()
+        .priority(1)
+        .each(move |(pos, _)| log_ref.borrow_mut().push(("effect", pos.x)));
+
+    let log_ref = log.clone();
+    world
+        .observer::<flecs::OnSet, &mut Position>()
+        .priority(-1)
+        .each(move |pos| {
+            pos.x = pos.x.min(10);
+            log_ref.borrow_mut().push(("validate", pos.x));
+        });
+
+    let e = world.entity().set(Velocity { x: 0, y: 0 });
+    e.set(Position { x: 20, y: 0 });
+    assert_eq!(*log.borrow(), vec![("validate", 10), ("effect", 10)]);
+
+    // the observers are only ordered for the event and component they have in common
+    log.borrow_mut().clear();
+    e.set(Velocity { x: 1, y: 0 });
+    assert_eq!(*log.borrow(), vec![("effect", 10)]);
+}
+
+#[test]
+#[should_panic(expected = "same event and component")]
+fn observer_order_other_component() {
+    let world = World::new();
+
+    let a = world.observer::<flecs::OnSet, &Position>().each(|_| {});
+
+    world
+        .observer::<flecs::OnSet, &Velocity>()
+        .after(a)
+        .each(|_| {});
+}
+
+#[test]
+fn observer_on_enter_on_leave() {
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    let world = World::new();
+    let entered = Rc::new(Cell::new(0));
+    let left = Rc::new(Cell::new(0));
+
+    let entered_ref = entered.clone();
+    world
+        .on_enter::<(&Position, &Velocity)>()
+        .without::<TagA>()
+        .with::<Mass>()
+        .parent()
+        .each(move |(pos, vel)| {
+            assert_eq!((pos.x, vel.x), (1, 0));
+            entered_ref.set(entered_ref.get() + 1);
+        });
+
+    let left_ref = left.clone();
+    world
+        .on_leave::<(&Position, &Velocity)>()
+        .without::<TagA>()
+        .with::<Mass>()
+        .parent()
+        .each_entity(move |_, _| left_ref.set(left_ref.get() + 1));
+
+    let parent = world.entity().set(Mass { value: 10 });
+    let e = world
+        .entity()
+        .child_of_id(parent)
+        .set(Position { x: 1, y: 0 });
+    assert_eq!((entered.get(), left.get()), (0, 0));
+
+    e.add::<Velocity>();
+    assert_eq!((entered.get(), left.get()), (1, 0));
+
+    // changes that don't affect whether the entity matches are ignored
+    e.set(Position { x: 1, y: 5 }).add::<TagB>();
+    assert_eq!((entered.get(), left.get()), (1, 0));
+
+    e.add::<TagA>();
+    assert_eq!((entered.get(), left.get()), (1, 1));
+
+    e.remove::<TagA>();
+    assert_eq!((entered.get(), left.get()), (2, 1));
+
+    e.destruct();
+    assert_eq!((entered.get(), left.get()), (2, 2));
+}
+
+#[test]
+#[should_panic(expected = "observer failed")]
+fn observer_panic_inside_component() {
+    let world = World::new();
+    world
+        .observer::<flecs::OnSet, &Position>()
+        .each(|_| panic!("observer failed"));
+    world.entity().set(Position { x: 1, y: 2 });
+}
+
+#[test]
+fn observer_panic_resumed_world_usable() {
+    let world = World::new();
+    let count = std::rc::Rc::new(std::cell::Cell::new(0));
+    let count_ref = count.clone();
+    world
+        .observer::<flecs::OnSet, &Position>()
+        .each(move |pos| {
+            if pos.x < 0 {
+                panic!("negative position");
+            }
+            count_ref.set(count_ref.get() + 1);
+        });
+
+    let e = world.entity();
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        e.set(Position { x: -1, y: 0 });
+    }));
+    let payload = result.unwrap_err();
+    assert_eq!(payload.downcast_ref::<&str>(), Some(&"negative position"));
+
+    // the panic doesn't leave the world in a broken state, and later callbacks run again
+    e.set(Position { x: 1, y: 0 });
+    world.entity().set(Position { x: 2, y: 0 });
+    assert_eq!(count.get(), 2);
+    e.get::<&Position>(|pos| assert_eq!(pos.x, 1));
+}
@@ -462,6 +462,89 @@ fn query_builder_set_var() {
     assert_eq!(count, 1);
 }
 
+#[test]
+fn query_builder_typed_vars() {
+    let world = World::new();
+
+    let apples = world.entity();
+    let pears = world.entity();
+
+    let alice = world.entity().add_first::<Eats>(apples);
+    let bob = world
+        .entity()
+        .add_first::<Eats>(pears)
+        .add_first::<Likes>(alice);
+    alice.add_first::<Likes>(bob);
+
+    let mut r = world
+        .query::<()>()
+        .with::<&Eats>()
+        .second()
+        .set_var("Food")
+        .with::<&Likes>()
+        .second()
+        .set_var("Person")
+        .build();
+
+    let food = r.var("Food");
+    let person = r.var("Person");
+    assert_eq!(food.index(), r.find_var("Food").unwrap());
+    assert!(r.try_var("Drink").is_none());
+
+    let names = r
+        .vars()
+        .into_iter()
+        .map(|(_, name)| name)
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["this", "Food", "Person"]);
+
+    let mut count = 0;
+    r.set_var(person, alice).each_iter(|it, index, ()| {
+        assert_eq!(it.entity(index), bob);
+        assert_eq!(it.get_var(food), pears);
+        assert_eq!(it.get_var(person), alice);
+        assert!(it.is_var_constrained(person));
+        assert!(!it.is_var_constrained(food));
+
+        let vars = it.vars().collect::<Vec<_>>();
+        assert_eq!(vars.len(), 3);
+        assert_eq!(vars[1].var, food);
+        assert_eq!(vars[1].name, "Food");
+        assert_eq!(vars[1].entity, Some(pears.entity_view(&world)));
+        assert!(!vars[1].is_constrained);
+        assert_eq!(vars[2].var, person);
+        assert!(vars[2].is_constrained);
+        count += 1;
+    });
+
+    assert_eq!(count, 1);
+}
+
+#[test]
+#[cfg(any(debug_assertions, feature = "flecs_force_enable_ecs_asserts"))]
+#[should_panic(expected = "query variable is used with a different query")]
+fn query_builder_typed_var_of_deleted_query() {
+    let world = World::new();
+
+    let query = world
+        .query::<()>()
+        .with::<&Eats>()
+        .second()
+        .set_var("Food")
+        .build();
+    let food = query.var("Food");
+    drop(query);
+
+    // the new query can be allocated where the deleted query was, the handle still doesn't match
+    let mut query = world
+        .query::<()>()
+        .with::<&Eats>()
+        .second()
+        .set_var("Food")
+        .build();
+    query.set_var(food, world.entity()).each(|_| {});
+}
+
 #[test]
 fn query_builder_set_2_vars() {
     let world = World::new();
@@ -2486,7 +2569,7 @@ fn query_builder_group_by_callbacks() {
             assert!(e2_found);
             assert!(e3_found);
             e1_found = true;
-            let ctx: *mut u64 = q.group_context(it.group_id()) as *mut u64;
+            let ctx: *mut u64 = q.group_context_ptr(it.group_id()) as *mut u64;
             assert_eq!(unsafe { *ctx }, it.group_id());
         }
         if e == e2 {
@@ -2495,7 +2578,7 @@ fn query_builder_group_by_callbacks() {
             assert!(!e2_found);
             assert!(e3_found);
             e2_found = true;
-            let ctx: *mut u64 = q.group_context(it.group_id()) as *mut u64;
+            let ctx: *mut u64 = q.group_context_ptr(it.group_id()) as *mut u64;
             assert_eq!(unsafe { *ctx }, it.group_id());
         }
         if e == e3 {
@@ -2504,7 +2587,7 @@ fn query_builder_group_by_callbacks() {
             assert!(!e2_found);
             assert!(!e3_found);
             e3_found = true;
-            let ctx: *mut u64 = q.group_context(it.group_id()) as *mut u64;
+            let ctx: *mut u64 = q.group_context_ptr(it.group_id()) as *mut u64;
             assert_eq!(unsafe { *ctx }, it.group_id());
         }
         count += 1;
@@ -2516,6 +2599,91 @@ fn query_builder_group_by_callbacks() {
     assert!(e3_found);
 }
 
+#[test]
+fn query_builder_group_by_closures() {
+    use std::rc::Rc;
+
+    struct GroupCtx {
+        group_id: u64,
+        deleted: Rc<Cell<u32>>,
+    }
+
+    impl Drop for GroupCtx {
+        fn drop(&mut self) {
+            self.deleted.set(self.deleted.get() + 1);
+        }
+    }
+
+    let world = World::new();
+
+    let tag = world.entity().id();
+    let position = world.component_id::<Position>();
+    let group_a = 10;
+    let group_b = 20;
+
+    let e1 = world.entity().set(Position { x: 1, y: 2 });
+    let e2 = world.entity().set(Position { x: 3, y: 4 }).add_id(tag);
+
+    let created = Rc::new(Cell::new(0));
+    let deleted = Rc::new(Cell::new(0));
+    let on_delete_count = Rc::new(Cell::new(0));
+
+    let q = world
+        .query::<&Position>()
+        .group_by_with::<Position>(move |table, id| {
+            assert_eq!(id, position);
+            if table.has_type_id(*tag) {
+                group_b
+            } else {
+                group_a
+            }
+        })
+        .on_group_create_with({
+            let created = created.clone();
+            let deleted = deleted.clone();
+            move |_, group_id| {
+                created.set(created.get() + 1);
+                GroupCtx {
+                    group_id,
+                    deleted: deleted.clone(),
+                }
+            }
+        })
+        .on_group_delete_with({
+            let on_delete_count = on_delete_count.clone();
+            move |_, group_id, ctx: GroupCtx| {
+                assert_eq!(ctx.group_id, group_id);
+                on_delete_count.set(on_delete_count.get() + 1);
+            }
+        })
+        .build();
+
+    assert_eq!(created.get(), 2);
+
+    let mut count = 0;
+    q.each_iter(|it, i, _| {
+        let ctx = q.group_context::<GroupCtx>(it.group_id()).unwrap();
+        assert_eq!(ctx.group_id, it.group_id());
+        if it.entity(i) == e1 {
+            assert_eq!(it.group_id(), group_a);
+        } else {
+            assert_eq!(it.entity(i), e2);
+            assert_eq!(it.group_id(), group_b);
+        }
+        count += 1;
+    });
+    assert_eq!(count, 2);
+
+    // wrong type or unknown group
+    assert!(q.group_context::<u64>(group_a).is_none());
+    assert!(q.group_context::<GroupCtx>(30).is_none());
+
+    q.destruct();
+
+    assert_eq!(on_delete_count.get(), 2);
+    assert_eq!(deleted.get(), 2);
+}
+
 #[test]
 fn query_builder_create_w_no_template_args() {
     let world = World::new();
@@ -2,6 +2,8 @@
 use flecs_ecs::core::*;
 use flecs_ecs::macros::*;
 
+use crate::common_test::*;
+
 #[test]
 fn query_uncached_destruction_no_panic() {
     #[derive(Component)]
@@ -69,3 +71,267 @@ fn query_panic_inside() {
         panic!();
     });
 }
+
+#[test]
+fn query_try_from_expr() {
+    let world = World::new();
+    world.component_named::<Position>("Position");
+    world.component_named::<Velocity>("Velocity");
+    world.component_named::<Tag>("Tag");
+
+    world
+        .entity()
+        .set(Position { x: 1, y: 2 })
+        .set(Velocity { x: 1, y: 1 });
+    world
+        .entity()
+        .set(Position { x: 3, y: 4 })
+        .set(Velocity { x: 1, y: 1 })
+        .add::<Tag>();
+
+    let query = world
+        .try_query_from_expr::<(&mut Position, &Velocity)>("Position, [in] Velocity, !Tag")
+        .unwrap();
+
+    let mut count = 0;
+    query.each(|(pos, vel)| {
+        pos.x += vel.x;
+        count += 1;
+    });
+
+    assert_eq!(count, 1);
+    assert_eq!(query.field_count(), 3);
+}
+
+#[test]
+fn query_try_from_expr_parse_error() {
+    let world = World::new();
+    world.component_named::<Position>("Position");
+
+    let err = world
+        .try_query_from_expr::<&Position>("Position, Unknown")
+        .err()
+        .unwrap();
+    assert_eq!(err.kind, QueryErrorKind::Parse);
+    assert_eq!(err.term_index, Some(1));
+    assert_eq!(err.column, Some(10));
+
+    let err = world
+        .try_query_from_expr::<&Position>("Position, (Position")
+        .err()
+        .unwrap();
+    assert_eq!(err.kind, QueryErrorKind::Parse);
+    assert_eq!(err.term_index, Some(1));
+}
+
+#[test]
+fn query_try_from_expr_mismatch() {
+    let world = World::new();
+    world.component_named::<Position>("Position");
+    world.component_named::<Velocity>("Velocity");
+
+    let err = world
+        .try_query_from_expr::<(&Position, &Velocity)>("Position")
+        .err()
+        .unwrap();
+    assert_eq!(err.kind, QueryErrorKind::MissingTerm);
+    assert_eq!(err.term_index, Some(1));
+
+    let err = world
+        .try_query_from_expr::<(&Position, &Velocity)>("Position, Position")
+        .err()
+        .unwrap();
+    assert_eq!(err.kind, QueryErrorKind::TypeMismatch);
+    assert_eq!(err.term_index, Some(1));
+    assert_eq!(err.column, Some(10));
+
+    let err = world
+        .try_query_from_expr::<&mut Position>("[in] Position")
+        .err()
+        .unwrap();
+    assert_eq!(err.kind, QueryErrorKind::AccessMismatch);
+    assert_eq!(err.term_index, Some(0));
+
+    let err = world
+        .try_query_from_expr::<&Position>("[none] Position")
+        .err()
+        .unwrap();
+    assert_eq!(err.kind, QueryErrorKind::AccessMismatch);
+
+    let err = world
+        .try_query_from_expr::<(&Position, Option<&Velocity>)>("Position, Velocity")
+        .err()
+        .unwrap();
+    assert_eq!(err.kind, QueryErrorKind::OperatorMismatch);
+    assert_eq!(err.term_index, Some(1));
+
+    assert!(world
+        .try_query_from_expr::<(&Position, Option<&Velocity>)>("Position, ?Velocity")
+        .is_ok());
+}
+
+#[test]
+fn query_changed_field() {
+    let world = World::new();
+
+    world
+        .entity()
+        .set(Position { x: 1, y: 2 })
+        .set(Velocity { x: 1, y: 1 });
+    world
+        .entity()
+        .set(Position { x: 3, y: 4 })
+        .set(Velocity { x: 1, y: 1 })
+        .add::<Tag>();
+
+    let changed = world.new_query::<(Changed<&Position>, &Velocity)>();
+
+    let count_changed = || {
+        let mut count = 0;
+        changed.each(|_| count += 1);
+        count
+    };
+
+    // everything is new the first time the query is iterated
+    assert_eq!(count_changed(), 2);
+    assert_eq!(count_changed(), 0);
+
+    // writing Position marks only the tables that were visited
+    world
+        .query::<&mut Position>()
+        .with::<Tag>()
+        .build()
+        .each(|p| p.x += 1);
+    assert_eq!(count_changed(), 1);
+    assert_eq!(count_changed(), 0);
+
+    // a change of a field that isn't wrapped in `Changed` is ignored
+    world.new_query::<&mut Velocity>().each(|v| v.x += 1);
+    assert_eq!(count_changed(), 0);
+}
+
+#[test]
+fn query_changed_skip_does_not_mark_written() {
+    let world = World::new();
+
+    world
+        .entity()
+        .set(Position { x: 1, y: 2 })
+        .set(Velocity { x: 1, y: 1 });
+
+    let integrate = world.new_query::<(Changed<&Position>, &mut Velocity)>();
+    let velocity_changed = world.new_query::<Changed<&Velocity>>();
+
+    let mut count = 0;
+    velocity_changed.each(|_| count += 1);
+    assert_eq!(count, 1);
+
+    integrate.each(|(_, v)| v.x += 1);
+
+    count = 0;
+    velocity_changed.each(|_| count += 1);
+    assert_eq!(count, 1);
+
+    // Position didn't change, so the table is skipped and Velocity isn't marked as written
+    integrate.each(|(_, v)| v.x += 1);
+
+    count = 0;
+    velocity_changed.each(|_| count += 1);
+    assert_eq!(count, 0);
+}
+
+#[test]
+fn query_changed_marks_visited_mut_fields() {
+    let world = World::new();
+
+    world.entity().set(Position { x: 1, y: 2 });
+
+    let changed = world.new_query::<Changed<&Position>>();
+    let count_changed = || {
+        let mut count = 0;
+        changed.each(|_| count += 1);
+        count
+    };
+    assert_eq!(count_changed(), 1);
+
+    // reading doesn't mark the field as written
+    world.new_query::<&Position>().each(|_| {});
+    assert_eq!(count_changed(), 0);
+
+    // writes aren't tracked, visiting the table with an `&mut` field marks it as written
+    world.new_query::<&mut Position>().each(|_| {});
+    assert_eq!(count_changed(), 1);
+}
+
+#[test]
+fn query_is_field_changed() {
+    let world = World::new();
+
+    let e = world
+        .entity()
+        .set(Position { x: 1, y: 2 })
+        .set(Velocity { x: 1, y: 1 });
+
+    let query = world.query::<(&Position, &Velocity)>().set_cached().build();
+
+    let fields_changed = || {
+        let mut changed = (false, false);
+        query.run(|mut it| {
+            while it.next() {
+                changed = (it.is_field_changed(0), it.is_field_changed(1));
+            }
+        });
+        changed
+    };
+
+    assert_eq!(fields_changed(), (true, true));
+    assert_eq!(fields_changed(), (false, false));
+
+    e.set(Velocity { x: 2, y: 2 });
+    assert_eq!(fields_changed(), (false, true));
+    assert_eq!(fields_changed(), (false, false));
+
+    e.set(Position { x: 2, y: 2 });
+    assert_eq!(fields_changed(), (true, false));
+}
+
+#[test]
+fn query_info() {
+    let world = World::new();
+
+    world.entity().set(Position { x: 1, y: 2 });
+    world.entity().set(Position { x: 3, y: 4 });
+    let e = world.entity().set(Position { x: 5, y: 6 }).add::<Tag>();
+
+    let cached = world.query::<&Position>().set_cached().build();
+    let mut uncached = world.new_query::<&Position>();
+
+    let info = cached.info();
+    assert!(info.is_cached);
+    // all terms are cacheable, so `Auto` is resolved to `All`
+    assert_eq!(info.cache_kind, QueryCacheKind::All);
+    assert_eq!(info.term_count, 1);
+    assert_eq!(info.field_count, 1);
+    assert_eq!(info.result_count, 2);
+    assert_eq!(info.entity_count, 3);
+    assert_eq!(info.table_count, 2);
+    assert_eq!(info.empty_table_count, 0);
+    assert!(info.cache_memory > 0);
+
+    e.destruct();
+
+    let info = cached.info();
+    assert_eq!(info.entity_count, 2);
+    assert_eq!(info.table_count, 1);
+    assert_eq!(info.empty_table_count, 1);
+
+    // the counts are read from the cache, without evaluating the query
+    assert_eq!(cached.info().eval_count, info.eval_count);
+
+    let info = uncached.info();
+    assert!(!info.is_cached);
+    assert_eq!(info.entity_count, 0);
+    assert_eq!(info.table_count, 0);
+    assert_eq!(info.cache_memory, 0);
+    assert_eq!(uncached.count(), 2);
+}
@@ -3029,6 +3029,117 @@ fn system_randomize_timers() {
     }
 }
 
+#[test]
+fn system_typed_context() {
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    struct Ctx {
+        value: i32,
+        drops: Rc<Cell<i32>>,
+    }
+
+    impl Drop for Ctx {
+        fn drop(&mut self) {
+            self.drops.set(self.drops.get() + 1);
+        }
+    }
+
+    let world = World::new();
+    let drops = Rc::new(Cell::new(0));
+    let sum = Rc::new(Cell::new(0));
+
+    world.entity().set(Position { x: 1, y: 2 });
+
+    let sum_ref = sum.clone();
+    let mut sys = world
+        .system::<&Position>()
+        .set_typed_context(Ctx {
+            value: 10,
+            drops: drops.clone(),
+        })
+        .each_iter(move |it, _, _| {
+            assert!(it.context::<i32>().is_none());
+            sum_ref.set(sum_ref.get() + it.context::<Ctx>().unwrap().value);
+        });
+
+    sys.run();
+    assert_eq!(sum.get(), 10);
+    assert_eq!(sys.typed_context::<Ctx>().unwrap().value, 10);
+    assert!(sys.typed_context::<i32>().is_none());
+
+    // replacing the context drops the previous one and keeps the callback intact
+    sys.set_typed_context(Ctx {
+        value: 20,
+        drops: drops.clone(),
+    });
+    assert_eq!(drops.get(), 1);
+
+    sys.run();
+    assert_eq!(sum.get(), 30);
+
+    sys.destruct();
+    assert_eq!(drops.get(), 2);
+}
+
+#[test]
+fn system_context_owns_query() {
+    let world = World::new();
+
+    world.entity().set(Position { x: 1, y: 2 });
+    world.entity().set(Position { x: 3, y: 4 });
+
+    let count = std::rc::Rc::new(std::cell::Cell::new(0));
+    let count_ref = count.clone();
+
+    let sys = world
+        .system::<()>()
+        .set_typed_context(world.new_query::<&Position>())
+        .run(move |mut it| {
+            while it.next() {}
+            let query = it.context::<Query<&Position>>().unwrap();
+            query.each(|_| count_ref.set(count_ref.get() + 1));
+        });
+
+    sys.run();
+    assert_eq!(count.get(), 2);
+
+    // the query is dropped together with the system, so no lingering query handle panic
+    drop(world);
+}
+
+#[test]
+fn system_context_mut() {
+    let world = World::new();
+
+    world.entity().set(Position { x: 1, y: 2 });
+    world.entity().set(Position { x: 3, y: 4 });
+
+    let sys = world
+        .system::<&Position>()
+        .set_typed_context(0i32)
+        .each_iter(|mut it, _, pos| {
+            assert!(unsafe { it.context_mut::<u32>() }.is_none());
+            *unsafe { it.context_mut::<i32>() }.unwrap() += pos.x;
+        });
+
+    sys.run();
+    sys.run();
+    assert_eq!(sys.typed_context::<i32>(), Some(&8));
+}
+
+#[test]
+#[allow(deprecated)]
+fn system_context_ptr_deprecated() {
+    let world = World::new();
+
+    let mut value = 42;
+    let mut sys = world.system::<()>().run(|_| {});
+    sys.set_context(&mut value as *mut i32 as *mut std::ffi::c_void);
+    assert_eq!(sys.context(), sys.context_ptr());
+    assert_eq!(sys.context_ptr() as *const i32, &value as *const i32);
+}
+
 // #[test] fn system_optional_pair_term() {
 //     let world = World::new();
 
@@ -6958,3 +7069,1072 @@ fn system_randomize_timers() {
 //                     assert_eq!(c.0,2);
 //                 });
 // }
+
+#[test]
+fn system_event_queue() {
+    use flecs_ecs::addons::event_queue::{EventReader, Events};
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    #[derive(Component)]
+    struct Damage(i32);
+
+    let world = World::new();
+    world.add_events::<Damage>();
+    let log = Rc::new(RefCell::new(Vec::new()));
+
+    // systems that send events in a later phase than the phase of the reader
+    world
+        .system::<&Position>()
+        .kind::<flecs::pipeline::PostUpdate>()
+        .each_iter(|it, _, p| it.world().event_writer().send(Damage(p.x)));
+
+    let mut reader = EventReader::<Damage>::new();
+    let log_ref = log.clone();
+    world
+        .system::<()>()
+        .kind::<flecs::pipeline::OnUpdate>()
+        .run(move |mut it| {
+            while it.next() {}
+            let mut frame = Vec::new();
+            reader.read(it.world(), |damage| frame.push(damage.0));
+            // events are only read once
+            assert_eq!(reader.read(it.world(), |_| {}), 0);
+            log_ref.borrow_mut().push(frame);
+        });
+
+    world.entity().set(Position { x: 1, y: 0 });
+    world.event_writer().send(Damage(10));
+
+    world.progress();
+    world.entity().set(Position { x: 2, y: 0 });
+    world.progress();
+    world.progress();
+
+    assert_eq!(*log.borrow(), vec![vec![10], vec![1], vec![1, 2]]);
+
+    world.get::<&Events<Damage>>(|events| assert_eq!(events.len(), 2));
+}
+
+#[test]
+fn system_pipeline_schedule() {
+    use flecs_ecs::addons::pipeline::SyncReason;
+
+    let world = World::new();
+    // systems without matching entities are inactive and not part of the schedule
+    world
+        .entity()
+        .set(Position { x: 0, y: 0 })
+        .set(Velocity { x: 1, y: 1 });
+
+    let spawn = world
+        .system_named::<()>("Spawn")
+        .write::<Position>()
+        .run(|mut it| while it.next() {});
+
+    let move_ = world
+        .system_named::<(&mut Position, &Velocity)>("Move")
+        .each(|_| {});
+
+    let log = world
+        .system_named::<&Velocity>("Log")
+        .immediate(true)
+        .each(|_| {});
+
+    let parallel = world
+        .system_named::<&Position>("Parallel")
+        .multi_threaded()
+        .each(|_| {});
+
+    let schedule = world.get_pipeline_schedule();
+    let systems: Vec<Entity> = schedule.systems().map(|s| s.id()).collect();
+    assert_eq!(
+        systems,
+        vec![spawn.id(), move_.id(), log.id(), parallel.id()]
+    );
+
+    let batches = schedule.batches();
+    assert_eq!(batches.len(), 4);
+    assert!(batches[0].sync_point.is_none());
+
+    let sync = batches[1].sync_point.unwrap();
+    assert_eq!(sync.system, move_.id());
+    assert!(
+        matches!(sync.reason, SyncReason::ComponentWrite(id) if id == world.component_id::<Position>())
+    );
+
+    assert!(batches[2].immediate);
+    let sync = batches[2].sync_point.unwrap();
+    assert_eq!(sync.system, log.id());
+    assert!(matches!(sync.reason, SyncReason::Immediate));
+
+    assert!(batches[3].multi_threaded);
+    let sync = batches[3].sync_point.unwrap();
+    assert_eq!(sync.system, parallel.id());
+    assert!(matches!(sync.reason, SyncReason::Threading));
+
+    let dot = schedule.to_dot();
+    assert!(dot.starts_with("digraph pipeline {"));
+    assert!(dot.contains(&format!("system{} [label=\"Spawn\"];", spawn.id())));
+    assert!(dot.contains("Position written"));
+    assert_eq!(dot.matches("shape=octagon").count(), 3);
+}
+
+#[test]
+fn system_before_after_in_set() {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    let world = World::new();
+    let order = Rc::new(RefCell::new(Vec::new()));
+    let physics = world.entity_named("Physics");
+
+    let system = |name: &'static str| {
+        let order = order.clone();
+        move |mut it: TableIter| {
+            while it.next() {}
+            order.borrow_mut().push(name);
+        }
+    };
+
+    world
+        .system_named::<()>("Render")
+        .after(physics)
+        .run(system("Render"));
+    world
+        .system_named::<()>("Collide")
+        .in_set(physics)
+        .run(system("Collide"));
+    let integrate = world
+        .system_named::<()>("Integrate")
+        .in_set(physics)
+        .before(world.lookup("Collide"))
+        .run(system("Integrate"));
+    world
+        .system_named::<()>("Move")
+        .before(physics)
+        .run(system("Move"));
+    world
+        .system_named::<()>("Input")
+        .before(integrate)
+        .run(system("Input"));
+
+    world.progress();
+    assert_eq!(
+        *order.borrow(),
+        vec!["Move", "Input", "Integrate", "Collide", "Render"]
+    );
+
+    // unconstrained systems keep their position in creation order
+    world.system_named::<()>("Audio").run(system("Audio"));
+    world
+        .system_named::<()>("Prepare")
+        .before(world.lookup("Input"))
+        .run(system("Prepare"));
+
+    order.borrow_mut().clear();
+    world.progress();
+    assert_eq!(
+        *order.borrow(),
+        vec![
+            "Move",
+            "Prepare",
+            "Input",
+            "Integrate",
+            "Collide",
+            "Audio",
+            "Render"
+        ]
+    );
+}
+
+#[test]
+#[should_panic(expected = "system ordering constraints contain a cycle: A -> B -> C -> A")]
+fn system_order_cycle() {
+    let world = World::new();
+    let a = world
+        .system_named::<()>("A")
+        .run(|mut it| while it.next() {});
+    let b = world
+        .system_named::<()>("B")
+        .after(a)
+        .run(|mut it| while it.next() {});
+    let c = world
+        .system_named::<()>("C")
+        .after(b)
+        .run(|mut it| while it.next() {});
+    world
+        .system_named::<()>("A")
+        .after(c)
+        .run(|mut it| while it.next() {});
+}
+
+#[test]
+fn system_order_cycle_rejected() {
+    let world = World::new();
+    let a = world
+        .system_named::<()>("A")
+        .run(|mut it| while it.next() {});
+    let b = world
+        .system_named::<()>("B")
+        .after(a)
+        .run(|mut it| while it.next() {});
+    let systems = world.count::<flecs::system::System>();
+
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        world
+            .system_named::<()>("C")
+            .after(b)
+            .before(a)
+            .run(|mut it| while it.next() {});
+    }));
+    assert!(result.is_err());
+
+    // the rejected system isn't created, and the world can still progress
+    assert!(world.try_lookup("C").is_none());
+    assert_eq!(world.count::<flecs::system::System>(), systems);
+    world.progress();
+}
+
+#[test]
+fn system_run_if() {
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    #[derive(Component)]
+    struct Paused;
+
+    let world = World::new();
+    let ran = Rc::new(Cell::new(0));
+    let evaluated = Rc::new(Cell::new(0));
+
+    let system = world
+        .system::<()>()
+        .run_if({
+            let evaluated = evaluated.clone();
+            move |world| {
+                evaluated.set(evaluated.get() + 1);
+                !world.has::<Paused>()
+            }
+        })
+        .run({
+            let ran = ran.clone();
+            move |mut it| {
+                while it.next() {}
+                ran.set(ran.get() + 1);
+            }
+        });
+
+    world.progress();
+    assert_eq!(ran.get(), 1);
+    assert_eq!(evaluated.get(), 1);
+
+    world.add::<Paused>();
+    world.progress();
+    assert_eq!(ran.get(), 1);
+    assert_eq!(evaluated.get(), 2);
+
+    // conditions are also evaluated when running the system manually
+    system.run();
+    assert_eq!(ran.get(), 1);
+
+    world.remove::<Paused>();
+    system.run();
+    assert_eq!(ran.get(), 2);
+}
+
+#[test]
+fn system_run_if_run_worker() {
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    let world = World::new();
+    let ran = Rc::new(Cell::new(0));
+    let evaluated = Rc::new(Cell::new(0));
+
+    let system = world
+        .system::<()>()
+        .run_if({
+            let evaluated = evaluated.clone();
+            move |_| {
+                evaluated.set(evaluated.get() + 1);
+                evaluated.get() == 1
+            }
+        })
+        .run({
+            let ran = ran.clone();
+            move |mut it| {
+                while it.next() {}
+                ran.set(ran.get() + 1);
+            }
+        });
+
+    // workers use the result of the last evaluation
+    world.progress();
+    system.run_worker(0, 2, 0.0, std::ptr::null_mut());
+    system.run_worker(1, 2, 0.0, std::ptr::null_mut());
+    assert_eq!(evaluated.get(), 1);
+    assert_eq!(ran.get(), 3);
+
+    system.run();
+    system.run_worker(0, 1, 0.0, std::ptr::null_mut());
+    assert_eq!(evaluated.get(), 2);
+    assert_eq!(ran.get(), 3);
+}
+
+#[test]
+fn system_run_if_multi_threaded() {
+    use std::sync::atomic::{AtomicI32, Ordering};
+    use std::sync::Arc;
+
+    let world = World::new();
+    world.set_threads(4);
+    for _ in 0..10 {
+        world.entity().set(Position { x: 0, y: 0 });
+    }
+
+    let evaluated = Arc::new(AtomicI32::new(0));
+    world
+        .system::<&mut Position>()
+        .multi_threaded()
+        .run_if({
+            let evaluated = evaluated.clone();
+            move |_| evaluated.fetch_add(1, Ordering::SeqCst) % 2 == 0
+        })
+        .each(|pos| pos.x += 1);
+
+    let sum = |world: &World| {
+        let mut sum = 0;
+        world.each::<&Position>(|pos| sum += pos.x);
+        sum
+    };
+
+    world.progress();
+    assert_eq!(evaluated.load(Ordering::SeqCst), 1);
+    assert_eq!(sum(&world), 10);
+
+    world.progress();
+    assert_eq!(evaluated.load(Ordering::SeqCst), 2);
+    assert_eq!(sum(&world), 10);
+
+    world.progress();
+    assert_eq!(sum(&world), 20);
+}
+
+#[test]
+fn system_run_if_any() {
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    #[derive(Component)]
+    struct NavDirty;
+
+    let world = World::new();
+    let ran = Rc::new(Cell::new(0));
+
+    world.system::<()>().run_if_any::<&NavDirty>().run({
+        let ran = ran.clone();
+        move |mut it| {
+            while it.next() {}
+            ran.set(ran.get() + 1);
+        }
+    });
+
+    world.progress();
+    assert_eq!(ran.get(), 0);
+
+    world.add::<NavDirty>();
+    world.progress();
+    world.progress();
+    assert_eq!(ran.get(), 2);
+
+    world.remove::<NavDirty>();
+    world.progress();
+    assert_eq!(ran.get(), 2);
+}
+
+#[test]
+fn system_run_if_resource_changed() {
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    #[derive(Component)]
+    struct Settings {
+        value: i32,
+    }
+
+    let world = World::new();
+    let ran = Rc::new(Cell::new(0));
+
+    world
+        .system::<()>()
+        .run_if_resource_changed::<Settings>()
+        .run({
+            let ran = ran.clone();
+            move |mut it| {
+                while it.next() {}
+                ran.set(ran.get() + 1);
+            }
+        });
+
+    world.progress();
+    assert_eq!(ran.get(), 0);
+
+    world.set(Settings { value: 1 });
+    world.progress();
+    assert_eq!(ran.get(), 1);
+
+    world.progress();
+    assert_eq!(ran.get(), 1);
+
+    world.set(Settings { value: 2 });
+    world.progress();
+    world.progress();
+    assert_eq!(ran.get(), 2);
+
+    // an entity that adds a component moves the singleton to a different table
+    world.singleton::<Settings>().add::<Position>();
+    world.progress();
+    assert_eq!(ran.get(), 3);
+    world.get::<&Settings>(|s| assert_eq!(s.value, 2));
+}
+
+#[test]
+fn system_fixed_timestep() {
+    use flecs_ecs::addons::pipeline::FixedUpdate;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    let world = World::new();
+    let log = Rc::new(RefCell::new(Vec::new()));
+
+    world.system::<()>().kind::<FixedUpdate>().run({
+        let log = log.clone();
+        move |mut it| {
+            while it.next() {}
+            log.borrow_mut().push(format!("fixed {}", it.delta_time()));
+        }
+    });
+
+    world
+        .system::<()>()
+        .kind::<flecs::pipeline::OnStore>()
+        .run({
+            let log = log.clone();
+            move |mut it| {
+                while it.next() {}
+                log.borrow_mut()
+                    .push(format!("render {:.1}", it.interpolation_alpha()));
+            }
+        });
+
+    // fixed update systems don't run without a fixed timestep
+    world.progress_time(1.0);
+    assert_eq!(*log.borrow(), vec!["render 0.0"]);
+    assert!(world.get_fixed_timestep().abs() < f32::EPSILON);
+
+    world.set_fixed_timestep(0.25, 3);
+    assert!((world.get_fixed_timestep() - 0.25).abs() < f32::EPSILON);
+
+    log.borrow_mut().clear();
+    world.progress_time(0.6);
+    assert_eq!(
+        *log.borrow(),
+        vec!["fixed 0.25", "fixed 0.25", "render 0.4"]
+    );
+    assert_eq!(world.get_fixed_timestep_steps(), 2);
+
+    log.borrow_mut().clear();
+    world.progress_time(0.1);
+    assert_eq!(*log.borrow(), vec!["render 0.8"]);
+    assert_eq!(world.get_fixed_timestep_steps(), 0);
+
+    // no more than 3 steps run per frame, the rest of the time is dropped
+    log.borrow_mut().clear();
+    world.progress_time(2.0);
+    assert_eq!(
+        *log.borrow(),
+        vec!["fixed 0.25", "fixed 0.25", "fixed 0.25", "render 0.8"]
+    );
+    assert!((world.get_fixed_timestep_alpha() - 0.8).abs() < 0.001);
+
+    world.set_fixed_timestep(0.0, 1);
+    log.borrow_mut().clear();
+    world.progress_time(1.0);
+    assert_eq!(*log.borrow(), vec!["render 0.0"]);
+}
+
+#[test]
+fn system_profiler() {
+    use flecs_ecs::addons::pipeline::SystemProfileKind;
+    use std::sync::{Arc, Mutex};
+
+    let world = World::new();
+    world.entity().set(Position { x: 0, y: 0 });
+    world.entity().set(Position { x: 0, y: 0 });
+
+    world
+        .system_named::<&Position>("Move")
+        .kind::<flecs::pipeline::OnUpdate>()
+        .each(|_| {});
+    world
+        .system_named::<&Position>("Render")
+        .kind::<flecs::pipeline::OnStore>()
+        .run(|mut it| while it.next() {});
+    world
+        .system_named::<()>("Input")
+        .kind::<flecs::pipeline::OnLoad>()
+        .run(|mut it| while it.next() {});
+
+    let log = Arc::new(Mutex::new(Vec::new()));
+    world.set_system_profiler({
+        let log = log.clone();
+        move |profile| {
+            assert_eq!(profile.stage_id, 0);
+            if profile.kind == SystemProfileKind::Begin {
+                assert_eq!(profile.elapsed, std::time::Duration::ZERO);
+            }
+            log.lock().unwrap().push(format!(
+                "{:?} {} {}",
+                profile.kind,
+                profile.system.name(),
+                profile.entity_count
+            ));
+        }
+    });
+
+    world.progress();
+    assert!(log.lock().unwrap().is_empty());
+    assert!(!world.is_system_profiler_enabled());
+
+    world.enable_system_profiler(true);
+    assert!(world.is_system_profiler_enabled());
+    world.progress();
+    assert_eq!(
+        *log.lock().unwrap(),
+        vec![
+            "Begin Input 0",
+            "End Input 0",
+            "Begin Move 0",
+            "End Move 2",
+            "Begin Render 0",
+            "End Render 2"
+        ]
+    );
+
+    world.enable_system_profiler(false);
+    log.lock().unwrap().clear();
+    world.progress();
+    assert!(log.lock().unwrap().is_empty());
+}
+
+#[test]
+fn system_try_each_log() {
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    let world = World::new();
+    world.entity().set(Position { x: 1, y: 0 });
+    world.entity().set(Position { x: -1, y: 0 });
+    world.entity().set(Position { x: 2, y: 0 });
+
+    let count = Rc::new(Cell::new(0));
+    let system = world.system::<&Position>().try_each({
+        let count = count.clone();
+        move |pos| {
+            count.set(count.get() + 1);
+            if pos.x < 0 {
+                return Err("negative position");
+            }
+            Ok(())
+        }
+    });
+
+    // the callback isn't invoked for the remaining entities after an error
+    assert!(world.progress());
+    assert_eq!(count.get(), 2);
+
+    // the system keeps running with the default policy
+    assert!(world.progress());
+    assert_eq!(count.get(), 4);
+    assert!(!system.has::<flecs::Disabled>());
+}
+
+#[test]
+fn system_try_each_entity_disable() {
+    use flecs_ecs::addons::system::SystemErrorPolicy;
+
+    let world = World::new();
+    world.set_system_error_policy(SystemErrorPolicy::Disable);
+    assert_eq!(world.get_system_error_policy(), SystemErrorPolicy::Disable);
+
+    let e = world.entity().set(Position { x: 0, y: 0 });
+    let system = world
+        .system::<&mut Position>()
+        .try_each_entity(move |entity, pos| {
+            pos.x += 1;
+            if pos.x == 2 {
+                return Err(format!("{} moved too far", entity.id()));
+            }
+            Ok(())
+        });
+
+    world.progress();
+    world.progress();
+    assert!(system.has::<flecs::Disabled>());
+
+    world.progress();
+    e.get::<&Position>(|pos| assert_eq!(pos.x, 2));
+}
+
+#[test]
+fn system_try_run_stop() {
+    use flecs_ecs::addons::system::SystemErrorPolicy;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    let world = World::new();
+    let ran = Rc::new(Cell::new(0));
+
+    world
+        .system::<()>()
+        .on_error(SystemErrorPolicy::Stop)
+        .try_run(|mut it| {
+            while it.next() {}
+            Err(std::io::Error::new(std::io::ErrorKind::NotFound, "file"))
+        });
+
+    world.system::<()>().run({
+        let ran = ran.clone();
+        move |mut it| {
+            while it.next() {}
+            ran.set(ran.get() + 1);
+        }
+    });
+
+    assert!(!world.progress());
+    assert_eq!(ran.get(), 0);
+    assert!(world.should_quit());
+}
+
+#[test]
+fn system_try_progress() {
+    use flecs_ecs::addons::system::SystemErrorPolicy;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    let world = World::new();
+    let fail = Rc::new(Cell::new(true));
+    let ran = Rc::new(Cell::new(0));
+
+    let failing = world
+        .system_named::<()>("Load")
+        .on_error(SystemErrorPolicy::Propagate)
+        .try_run({
+            let fail = fail.clone();
+            move |mut it| {
+                while it.next() {}
+                if fail.get() {
+                    return Err("bad asset reference");
+                }
+                Ok(())
+            }
+        });
+
+    world.system::<()>().run({
+        let ran = ran.clone();
+        move |mut it| {
+            while it.next() {}
+            ran.set(ran.get() + 1);
+        }
+    });
+
+    let error = world.try_progress().unwrap_err();
+    assert_eq!(error.system(), failing.id());
+    assert_eq!(error.error().to_string(), "bad asset reference");
+    assert_eq!(error.to_string(), "system Load failed: bad asset reference");
+    assert_eq!(ran.get(), 0);
+
+    // the error is logged by progress
+    assert!(world.progress());
+    assert_eq!(ran.get(), 0);
+
+    fail.set(false);
+    assert!(world.try_progress().unwrap());
+    assert_eq!(ran.get(), 1);
+}
+
+#[test]
+fn system_panic_resumed_after_progress() {
+    let world = World::new();
+    let fail = std::rc::Rc::new(std::cell::Cell::new(true));
+    let fail_ref = fail.clone();
+
+    world.system::<&mut Position>().each_entity(move |e, pos| {
+        if fail_ref.get() {
+            panic!("system failed");
+        }
+        pos.x += 1;
+        e.add::<TagA>();
+    });
+
+    let e = world.entity().set(Position { x: 0, y: 0 });
+
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| world.progress()));
+    let payload = result.unwrap_err();
+    assert_eq!(payload.downcast_ref::<&str>(), Some(&"system failed"));
+    assert!(!world.is_deferred());
+
+    fail.set(false);
+    world.progress();
+    assert!(e.has::<TagA>());
+    e.get::<&Position>(|pos| assert_eq!(pos.x, 1));
+}
+
+#[test]
+fn system_run_panic_resumed() {
+    let world = World::new();
+    world.entity().set(Position { x: 0, y: 0 });
+    world.entity().set(Position { x: 1, y: 0 });
+
+    let sys = world.system::<&Position>().run(|mut it| {
+        if it.next() {
+            panic!("run failed");
+        }
+    });
+
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        sys.run();
+    }));
+    assert!(result.is_err());
+
+    // the interrupted iteration was finished, so the table can be modified again
+    world.entity().set(Position { x: 2, y: 0 });
+    assert_eq!(world.new_query::<&Position>().count(), 3);
+}
+
+#[test]
+fn system_pipeline_runner_phases() {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    let world = World::new();
+    let log = Rc::new(RefCell::new(Vec::new()));
+
+    let add_system = |name: &'static str, phase: Entity| {
+        let log = log.clone();
+        world
+            .system_named::<()>(name)
+            .kind_id(phase)
+            .run(move |mut it| {
+                while it.next() {}
+                log.borrow_mut().push(name);
+            });
+    };
+    add_system("Store", world.component_id::<flecs::pipeline::OnStore>());
+    add_system("Load", world.component_id::<flecs::pipeline::OnLoad>());
+    add_system("Update", world.component_id::<flecs::pipeline::OnUpdate>());
+    add_system(
+        "Validate",
+        world.component_id::<flecs::pipeline::OnValidate>(),
+    );
+
+    world
+        .pipeline_runner_id(0)
+        .until::<flecs::pipeline::OnUpdate>()
+        .run(1.0);
+    assert_eq!(*log.borrow(), vec!["Load", "Update"]);
+
+    log.borrow_mut().clear();
+    world
+        .pipeline_runner_id(0)
+        .from::<flecs::pipeline::OnValidate>()
+        .run(1.0);
+    assert_eq!(*log.borrow(), vec!["Validate", "Store"]);
+
+    // step through the pipeline one phase at a time
+    log.borrow_mut().clear();
+    let runner = world.pipeline_runner_id(0);
+    let phases = runner.phases();
+    assert_eq!(phases.len(), 4);
+    for (index, phase) in phases.iter().enumerate() {
+        runner.clone().phase_id(*phase).run(1.0);
+        assert_eq!(log.borrow().len(), index + 1);
+    }
+    assert_eq!(*log.borrow(), vec!["Load", "Update", "Validate", "Store"]);
+}
+
+#[test]
+fn system_pipeline_runner_with_tag() {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    let world = World::new();
+    let log = Rc::new(RefCell::new(Vec::new()));
+
+    let add_system = |name: &'static str, tagged: bool| {
+        let log = log.clone();
+        let system = world
+            .system_named::<()>(name)
+            .kind::<flecs::pipeline::OnUpdate>()
+            .run(move |mut it| {
+                while it.next() {}
+                log.borrow_mut().push(name);
+            });
+        if tagged {
+            system.add::<TagA>();
+        }
+    };
+    add_system("Editor", true);
+    add_system("Game", false);
+    add_system("Gizmos", true);
+
+    let runner = world.pipeline_runner_id(0).with_tag::<TagA>().clone();
+    let names: Vec<&str> = runner.systems().iter().map(|s| s.name()).collect();
+    assert_eq!(names, vec!["Editor", "Gizmos"]);
+
+    runner.run(1.0);
+    assert_eq!(*log.borrow(), vec!["Editor", "Gizmos"]);
+
+    log.borrow_mut().clear();
+    world
+        .pipeline_runner_id(0)
+        .with_tag::<TagA>()
+        .with_tag::<TagB>()
+        .run(1.0);
+    assert!(log.borrow().is_empty());
+
+    world.progress_time(1.0);
+    assert_eq!(*log.borrow(), vec!["Editor", "Game", "Gizmos"]);
+
+    // systems can also be selected from a custom pipeline
+    world
+        .pipeline_type::<PipelineType>()
+        .with::<flecs::system::System>()
+        .with::<TagA>()
+        .build();
+
+    log.borrow_mut().clear();
+    world
+        .pipeline_runner::<PipelineType>()
+        .until::<flecs::pipeline::OnUpdate>()
+        .run(1.0);
+    assert_eq!(*log.borrow(), vec!["Editor", "Gizmos"]);
+}
+
+#[test]
+fn system_pipeline_runner_frame() {
+    use flecs_ecs::addons::system::SystemErrorPolicy;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    let world = World::new();
+    let ran = Rc::new(Cell::new(0));
+
+    world
+        .system::<()>()
+        .kind::<flecs::pipeline::OnLoad>()
+        .run_if(|_| false)
+        .run(|_| panic!("the run condition is false"));
+
+    let fail = Rc::new(Cell::new(false));
+    world
+        .system::<()>()
+        .on_error(SystemErrorPolicy::Stop)
+        .try_run({
+            let fail = fail.clone();
+            move |mut it| {
+                while it.next() {}
+                if fail.get() {
+                    return Err(std::io::Error::new(std::io::ErrorKind::NotFound, "file"));
+                }
+                Ok(())
+            }
+        });
+
+    world.system::<()>().run({
+        let ran = ran.clone();
+        move |mut it| {
+            while it.next() {}
+            assert_eq!(it.delta_time(), 0.5);
+            ran.set(ran.get() + 1);
+        }
+    });
+
+    // the runner runs a frame, which updates the time of the world
+    world.pipeline_runner_id(0).run(0.5);
+    assert_eq!(ran.get(), 1);
+    assert_eq!(world.info().delta_time, 0.5);
+
+    // a system error stops the systems after it
+    fail.set(true);
+    world.pipeline_runner_id(0).run(0.5);
+    assert_eq!(ran.get(), 1);
+    assert!(world.should_quit());
+}
+
+#[test]
+fn system_set_callback() {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    let world = World::new();
+    let entity = world.entity().set(Position { x: 0, y: 0 });
+    let log = Rc::new(RefCell::new(Vec::new()));
+
+    let mut system = world.system_named::<&mut Position>("Move").each({
+        let log = log.clone();
+        move |p| {
+            p.x += 1;
+            log.borrow_mut().push("Move");
+        }
+    });
+    world.system_named::<()>("Render").run({
+        let log = log.clone();
+        move |mut it| {
+            while it.next() {}
+            log.borrow_mut().push("Render");
+        }
+    });
+
+    world.progress();
+    assert_eq!(*log.borrow(), vec!["Move", "Render"]);
+    assert_eq!(Rc::strong_count(&log), 3);
+
+    // the old closure is dropped, the system keeps its position in the pipeline
+    system.set_callback::<&mut Position, _>({
+        let log = log.clone();
+        move |p| {
+            p.x += 10;
+            log.borrow_mut().push("Move2");
+        }
+    });
+    assert_eq!(Rc::strong_count(&log), 3);
+
+    log.borrow_mut().clear();
+    world.progress();
+    assert_eq!(*log.borrow(), vec!["Move2", "Render"]);
+    entity.get::<&Position>(|p| assert_eq!(p.x, 11));
+
+    let entity_id = entity.id();
+    system.set_callback_entity::<&mut Position, _>(move |e, p| {
+        assert_eq!(e, entity_id);
+        p.x += 100;
+    });
+    world.progress();
+    entity.get::<&Position>(|p| assert_eq!(p.x, 111));
+
+    assert_eq!(system.name(), "Move");
+    assert_eq!(world.lookup("Move"), *system);
+}
+
+#[test]
+fn system_set_run() {
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    let world = World::new();
+    world.entity().set(Position { x: 0, y: 0 });
+    world.entity().set(Position { x: 0, y: 0 });
+
+    let count = Rc::new(Cell::new(0));
+    let mut system = world.system::<&Position>().each({
+        let count = count.clone();
+        move |_| count.set(count.get() + 1)
+    });
+
+    system.run();
+    assert_eq!(count.get(), 2);
+
+    system.set_run({
+        let count = count.clone();
+        move |mut it| {
+            while it.next() {
+                count.set(count.get() + 10 * it.count());
+            }
+        }
+    });
+    system.run();
+    assert_eq!(count.get(), 22);
+
+    // replacing the run callback with an each callback removes the run callback
+    system.set_callback::<&Position, _>({
+        let count = count.clone();
+        move |_| count.set(count.get() + 100)
+    });
+    system.run();
+    assert_eq!(count.get(), 222);
+    assert_eq!(Rc::strong_count(&count), 2);
+
+    system.destruct();
+    assert_eq!(Rc::strong_count(&count), 1);
+}
+
+#[test]
+fn system_set_callback_keeps_run_if() {
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    let world = World::new();
+    world.entity().set(Position { x: 0, y: 0 });
+
+    let enabled = Rc::new(Cell::new(false));
+    let count = Rc::new(Cell::new(0));
+    let mut system = world
+        .system::<&Position>()
+        .run_if({
+            let enabled = enabled.clone();
+            move |_| enabled.get()
+        })
+        .each(|_| {});
+
+    system.set_callback::<&Position, _>({
+        let count = count.clone();
+        move |_| count.set(count.get() + 1)
+    });
+    world.progress();
+    assert_eq!(count.get(), 0);
+
+    system.set_run({
+        let count = count.clone();
+        move |mut it| {
+            while it.next() {
+                count.set(count.get() + 10);
+            }
+        }
+    });
+    world.progress();
+    assert_eq!(count.get(), 0);
+
+    enabled.set(true);
+    world.progress();
+    assert_eq!(count.get(), 10);
+    assert_eq!(Rc::strong_count(&count), 2);
+}
+
+#[test]
+#[should_panic(expected = "the components of the callback don't match the query of the system")]
+fn system_set_callback_mismatched_components() {
+    let world = World::new();
+
+    let mut system = world.system::<&Position>().each(|_| {});
+    system.set_callback::<&Velocity, _>(|_| {});
+}
+
+#[test]
+#[should_panic(expected = "the components of the callback don't match the query of the system")]
+fn system_set_callback_mismatched_access() {
+    let world = World::new();
+
+    let mut system = world.system::<&Position>().each(|_| {});
+    system.set_callback::<&mut Position, _>(|_| {});
+}
+
+#[test]
+#[should_panic(expected = "the components of the callback don't match the query of the system")]
+fn system_set_callback_mismatched_optional() {
+    let world = World::new();
+
+    let mut system = world.system::<Option<&Position>>().each(|_| {});
+    system.set_callback::<&Position, _>(|_| {});
+}
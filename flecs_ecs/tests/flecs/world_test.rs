@@ -1,5 +1,5 @@
 #![allow(dead_code)]
-use flecs_ecs::core::World;
+use flecs_ecs::core::*;
 
 #[test]
 fn world_no_panic_clone_test() {
@@ -25,3 +25,119 @@ fn world_panic_lingering_query_handles() {
     let _query = world.new_query::<()>();
     std::mem::drop(world);
 }
+
+#[test]
+fn world_typed_context() {
+    use std::cell::Cell;
+    use std::rc::Rc;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+
+    struct Ctx(Arc<AtomicBool>);
+
+    impl Drop for Ctx {
+        fn drop(&mut self) {
+            self.0.store(true, Ordering::Relaxed);
+        }
+    }
+
+    let dropped = Arc::new(AtomicBool::new(false));
+
+    let mut world = World::new();
+    assert!(world.typed_context::<Ctx>().is_none());
+
+    world.set_typed_context(Ctx(dropped.clone()));
+    assert!(world.typed_context::<Ctx>().is_some());
+    assert!(world.typed_context::<i32>().is_none());
+
+    let found = Rc::new(Cell::new(false));
+    let found_ref = found.clone();
+    world.system::<()>().run(move |mut it| {
+        while it.next() {}
+        found_ref.set(it.world().typed_context::<Ctx>().is_some());
+    });
+    world.progress();
+    assert!(found.get());
+
+    drop(world);
+    assert!(dropped.load(Ordering::Relaxed));
+}
+
+#[test]
+fn world_app_closures() {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    let world = World::new();
+    let log = Rc::new(RefCell::new(Vec::new()));
+
+    let log_ref = log.clone();
+    world.system::<()>().run(move |mut it| {
+        while it.next() {}
+        log_ref.borrow_mut().push("system");
+    });
+
+    let code = world
+        .app()
+        .set_frames(2)
+        .on_init(|_| log.borrow_mut().push("init"))
+        .on_frame(|_| log.borrow_mut().push("frame"))
+        .on_shutdown(|world| {
+            assert!(world.should_quit());
+            log.borrow_mut().push("shutdown");
+        })
+        .run();
+
+    assert_eq!(code, 0);
+    assert_eq!(
+        *log.borrow(),
+        ["init", "frame", "system", "frame", "system", "shutdown"]
+    );
+
+    // the app doesn't take ownership of the world
+    let e = world.entity();
+    assert!(e.is_alive());
+}
+
+#[test]
+fn world_app_run_action() {
+    let world = World::new();
+    let mut frames = 0;
+
+    let code = world
+        .app()
+        .on_frame(|world| {
+            frames += 1;
+            if frames == 3 {
+                world.quit();
+            }
+        })
+        .set_run_action(|runner| {
+            while runner.frame() {}
+            runner.exit_code() + 10
+        })
+        .run();
+
+    assert_eq!(code, 10);
+    assert_eq!(frames, 3);
+}
+
+#[test]
+fn world_app_on_frame_panic() {
+    let world = World::new();
+
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        world
+            .app()
+            .set_frames(5)
+            .on_frame(|_| panic!("frame failed"))
+            .run();
+    }));
+    let payload = result.unwrap_err();
+    assert_eq!(payload.downcast_ref::<&str>(), Some(&"frame failed"));
+
+    // the world can run another app after the panic
+    let mut frames = 0;
+    world.app().set_frames(2).on_frame(|_| frames += 1).run();
+    assert_eq!(frames, 2);
+}
@@ -8032,6 +8032,133 @@ extern "C" {
         table: *mut ecs_table_t,
     ) -> i32;
 }
+extern "C" {
+    pub fn ecs_rust_iter_field_changed(it: *mut ecs_iter_t, field: i32) -> bool;
+}
+extern "C" {
+    pub fn ecs_rust_iter_mark_written(it: *mut ecs_iter_t, fields: ecs_flags32_t);
+}
+#[doc = "Statistics of a query cache, returned by ecs_rust_query_cache_info()."]
+#[repr(C)]
+#[derive(Debug, Default, Copy, Clone)]
+pub struct ecs_rust_query_cache_info_t {
+    #[doc = "< Number of results of non-empty tables"]
+    pub result_count: i32,
+    #[doc = "< Number of entities in matched tables"]
+    pub entity_count: i32,
+    #[doc = "< Number of matched non-empty tables"]
+    pub table_count: i32,
+    #[doc = "< Number of matched empty tables"]
+    pub empty_table_count: i32,
+    #[doc = "< Number of groups (if group_by is used)"]
+    pub group_count: i32,
+    #[doc = "< How often tables have been (un)matched"]
+    pub match_count: i32,
+    #[doc = "< How often the cache has been rematched"]
+    pub rematch_count: i32,
+    #[doc = "< Approximate memory used by the cache in bytes"]
+    pub memory: i64,
+}
+pub const ecs_rust_merge_reason_t_EcsRustMergeNone: ecs_rust_merge_reason_t = 0;
+pub const ecs_rust_merge_reason_t_EcsRustMergeComponent: ecs_rust_merge_reason_t = 1;
+pub const ecs_rust_merge_reason_t_EcsRustMergeThreading: ecs_rust_merge_reason_t = 2;
+pub const ecs_rust_merge_reason_t_EcsRustMergeImmediate: ecs_rust_merge_reason_t = 3;
+#[doc = "Reason why a pipeline inserted a merge (sync point) before an operation."]
+pub type ecs_rust_merge_reason_t = ::core::ffi::c_uint;
+#[doc = "Operation of a pipeline schedule, returned by ecs_rust_pipeline_get_ops()."]
+#[repr(C)]
+#[derive(Debug, Default, Copy, Clone)]
+pub struct ecs_rust_pipeline_op_t {
+    #[doc = "< Index of the first system of the operation"]
+    pub offset: i32,
+    #[doc = "< Number of systems in the operation"]
+    pub count: i32,
+    #[doc = "< Whether the systems run multi threaded"]
+    pub multi_threaded: bool,
+    #[doc = "< Whether the systems run in immediate mode"]
+    pub immediate: bool,
+    #[doc = "< Why the merge before the operation was inserted"]
+    pub merge_reason: i32,
+    #[doc = "< System that caused the merge"]
+    pub merge_system: ecs_entity_t,
+    #[doc = "< Component that caused the merge"]
+    pub merge_id: ecs_id_t,
+}
+extern "C" {
+    pub fn ecs_rust_query_cache_info(
+        query: *const ecs_query_t,
+        info: *mut ecs_rust_query_cache_info_t,
+    ) -> bool;
+}
+extern "C" {
+    pub fn ecs_rust_query_group_by_ctx(
+        query: *const ecs_query_t,
+        ctx_free: *mut ecs_ctx_free_t,
+    ) -> *mut ::core::ffi::c_void;
+}
+extern "C" {
+    pub fn ecs_rust_pipeline_schedule(
+        world: *mut ecs_world_t,
+        pipeline: ecs_entity_t,
+        systems: *mut *const ecs_entity_t,
+        system_count: *mut i32,
+    ) -> i32;
+}
+extern "C" {
+    pub fn ecs_rust_pipeline_get_ops(
+        world: *mut ecs_world_t,
+        pipeline: ecs_entity_t,
+        ops: *mut ecs_rust_pipeline_op_t,
+        op_count: i32,
+    ) -> i32;
+}
+extern "C" {
+    pub fn ecs_rust_ordered_pipeline_init(
+        world: *mut ecs_world_t,
+        startup: bool,
+        order_by: ecs_order_by_action_t,
+    ) -> ecs_entity_t;
+}
+extern "C" {
+    #[doc = "Runs the startup systems like the first frame of ecs_progress() does, and\n sorts the systems of a phase with order_by if it isn't NULL."]
+    pub fn ecs_rust_run_startup_systems(world: *mut ecs_world_t, order_by: ecs_order_by_action_t);
+}
+extern "C" {
+    pub fn ecs_rust_system_order_changed(world: *mut ecs_world_t, system: ecs_entity_t);
+}
+extern "C" {
+    pub fn ecs_rust_get_component_version(
+        world: *const ecs_world_t,
+        entity: ecs_entity_t,
+        id: ecs_id_t,
+        table_id: *mut u64,
+        version: *mut i32,
+    ) -> bool;
+}
+extern "C" {
+    #[doc = "Returns the observers that flecs notifies for each term of a multi term\n observer, or 0 if the observer has a single term and is notified itself."]
+    pub fn ecs_rust_observer_children(
+        observer: *const ecs_observer_t,
+        children: *mut *const *mut ecs_observer_t,
+    ) -> i32;
+}
+extern "C" {
+    #[doc = "Notifies an observer, or the observer of a term of a multi term observer,\n of the event of an observer with the same term. Used to invoke observers in\n order. The last event that the observer handled is passed in and out with\n last_event_id, so that an event that arrives for multiple terms is handled\n once."]
+    pub fn ecs_rust_observer_invoke(
+        it: *const ecs_iter_t,
+        observer: *mut ecs_observer_t,
+        notified: *mut ecs_observer_t,
+        last_event_id: *mut i32,
+    );
+}
+extern "C" {
+    #[doc = "Returns whether an observer for event is registered for id, or for a\n wildcard that matches id."]
+    pub fn ecs_rust_has_observers(
+        world: *const ecs_world_t,
+        event: ecs_entity_t,
+        id: ecs_id_t,
+    ) -> bool;
+}
 #[repr(C)]
 #[derive(Debug, Copy, Clone)]
 pub struct ecs_event_id_record_t {
@@ -102,3 +102,539 @@ error:
     return -1;
 }
 
+
+bool ecs_rust_iter_field_changed(
+    ecs_iter_t *it,
+    int32_t field)
+{
+    ecs_check(it != NULL, ECS_INVALID_PARAMETER, NULL);
+
+    /* Only query iterators keep track of changes, treat everything else as
+     * changed. */
+    if (it->next != ecs_query_next) {
+        return true;
+    }
+
+    ecs_check(ECS_BIT_IS_SET(it->flags, EcsIterIsValid),
+        ECS_INVALID_PARAMETER, NULL);
+
+    ecs_query_iter_t *qit = &it->priv_.iter.query;
+    ecs_query_impl_t *impl = flecs_query_impl(qit->query);
+    ecs_query_t *q = &impl->pub;
+    ecs_check(field >= 0 && field < q->field_count,
+        ECS_INVALID_PARAMETER, NULL);
+
+    if (q->fixed_fields & flecs_ito(uint32_t, 1 << field)) {
+        return flecs_query_check_fixed_monitor(impl);
+    }
+
+    /* Uncached queries don't have monitors to compare against */
+    if (!impl->cache) {
+        return true;
+    }
+
+    ecs_query_cache_table_match_t *qm =
+        (ecs_query_cache_table_match_t*)qit->prev;
+    ecs_check(qm != NULL, ECS_INVALID_PARAMETER, NULL);
+
+    /* Entities added to or removed from the table count as a change of every
+     * field of the table. */
+    if (flecs_query_check_match_monitor_term(impl, qm, 0)) {
+        return true;
+    }
+
+    return flecs_query_check_match_monitor_term(impl, qm, field + 1);
+error:
+    return true;
+}
+
+void ecs_rust_iter_mark_written(
+    ecs_iter_t *it,
+    ecs_termset_t fields)
+{
+    ecs_check(it != NULL, ECS_INVALID_PARAMETER, NULL);
+
+    if (it->next != ecs_query_next || (it->flags & EcsIterSkip)) {
+        return;
+    }
+
+    ecs_check(ECS_BIT_IS_SET(it->flags, EcsIterIsValid),
+        ECS_INVALID_PARAMETER, NULL);
+
+    ecs_query_iter_t *qit = &it->priv_.iter.query;
+    ecs_query_impl_t *impl = flecs_query_impl(qit->query);
+
+    /* Do the bookkeeping ecs_query_next would otherwise do for the current
+     * result, but only mark the provided fields as dirty. */
+    ecs_termset_t set_fields = it->set_fields;
+    it->set_fields &= fields;
+    flecs_query_mark_fields_dirty(impl, it);
+    it->set_fields = set_fields;
+
+    if (qit->prev && (impl->pub.flags & EcsQueryHasMonitor)) {
+        flecs_query_sync_match_monitor(impl, qit->prev);
+    }
+
+    it->flags |= EcsIterSkip;
+error:
+    return;
+}
+
+bool ecs_rust_query_cache_info(
+    const ecs_query_t *query,
+    ecs_rust_query_cache_info_t *info)
+{
+    ecs_check(query != NULL, ECS_INVALID_PARAMETER, NULL);
+    ecs_check(info != NULL, ECS_INVALID_PARAMETER, NULL);
+
+    ecs_os_zeromem(info);
+
+    ecs_query_impl_t *impl = flecs_query_impl(query);
+    ecs_query_cache_t *cache = impl->cache;
+    if (!cache) {
+        return false;
+    }
+
+    info->group_count = flecs_ito(int32_t, ecs_map_count(&cache->groups));
+    info->match_count = cache->match_count;
+    info->rematch_count = cache->rematch_count;
+
+    /* Count the matched tables and entities from the cache, without running
+     * the query. Approximate the memory used by the cache by adding up the
+     * allocations for each matched table. */
+    int64_t memory = ECS_SIZEOF(ecs_query_cache_t);
+    memory += info->group_count * ECS_SIZEOF(ecs_query_cache_table_list_t);
+    memory += ecs_vec_size(&cache->table_slices) *
+        ECS_SIZEOF(ecs_query_cache_table_match_t);
+
+    ecs_table_cache_iter_t it;
+    if (flecs_table_cache_all_iter(&cache->cache, &it)) {
+        ecs_query_cache_table_t *qt;
+        while ((qt = flecs_table_cache_next(&it, ecs_query_cache_table_t))) {
+            memory += ECS_SIZEOF(ecs_query_cache_table_t);
+
+            int32_t entity_count = ecs_table_count(qt->hdr.table);
+            if (entity_count) {
+                info->table_count ++;
+                info->entity_count += entity_count;
+            } else {
+                info->empty_table_count ++;
+            }
+
+            ecs_query_cache_table_match_t *qm;
+            for (qm = qt->first; qm != NULL; qm = qm->next_match) {
+                if (entity_count) {
+                    info->result_count ++;
+                }
+                memory += ECS_SIZEOF(ecs_query_cache_table_match_t);
+                memory += 2 * cache->allocators.columns.data_size;
+                memory += cache->allocators.ids.data_size;
+                memory += cache->allocators.sources.data_size;
+                memory += ecs_vec_size(&qm->refs) * ECS_SIZEOF(ecs_ref_t);
+                if (qm->monitor) {
+                    memory += cache->allocators.monitors.data_size;
+                }
+            }
+        }
+    }
+
+    info->memory = memory;
+    return true;
+error:
+    return false;
+}
+
+void* ecs_rust_query_group_by_ctx(
+    const ecs_query_t *query,
+    ecs_ctx_free_t *ctx_free)
+{
+    ecs_check(query != NULL, ECS_INVALID_PARAMETER, NULL);
+
+    ecs_query_impl_t *impl = flecs_query_impl(query);
+    ecs_query_cache_t *cache = impl->cache;
+    if (!cache) {
+        goto error;
+    }
+
+    if (ctx_free) {
+        *ctx_free = cache->group_by_ctx_free;
+    }
+
+    return cache->group_by_ctx;
+error:
+    if (ctx_free) {
+        *ctx_free = NULL;
+    }
+    return NULL;
+}
+
+#ifdef FLECS_PIPELINE
+int32_t ecs_rust_pipeline_schedule(
+    ecs_world_t *world,
+    ecs_entity_t pipeline,
+    const ecs_entity_t **systems,
+    int32_t *system_count)
+{
+    ecs_check(!(world->flags & EcsWorldReadonly), ECS_INVALID_OPERATION,
+        "cannot compute pipeline schedule while world is in readonly mode");
+
+    const EcsPipeline *p = ecs_get(world, pipeline, EcsPipeline);
+    ecs_check(p != NULL, ECS_INVALID_PARAMETER, "entity is not a pipeline");
+
+    /* Make sure the schedule reflects the systems that currently exist */
+    ecs_run_aperiodic(world, 0);
+    flecs_pipeline_build(world, p->state);
+
+    *systems = ecs_vec_first_t(&p->state->systems, ecs_entity_t);
+    *system_count = ecs_vec_count(&p->state->systems);
+    return ecs_vec_count(&p->state->ops);
+error:
+    *systems = NULL;
+    *system_count = 0;
+    return 0;
+}
+
+/* Same as flecs_pipeline_check_terms(), but also returns the id of the first
+ * term that requires a merge. */
+static
+bool flecs_rust_pipeline_check_terms(
+    ecs_world_t *world,
+    ecs_query_t *query,
+    bool is_active,
+    ecs_write_state_t *ws,
+    ecs_id_t *merge_id)
+{
+    bool needs_merge = false;
+    ecs_term_t *terms = query->terms;
+    int32_t t, term_count = query->term_count;
+
+    /* Check This terms first, same as the pipeline does */
+    int32_t pass;
+    for (pass = 0; pass < 2; pass ++) {
+        for (t = 0; t < term_count; t ++) {
+            ecs_term_t *term = &terms[t];
+            if (ecs_term_match_this(term) != !pass) {
+                continue;
+            }
+            if (flecs_pipeline_check_term(world, term, is_active, ws)) {
+                if (!needs_merge) {
+                    *merge_id = term->id;
+                }
+                needs_merge = true;
+            }
+        }
+    }
+
+    return needs_merge;
+}
+
+/* Replays flecs_pipeline_build() to find out why the merges between the
+ * operations of the pipeline were inserted. */
+static
+void flecs_rust_pipeline_merge_reasons(
+    ecs_world_t *world,
+    ecs_pipeline_state_t *pq,
+    ecs_rust_pipeline_op_t *ops,
+    int32_t op_count)
+{
+    ecs_allocator_t *a = &world->allocator;
+    ecs_write_state_t ws = {0};
+    ecs_map_init(&ws.ids, a);
+    ecs_map_init(&ws.wildcard_ids, a);
+
+    bool multi_threaded = false;
+    bool immediate = false;
+    bool first = true;
+    bool new_op = true;     /* Whether the next system starts an operation */
+    int32_t cur = -1;       /* Index of the current operation */
+    int32_t cur_count = 0;  /* Number of active systems in the operation */
+
+    ecs_iter_t it = ecs_query_iter(world, pq->query);
+    while (ecs_query_next(&it)) {
+        EcsPoly *poly = flecs_pipeline_term_system(&it);
+        bool is_active = ecs_table_get_type_index(
+            world, it.table, EcsEmpty) == -1;
+
+        int32_t i;
+        for (i = 0; i < it.count; i ++) {
+            ecs_system_t *sys = (ecs_system_t*)poly[i].poly;
+            ecs_query_t *q = sys->query;
+
+            ecs_id_t merge_id = 0;
+            bool needs_merge = flecs_rust_pipeline_check_terms(
+                world, q, is_active, &ws, &merge_id);
+            int32_t reason = needs_merge ? EcsRustMergeComponent : 0;
+
+            if (is_active) {
+                if (first) {
+                    multi_threaded = sys->multi_threaded;
+                    immediate = sys->immediate;
+                    first = false;
+                }
+
+                if (sys->multi_threaded != multi_threaded) {
+                    multi_threaded = sys->multi_threaded;
+                    reason = reason ? reason : EcsRustMergeThreading;
+                }
+                if (sys->immediate != immediate) {
+                    immediate = sys->immediate;
+                    reason = reason ? reason : EcsRustMergeImmediate;
+                }
+            }
+
+            if (immediate) {
+                reason = reason ? reason : EcsRustMergeImmediate;
+            }
+
+            if (reason) {
+                flecs_pipeline_reset_write_state(&ws);
+
+                /* An empty operation is reused for the next systems */
+                if (cur_count) {
+                    new_op = true;
+                }
+
+                if (is_active) {
+                    ecs_id_t unused = 0;
+                    flecs_rust_pipeline_check_terms(
+                        world, q, true, &ws, &unused);
+                }
+            }
+
+            if (new_op) {
+                new_op = false;
+                cur ++;
+                cur_count = 0;
+            }
+
+            if (reason && cur > 0 && cur < op_count) {
+                ops[cur].merge_reason = reason;
+                ops[cur].merge_system = it.entities[i];
+                ops[cur].merge_id = reason == EcsRustMergeComponent ? 
+                    merge_id : 0;
+            }
+
+            if (is_active) {
+                cur_count ++;
+            }
+        }
+    }
+
+    ecs_map_fini(&ws.ids);
+    ecs_map_fini(&ws.wildcard_ids);
+}
+
+int32_t ecs_rust_pipeline_get_ops(
+    ecs_world_t *world,
+    ecs_entity_t pipeline,
+    ecs_rust_pipeline_op_t *ops,
+    int32_t op_count)
+{
+    const EcsPipeline *p = ecs_get(world, pipeline, EcsPipeline);
+    ecs_check(p != NULL, ECS_INVALID_PARAMETER, "entity is not a pipeline");
+
+    ecs_pipeline_state_t *pq = p->state;
+    int32_t i, count = ecs_vec_count(&pq->ops);
+    if (count > op_count) {
+        count = op_count;
+    }
+
+    for (i = 0; i < count; i ++) {
+        const ecs_pipeline_op_t *src = ecs_vec_get_t(
+            &pq->ops, ecs_pipeline_op_t, i);
+        ops[i] = (ecs_rust_pipeline_op_t){
+            .offset = src->offset,
+            .count = src->count,
+            .multi_threaded = src->multi_threaded,
+            .immediate = src->immediate
+        };
+    }
+
+    flecs_rust_pipeline_merge_reasons(world, pq, ops, count);
+    return count;
+error:
+    return 0;
+}
+
+ecs_entity_t ecs_rust_ordered_pipeline_init(
+    ecs_world_t *world,
+    bool startup,
+    ecs_order_by_action_t order_by)
+{
+    /* Same query as the builtin and startup pipelines, with a term for the
+     * system object that is passed to order_by */
+    return ecs_pipeline_init(world, &(ecs_pipeline_desc_t){
+        .query = {
+            .terms = {
+                { .id = EcsSystem },
+                { .id = EcsPhase, .src.id = EcsCascade, .trav = EcsDependsOn },
+                { .id = ecs_dependson(EcsOnStart), .trav = EcsDependsOn, 
+                    .oper = startup ? EcsAnd : EcsNot },
+                { .id = EcsDisabled, .src.id = EcsUp, .trav = EcsDependsOn, .oper = EcsNot },
+                { .id = EcsDisabled, .src.id = EcsUp, .trav = EcsChildOf, .oper = EcsNot },
+                { .id = ecs_pair(ecs_id(EcsPoly), EcsSystem), .inout = EcsIn }
+            },
+            .order_by = ecs_pair(ecs_id(EcsPoly), EcsSystem),
+            .order_by_callback = order_by
+        }
+    });
+}
+
+/* Copy of static flecs_run_startup_systems() of flecs v4.0.0, except that the
+ * startup pipeline is created with ecs_rust_ordered_pipeline_init(), which
+ * sorts the systems of a phase with order_by. The log statements are left out.
+ * Keep in sync with upstream when updating flecs. */
+void ecs_rust_run_startup_systems(
+    ecs_world_t *world,
+    ecs_order_by_action_t order_by)
+{
+    if (!order_by) {
+        flecs_run_startup_systems(world);
+        return;
+    }
+
+    ecs_id_record_t *idr = flecs_id_record_get(world, 
+        ecs_dependson(EcsOnStart));
+    if (!idr || !flecs_table_cache_count(&idr->cache)) {
+        return;
+    }
+
+    int32_t stage_count = world->stage_count;
+    world->stage_count = 1; /* Prevents running startup systems on workers */
+
+    ecs_entity_t start_pip = ecs_rust_ordered_pipeline_init(
+        world, true, order_by);
+    ecs_assert(start_pip != 0, ECS_INTERNAL_ERROR, NULL);
+    const EcsPipeline *p = ecs_get(world, start_pip, EcsPipeline);
+    ecs_check(p != NULL, ECS_INVALID_OPERATION, 
+        "pipeline entity is missing flecs.pipeline.Pipeline component");
+    flecs_workers_progress(world, p->state, 0);
+
+    ecs_delete(world, start_pip);
+    world->stage_count = stage_count;
+error:
+    return;
+}
+#endif
+
+#ifdef FLECS_SYSTEM
+void ecs_rust_system_order_changed(
+    ecs_world_t *world,
+    ecs_entity_t system)
+{
+    /* Pipelines that sort systems by their order read it through the system
+     * object, mark it as dirty so that pipeline queries sort systems again. */
+    ecs_record_t *r = flecs_entities_get(world, system);
+    if (r && r->table) {
+        flecs_table_mark_dirty(world, r->table, 
+            ecs_pair(ecs_id(EcsPoly), EcsSystem));
+    }
+}
+#endif
+
+bool ecs_rust_get_component_version(
+    const ecs_world_t *world,
+    ecs_entity_t entity,
+    ecs_id_t id,
+    uint64_t *table_id,
+    int32_t *version)
+{
+    world = ecs_get_world(world);
+
+    ecs_record_t *r = flecs_entities_get(world, entity);
+    if (!r || !r->table) {
+        return false;
+    }
+
+    ecs_table_t *table = r->table;
+    const ecs_table_record_t *tr = flecs_table_record_get(world, table, id);
+    if (!tr || tr->column == -1) {
+        return false;
+    }
+
+    /* The dirty state of a table is created on demand, after which changes to
+     * its components increase the version of their column. */
+    int32_t *dirty_state = flecs_table_get_dirty_state(
+        ECS_CONST_CAST(ecs_world_t*, world), table);
+    *table_id = table->id;
+    *version = dirty_state[tr->column + 1];
+    return true;
+}
+
+
+int32_t ecs_rust_observer_children(
+    const ecs_observer_t *observer,
+    ecs_observer_t *const **children)
+{
+    ecs_observer_impl_t *impl = flecs_observer_impl(
+        ECS_CONST_CAST(ecs_observer_t*, observer));
+    if (!(impl->flags & EcsObserverIsMulti)) {
+        *children = NULL;
+        return 0;
+    }
+
+    *children = ecs_vec_first_t(&impl->children, ecs_observer_t*);
+    return ecs_vec_count(&impl->children);
+}
+
+void ecs_rust_observer_invoke(
+    const ecs_iter_t *it,
+    ecs_observer_t *observer,
+    ecs_observer_t *notified,
+    int32_t *last_event_id)
+{
+    ecs_observer_impl_t *impl = flecs_observer_impl(observer);
+    ecs_iter_t notified_it = *it;
+
+    /* The event of the iterator was reversed for a Not term, restore an event
+     * that is reversed to the same event for the notified observer. */
+    const ecs_term_t *term = &it->query->terms[0];
+    if (term->oper == EcsNot) {
+        if (it->event == EcsOnRemove) {
+            notified_it.event = EcsOnAdd;
+        } else if (it->event == EcsOnAdd) {
+            notified_it.event = EcsOnRemove;
+        }
+    }
+
+    /* flecs filters out events that a multi term observer already handled.
+     * The observer is also notified by flecs itself, which doesn't invoke it,
+     * so the last event it handled is tracked by the caller. */
+    if (impl->last_event_id) {
+        impl->last_event_id[0] = *last_event_id;
+    }
+
+    flecs_uni_observer_invoke(it->real_world, notified, &notified_it,
+        it->table, 0, flecs_is_simple_result(&notified_it));
+
+    if (impl->last_event_id) {
+        *last_event_id = impl->last_event_id[0];
+    }
+}
+
+bool ecs_rust_has_observers(
+    const ecs_world_t *world,
+    ecs_entity_t event,
+    ecs_id_t id)
+{
+    world = ecs_get_world(world);
+    ecs_observable_t *observable = ECS_CONST_CAST(ecs_observable_t*,
+        &world->observable);
+
+    if (flecs_observers_exist(observable, id, event) ||
+        flecs_observers_exist(observable, EcsWildcard, event) ||
+        flecs_observers_exist(observable, EcsAny, event))
+    {
+        return true;
+    }
+
+    if (ECS_IS_PAIR(id)) {
+        ecs_entity_t first = ECS_PAIR_FIRST(id);
+        ecs_entity_t second = ECS_PAIR_SECOND(id);
+        return flecs_observers_exist(observable, ecs_pair(first, EcsWildcard), event) ||
+            flecs_observers_exist(observable, ecs_pair(EcsWildcard, second), event) ||
+            flecs_observers_exist(observable, ecs_pair(EcsWildcard, EcsWildcard), event);
+    }
+
+    return false;
+}
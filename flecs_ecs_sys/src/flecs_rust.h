@@ -1,5 +1,36 @@
 #include "flecs.h"
 
+/** Statistics of a query cache, returned by ecs_rust_query_cache_info(). */
+typedef struct ecs_rust_query_cache_info_t {
+    int32_t result_count;       /**< Number of results of non-empty tables */
+    int32_t entity_count;       /**< Number of entities in matched tables */
+    int32_t table_count;        /**< Number of matched non-empty tables */
+    int32_t empty_table_count;  /**< Number of matched empty tables */
+    int32_t group_count;        /**< Number of groups (if group_by is used) */
+    int32_t match_count;        /**< How often tables have been (un)matched */
+    int32_t rematch_count;      /**< How often the cache has been rematched */
+    int64_t memory;             /**< Approximate memory used by the cache in bytes */
+} ecs_rust_query_cache_info_t;
+
+/** Reason why a pipeline inserted a merge (sync point) before an operation. */
+typedef enum ecs_rust_merge_reason_t {
+    EcsRustMergeNone,       /**< No merge, first operation of the pipeline */
+    EcsRustMergeComponent,  /**< A system reads a component written to the stage */
+    EcsRustMergeThreading,  /**< A system switches between single and multi threaded */
+    EcsRustMergeImmediate   /**< A system switches to or from immediate mode */
+} ecs_rust_merge_reason_t;
+
+/** Operation of a pipeline schedule, returned by ecs_rust_pipeline_get_ops(). */
+typedef struct ecs_rust_pipeline_op_t {
+    int32_t offset;             /**< Index of the first system of the operation */
+    int32_t count;              /**< Number of systems in the operation */
+    bool multi_threaded;        /**< Whether the systems run multi threaded */
+    bool immediate;             /**< Whether the systems run in immediate mode */
+    int32_t merge_reason;       /**< Why the merge before the operation was inserted */
+    ecs_entity_t merge_system;  /**< System that caused the merge */
+    ecs_id_t merge_id;          /**< Component that caused the merge */
+} ecs_rust_pipeline_op_t;
+
 FLECS_API
 void* ecs_rust_mut_get_id(
     const ecs_world_t *world,
@@ -22,3 +53,95 @@ FLECS_API
     ecs_id_t id,
     ecs_table_t* table);
 
+
+FLECS_API
+bool ecs_rust_iter_field_changed(
+    ecs_iter_t *it,
+    int32_t field);
+
+FLECS_API
+void ecs_rust_iter_mark_written(
+    ecs_iter_t *it,
+    ecs_termset_t fields);
+
+FLECS_API
+bool ecs_rust_query_cache_info(
+    const ecs_query_t *query,
+    ecs_rust_query_cache_info_t *info);
+
+FLECS_API
+void* ecs_rust_query_group_by_ctx(
+    const ecs_query_t *query,
+    ecs_ctx_free_t *ctx_free);
+
+#ifdef FLECS_PIPELINE
+FLECS_API
+int32_t ecs_rust_pipeline_schedule(
+    ecs_world_t *world,
+    ecs_entity_t pipeline,
+    const ecs_entity_t **systems,
+    int32_t *system_count);
+
+FLECS_API
+int32_t ecs_rust_pipeline_get_ops(
+    ecs_world_t *world,
+    ecs_entity_t pipeline,
+    ecs_rust_pipeline_op_t *ops,
+    int32_t op_count);
+
+FLECS_API
+ecs_entity_t ecs_rust_ordered_pipeline_init(
+    ecs_world_t *world,
+    bool startup,
+    ecs_order_by_action_t order_by);
+
+/** Runs the startup systems like the first frame of ecs_progress() does, and
+ * sorts the systems of a phase with order_by if it isn't NULL. */
+FLECS_API
+void ecs_rust_run_startup_systems(
+    ecs_world_t *world,
+    ecs_order_by_action_t order_by);
+#endif
+
+#ifdef FLECS_SYSTEM
+FLECS_API
+void ecs_rust_system_order_changed(
+    ecs_world_t *world,
+    ecs_entity_t system);
+#endif
+
+FLECS_API
+bool ecs_rust_get_component_version(
+    const ecs_world_t *world,
+    ecs_entity_t entity,
+    ecs_id_t id,
+    uint64_t *table_id,
+    int32_t *version);
+
+
+/** Returns the observers that flecs notifies for each term of a multi term
+ * observer, or 0 if the observer has a single term and is notified itself. */
+FLECS_API
+int32_t ecs_rust_observer_children(
+    const ecs_observer_t *observer,
+    ecs_observer_t *const **children);
+
+/** Notifies an observer, or the observer of a term of a multi term observer,
+ * of the event of an observer with the same term. Used to invoke observers in
+ * order. The last event that the observer handled is passed in and out with
+ * last_event_id, so that an event that arrives for multiple terms is handled
+ * once. */
+FLECS_API
+void ecs_rust_observer_invoke(
+    const ecs_iter_t *it,
+    ecs_observer_t *observer,
+    ecs_observer_t *notified,
+    int32_t *last_event_id);
+
+/** Returns whether an observer for event is registered for id, or for a
+ * wildcard that matches id. */
+FLECS_API
+bool ecs_rust_has_observers(
+    const ecs_world_t *world,
+    ecs_entity_t event,
+    ecs_id_t id);